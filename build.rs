@@ -0,0 +1,28 @@
+fn main() {
+    let proto = "proto/text_modifier.proto";
+    println!("cargo:rerun-if-changed={}", proto);
+
+    // The gRPC service is generated from the proto file, parsed without protoc
+    #[cfg(feature = "grpc")]
+    {
+        let descriptors = protox::compile([proto], ["proto"]).expect("invalid proto file");
+        tonic_prost_build::configure()
+            .build_client(false)
+            .compile_fds(descriptors)
+            .expect("failed to generate the gRPC service");
+    }
+
+    // The C header is kept in the repository for C and C++ users without cargo
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        cbindgen::Builder::new()
+            .with_src("src/ffi.rs")
+            .with_language(cbindgen::Language::C)
+            .with_include_guard("TEXT_MODIFIER_H")
+            .with_autogen_warning("/* Generated by cbindgen from src/ffi.rs, do not edit */")
+            .generate()
+            .expect("failed to generate the C header")
+            .write_to_file("include/text_modifier.h");
+    }
+}
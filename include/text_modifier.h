@@ -0,0 +1,38 @@
+#ifndef TEXT_MODIFIER_H
+#define TEXT_MODIFIER_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Runs the built-in operation `op_name`, e.g. "slugify", on `input`.
+ *
+ * Returns the output, to be released with `tm_free`, or NULL when the
+ * operation failed, with the message in `tm_last_error`.
+ *
+ * # Safety
+ *
+ * `op_name` and `input` must be NUL-terminated strings.
+ */
+char *tm_transform(const char *op_name, const char *input);
+
+/**
+ * Releases a string returned by `tm_transform`. NULL is ignored.
+ *
+ * # Safety
+ *
+ * `text` must come from `tm_transform` and not have been released before.
+ */
+void tm_free(char *text);
+
+/**
+ * The message of the last `tm_transform` on this thread that failed, or NULL
+ * when it succeeded. Valid until the next `tm_transform` on the thread.
+ */
+const char *tm_last_error(void);
+
+#endif  /* TEXT_MODIFIER_H */
@@ -0,0 +1,133 @@
+## Command line
+
+available-operations = Dostupné operace:
+error = Chyba: { $message }
+error-processing = Chyba při zpracování vstupu: { $message }
+warning = Varování: { $message }
+config-invalid = Neplatný konfigurační soubor { $path }: { $message }
+expected-format = Očekávaný formát: <operace> <vstup>
+dropped-command = Varování: příkaz na řádku { $line } byl zahozen, fronta je plná
+error-sending = Chyba při odesílání příkazu: { $message }
+ctrl-c-unavailable = Varování: nelze zachytit Ctrl-C: { $message }
+stopping = Dokončuji rozpracované příkazy, dalším Ctrl-C ukončíte program okamžitě
+jsonrpc-arguments = Chyba: --jsonrpc čte požadavky ze standardního vstupu, odeberte argumenty
+client-usage = client vyžaduje <operace> <vstup>
+bench-usage = bench vyžaduje <operace> [--iterations N] [--warmup N] <vstup>
+bench-result = { $operation }: { $iterations } běhů, min { $min }, průměr { $mean }, p99 { $p99 }, { $throughput } MiB/s
+output-checksum = SHA-256 výstupu: { $hash }
+audit-open-failed = Nelze otevřít auditní log { $path }: { $reason }
+audit-write-failed = Varování: zápis do auditního logu selhal: { $reason }
+stats-usage = stats nepřijímá žádné argumenty kromě --reset
+stats-empty = Zatím nebyly započítány žádné operace
+stats-no-config-dir = Statistiky použití potřebují k uložení HOME nebo XDG_CONFIG_HOME
+stats-read-failed = Nepodařilo se načíst statistiky použití { $path }: { $reason }
+stats-save-failed = Varování: nepodařilo se uložit statistiky použití { $path }: { $reason }
+stats-reset-failed = Nepodařilo se vynulovat statistiky použití { $path }: { $reason }
+stats-operation = Operace
+stats-runs = Spuštění
+stats-total = Celkový čas
+stats-mean = Průměrný čas
+confirm-overwrite = { $operation } se chystá přepsat soubory: { $count }
+confirm-more = a dalších { $count }
+confirm-question = Přepsat je? [a/N]
+confirm-yes = ano
+confirm-needs-yes = Nic nebylo přepsáno, chybí terminál pro potvrzení, s --yes se soubory přepíšou bez ptaní
+hook-failed = Varování: hook { $hook } selhal: { $reason }
+
+## REPL
+
+repl-recording = Nahrávám makro { $name }, :stop ho uloží
+repl-recorded = Makro { $name } uloženo, počet příkazů: { $count }
+repl-already-recording = Makro { $name } se už nahrává
+repl-not-recording = Žádné makro se nenahrává, začněte příkazem :record <název>
+repl-unknown-macro = Makro { $name } neexistuje
+repl-invalid-name = Neplatný název makra: { $name }, použijte písmena, číslice, - a _
+repl-no-config-dir = K uložení maker je potřeba HOME nebo XDG_CONFIG_HOME
+repl-save-failed = Nelze uložit makro { $name }: { $reason }
+repl-not-saved = Makro { $name } nebylo uloženo, zůstává dříve nahrané
+repl-in-macro = Makra nemohou používat { $command }
+repl-set-usage = Očekávaný formát: :set <název> = <operace> <vstup>
+repl-cache-stats = Mezipaměť: { $entries } záznamů, { $bytes } bajtů, { $hits } zásahů, { $misses } výpadků
+repl-cache-cleared = Mezipaměť vyprázdněna
+repl-cache-off = Mezipaměť je vypnutá, bylo zadáno --no-cache
+repl-cache-usage = Očekáváno :cache stats nebo :cache clear
+repl-unknown-command = Neznámý příkaz: { $command }, dostupné příkazy jsou: :set, :record, :stop, :play, :cache
+
+## Options
+
+unknown-option = Neznámá volba: { $option }
+adaptive-chunk-size = --adaptive určuje velikost bloků sám, vynechte --chunk-lines a --chunk-bytes
+option-requires-value = Volba { $option } vyžaduje hodnotu
+invalid-value = Neplatná hodnota pro { $option }: { $value }
+invalid-backpressure = Neplatný backpressure: { $value }, dostupné možnosti jsou: block, drop
+unknown-language = Nepodporovaný jazyk: { $value }, dostupné jazyky jsou: en, cs, de
+
+## Errors of operations
+
+invalid-operation = Neplatná operace: { $input }
+invalid-operation-suggestions = Neplatná operace: { $input }, nemysleli jste { $suggestions }?
+failed-validation = Neplatná hodnota { $value }: { $reason }
+or = nebo
+timeout = Vypršel časový limit { $limit }
+read-failed = Nelze přečíst '{ $path }': { $reason }
+plugin-failed = Nelze načíst plugin '{ $path }': { $reason }
+too-large = Vstup překračuje limit { $limit } bajtů
+too-many-requests = Příliš mnoho požadavků: { $reason }
+
+## Operations
+
+operation-anagram-key = Seřadí písmena každého slova, takže anagramy dostanou stejný klíč
+operation-analyze = Vypíše podíly tříd znaků, písma a hrubý odhad jazyka
+operation-camelcase = Převede text do formátu camelCase
+operation-crlf = Převede všechny konce řádků na CRLF
+operation-csv = Zobrazí CSV soubor jako tabulku
+operation-csv-apply = Použije textovou operaci na hodnoty sloupce
+operation-csv-clean-headers = Upraví názvy sloupců pro import do databáze
+operation-csv-dedupe = Odstraní duplicitní řádky
+operation-csv-diff = Vypíše přidané, odebrané a změněné řádky mezi dvěma CSV soubory
+operation-csv-groupby = Seskupí řádky a agreguje každou skupinu
+operation-csv-join = Spojí dva CSV soubory podle klíčových sloupců
+operation-csv-stats = Počty, minimum a maximum, průměr a součet každého sloupce
+operation-csv-transpose = Prohodí řádky a sloupce
+operation-csv-validate = Vypíše chybné řádky s čísly řádků
+operation-csv2json = Převede CSV na JSON pole objektů
+operation-csv2sql = Převede CSV na příkazy CREATE TABLE a INSERT
+operation-detect-encoding = Odhadne kódování souboru a jak jistý je odhad
+operation-detect-eol = Zjistí konce řádků souboru a zda jsou smíšené
+operation-email-normalize = Ověří e-mailovou adresu a upraví ji pro porovnávání
+operation-escape = Zapíše konce řádků, tabulátory, uvozovky a řídicí znaky jako escape sekvence se zpětným lomítkem
+operation-extract-emails = Vypíše e-mailové adresy nalezené v textu, jednu na řádek
+operation-extract-urls = Vypíše URL nalezené v textu, jednu na řádek
+operation-filter-dictionary = Vypíše nebo odstraní slova textu, která chybí v seznamu slov
+operation-frontmatter = Vypíše YAML frontmatter Markdown souboru jako JSON
+operation-frontmatter-apply = Použije textovou operaci na pole frontmatteru Markdown souborů
+operation-hexdump = Zobrazí bajty souboru jako posuny, hexadecimální hodnoty a ASCII
+operation-iban-format = Ověří IBAN a rozdělí ho do skupin po čtyřech znacích
+operation-is-palindrome = Zjistí, zda se text čte pozpátku stejně, bez ohledu na velikost písmen, diakritiku a interpunkci
+operation-jget = Vypíše hodnotu na JSON Pointeru nebo cestě
+operation-json-pretty = Přeformátuje JSON, odsazený nebo kompaktní
+operation-json2csv = Zploští JSON objekty do CSV
+operation-json2yaml = Převede JSON na YAML
+operation-lf = Převede všechny konce řádků na LF
+operation-lint-text = Označí zdvojená slova, mezery na konci řádků, smíšené odsazení a dlouhé řádky
+operation-lowercase = Převede text na malá písmena
+operation-luhn-check = Ověří čísla karet a jiná čísla kontrolním součtem Luhn
+operation-md-headings = Vypíše nadpisy Markdown souboru s jejich úrovněmi
+operation-md-links = Vypíše URL odkazů a obrázků v Markdown souboru
+operation-md-toc = Vytvoří obsah Markdown souboru
+operation-no-spaces = Odstraní z textu všechny mezery
+operation-phone = Načte telefonní číslo a zapíše ho ve formátu E.164 nebo jiném
+operation-redact = Zamaskuje e-maily, telefonní čísla a čísla karet
+operation-script = Upraví text skriptem v jazyce rhai
+operation-sentences = Rozdělí text na jednu větu na řádek
+operation-slugify = Vytvoří slug vhodný do URL
+operation-snakecase = Převede text do formátu snake_case
+operation-sort-lines = Seřadí řádky souboru, příliš velký soubor přes dočasné soubory
+operation-toml2json = Převede TOML na JSON
+operation-unescape = Převede escape sekvence se zpětným lomítkem, např. \n nebo \t, na znaky
+operation-uppercase = Převede text na VELKÁ PÍSMENA
+operation-words = Rozdělí text na jedno slovo na řádek
+operation-xml-escape = Nahradí znaky se zvláštním významem v XML entitami
+operation-xml-pretty = Přeformátuje XML s jednotným odsazením
+operation-xml-unescape = Nahradí XML entity a číselné odkazy na znaky
+operation-yaml2json = Převede YAML na JSON
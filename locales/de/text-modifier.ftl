@@ -0,0 +1,133 @@
+## Command line
+
+available-operations = Verfügbare Operationen:
+error = Fehler: { $message }
+error-processing = Fehler beim Verarbeiten der Eingabe: { $message }
+warning = Warnung: { $message }
+config-invalid = Ungültige Konfigurationsdatei { $path }: { $message }
+expected-format = Erwartetes Format: <Operation> <Eingabe>
+dropped-command = Warnung: Befehl in Zeile { $line } verworfen, die Warteschlange ist voll
+error-sending = Fehler beim Senden des Befehls: { $message }
+ctrl-c-unavailable = Warnung: Strg+C kann nicht abgefangen werden: { $message }
+stopping = Laufende Befehle werden noch beendet, erneutes Strg+C bricht sofort ab
+jsonrpc-arguments = Fehler: --jsonrpc liest die Anfragen von der Standardeingabe, bitte die Argumente weglassen
+client-usage = client erwartet <Operation> <Eingabe>
+bench-usage = bench erwartet <Operation> [--iterations N] [--warmup N] <Eingabe>
+bench-result = { $operation }: { $iterations } Läufe, min { $min }, Mittel { $mean }, p99 { $p99 }, { $throughput } MiB/s
+output-checksum = SHA-256 der Ausgabe: { $hash }
+audit-open-failed = Das Audit-Log { $path } kann nicht geöffnet werden: { $reason }
+audit-write-failed = Warnung: Schreiben in das Audit-Log fehlgeschlagen: { $reason }
+stats-usage = stats nimmt außer --reset keine Argumente
+stats-empty = Noch keine Operationen gezählt
+stats-no-config-dir = Nutzungsstatistiken brauchen HOME oder XDG_CONFIG_HOME zum Speichern
+stats-read-failed = Die Nutzungsstatistik { $path } konnte nicht gelesen werden: { $reason }
+stats-save-failed = Warnung: Die Nutzungsstatistik { $path } konnte nicht gespeichert werden: { $reason }
+stats-reset-failed = Die Nutzungsstatistik { $path } konnte nicht zurückgesetzt werden: { $reason }
+stats-operation = Operation
+stats-runs = Läufe
+stats-total = Gesamtzeit
+stats-mean = Mittlere Zeit
+confirm-overwrite = { $operation } überschreibt gleich Dateien: { $count }
+confirm-more = und { $count } weitere
+confirm-question = Überschreiben? [j/N]
+confirm-yes = ja
+confirm-needs-yes = Nichts wurde überschrieben, es gibt kein Terminal zum Bestätigen, mit --yes werden Dateien ohne Nachfrage überschrieben
+hook-failed = Warnung: Der Hook { $hook } ist fehlgeschlagen: { $reason }
+
+## REPL
+
+repl-recording = Makro { $name } wird aufgezeichnet, :stop speichert es
+repl-recorded = Makro { $name } mit { $count } Befehlen gespeichert
+repl-already-recording = Makro { $name } wird bereits aufgezeichnet
+repl-not-recording = Es wird kein Makro aufgezeichnet, starten Sie eines mit :record <Name>
+repl-unknown-macro = Kein Makro namens { $name }
+repl-invalid-name = Ungültiger Makroname: { $name }, erlaubt sind Buchstaben, Ziffern, - und _
+repl-no-config-dir = Zum Speichern von Makros wird HOME oder XDG_CONFIG_HOME benötigt
+repl-save-failed = Makro { $name } konnte nicht gespeichert werden: { $reason }
+repl-not-saved = Makro { $name } wurde nicht gespeichert, das zuvor aufgenommene bleibt
+repl-in-macro = Makros können { $command } nicht verwenden
+repl-set-usage = Erwartet wird :set <Name> = <Operation> <Eingabe>
+repl-cache-stats = Cache: { $entries } Einträge, { $bytes } Bytes, { $hits } Treffer, { $misses } Fehlschläge
+repl-cache-cleared = Cache geleert
+repl-cache-off = Der Cache ist aus, --no-cache wurde angegeben
+repl-cache-usage = Erwartet :cache stats oder :cache clear
+repl-unknown-command = Unbekannter Befehl: { $command }, verfügbare Befehle sind: :set, :record, :stop, :play, :cache
+
+## Options
+
+unknown-option = Unbekannte Option: { $option }
+adaptive-chunk-size = --adaptive bestimmt die Blockgröße selbst, lassen Sie --chunk-lines und --chunk-bytes weg
+option-requires-value = Die Option { $option } erwartet einen Wert
+invalid-value = Ungültiger Wert für { $option }: { $value }
+invalid-backpressure = Ungültiger Backpressure-Modus: { $value }, verfügbar sind: block, drop
+unknown-language = Nicht unterstützte Sprache: { $value }, verfügbar sind: en, cs, de
+
+## Errors of operations
+
+invalid-operation = Ungültige Operation: { $input }
+invalid-operation-suggestions = Ungültige Operation: { $input }, meinten Sie { $suggestions }?
+failed-validation = Ungültiger Wert { $value }: { $reason }
+or = oder
+timeout = Zeitlimit von { $limit } überschritten
+read-failed = '{ $path }' konnte nicht gelesen werden: { $reason }
+plugin-failed = Plugin '{ $path }' konnte nicht geladen werden: { $reason }
+too-large = Die Eingabe überschreitet das Limit von { $limit } Bytes
+too-many-requests = Zu viele Anfragen: { $reason }
+
+## Operations
+
+operation-anagram-key = Sortiert die Buchstaben jedes Wortes, sodass Anagramme gleiche Schlüssel erhalten
+operation-analyze = Meldet Zeichenklassen, Schriften und eine grobe Schätzung der Sprache
+operation-camelcase = Wandelt Text in camelCase um
+operation-crlf = Wandelt alle Zeilenenden in CRLF um
+operation-csv = Zeigt eine CSV-Datei als Tabelle an
+operation-csv-apply = Wendet eine Textoperation auf die Werte einer Spalte an
+operation-csv-clean-headers = Bereinigt Spaltennamen für den Datenbankimport
+operation-csv-dedupe = Entfernt doppelte Zeilen
+operation-csv-diff = Listet hinzugefügte, entfernte und geänderte Zeilen zweier CSV-Dateien auf
+operation-csv-groupby = Gruppiert Zeilen und aggregiert jede Gruppe
+operation-csv-join = Verknüpft zwei CSV-Dateien über Schlüsselspalten
+operation-csv-stats = Anzahl, Minimum und Maximum, Mittelwert und Summe je Spalte
+operation-csv-transpose = Vertauscht Zeilen und Spalten
+operation-csv-validate = Meldet fehlerhafte Zeilen mit ihren Zeilennummern
+operation-csv2json = Wandelt CSV in ein JSON-Array von Objekten um
+operation-csv2sql = Wandelt CSV in CREATE TABLE- und INSERT-Anweisungen um
+operation-detect-encoding = Errät die Kodierung einer Datei und wie sicher die Vermutung ist
+operation-detect-eol = Ermittelt die Zeilenenden einer Datei und ob sie gemischt sind
+operation-email-normalize = Prüft eine E-Mail-Adresse und normalisiert sie zum Vergleichen
+operation-escape = Schreibt Zeilenumbrüche, Tabulatoren, Anführungszeichen und Steuerzeichen als Backslash-Escapes
+operation-extract-emails = Gibt die im Text gefundenen E-Mail-Adressen aus, eine pro Zeile
+operation-extract-urls = Gibt die im Text gefundenen URLs aus, eine pro Zeile
+operation-filter-dictionary = Meldet oder entfernt die Wörter eines Textes, die in einer Wortliste fehlen
+operation-frontmatter = Gibt das YAML-Frontmatter einer Markdown-Datei als JSON aus
+operation-frontmatter-apply = Wendet eine Textoperation auf ein Frontmatter-Feld von Markdown-Dateien an
+operation-hexdump = Zeigt die Bytes einer Datei als Offsets, Hex und ASCII
+operation-iban-format = Prüft eine IBAN und gruppiert sie in Viererblöcke
+operation-is-palindrome = Prüft, ob sich ein Text rückwärts gleich liest, ohne Groß- und Kleinschreibung, Akzente und Satzzeichen
+operation-jget = Gibt den Wert an einem JSON Pointer oder Pfad aus
+operation-json-pretty = Formatiert JSON neu, eingerückt oder kompakt
+operation-json2csv = Flacht JSON-Objekte zu CSV ab
+operation-json2yaml = Wandelt JSON in YAML um
+operation-lf = Wandelt alle Zeilenenden in LF um
+operation-lint-text = Markiert doppelte Wörter, Leerzeichen am Zeilenende, gemischte Einrückung und lange Zeilen
+operation-lowercase = Wandelt Text in Kleinbuchstaben um
+operation-luhn-check = Prüft Karten- und andere Nummern mit der Luhn-Prüfsumme
+operation-md-headings = Listet die Überschriften einer Markdown-Datei mit ihren Ebenen auf
+operation-md-links = Listet die URLs der Links und Bilder einer Markdown-Datei auf
+operation-md-toc = Erzeugt ein Inhaltsverzeichnis für eine Markdown-Datei
+operation-no-spaces = Entfernt alle Leerzeichen aus dem Text
+operation-phone = Liest eine Telefonnummer und gibt sie im E.164- oder einem anderen Format aus
+operation-redact = Maskiert E-Mail-Adressen, Telefonnummern und Kartennummern
+operation-script = Verändert Text mit einem rhai-Skript
+operation-sentences = Teilt Text in einen Satz pro Zeile auf
+operation-slugify = Erzeugt URL-taugliche Slugs
+operation-snakecase = Wandelt Text in snake_case um
+operation-sort-lines = Sortiert die Zeilen einer Datei, zu große Dateien über temporäre Dateien
+operation-toml2json = Wandelt TOML in JSON um
+operation-unescape = Wandelt Backslash-Escapes wie \n oder \t in die Zeichen um
+operation-uppercase = Wandelt Text in GROSSBUCHSTABEN um
+operation-words = Teilt Text in ein Wort pro Zeile auf
+operation-xml-escape = Ersetzt Zeichen mit besonderer Bedeutung in XML durch Entitäten
+operation-xml-pretty = Formatiert XML mit einheitlicher Einrückung neu
+operation-xml-unescape = Ersetzt XML-Entitäten und numerische Zeichenreferenzen
+operation-yaml2json = Wandelt YAML in JSON um
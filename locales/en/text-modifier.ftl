@@ -0,0 +1,79 @@
+# Messages of the command line tool. Operation descriptions fall back to the
+# operation's own, English, description without an `operation-<name>` message
+
+## Command line
+
+available-operations = Available operations are:
+error = Error: { $message }
+error-processing = Error processing input: { $message }
+warning = Warning: { $message }
+config-invalid = Invalid config file { $path }: { $message }
+expected-format = Expected format: <operation> <input>
+dropped-command = Warning: dropped the command on line { $line }, the queue is full
+error-sending = Error sending command: { $message }
+ctrl-c-unavailable = Warning: could not handle Ctrl-C: { $message }
+stopping = Stopping after the commands in progress, press Ctrl-C again to abort
+jsonrpc-arguments = Error: --jsonrpc reads its requests from stdin, drop the arguments
+client-usage = client requires <operation> <input>
+bench-usage = bench requires <operation> [--iterations N] [--warmup N] <input>
+bench-result = { $operation }: { $iterations } runs, min { $min }, mean { $mean }, p99 { $p99 }, { $throughput } MiB/s
+output-checksum = SHA-256 of the output: { $hash }
+audit-open-failed = Failed to open the audit log { $path }: { $reason }
+audit-write-failed = Warning: failed to write to the audit log: { $reason }
+stats-usage = stats takes no arguments but --reset
+stats-empty = No operations counted yet
+stats-no-config-dir = Usage stats need HOME or XDG_CONFIG_HOME to be saved
+stats-read-failed = Failed to read the usage stats { $path }: { $reason }
+stats-save-failed = Warning: failed to save the usage stats { $path }: { $reason }
+stats-reset-failed = Failed to reset the usage stats { $path }: { $reason }
+stats-operation = Operation
+stats-runs = Runs
+stats-total = Total time
+stats-mean = Mean time
+confirm-overwrite = { $operation } is about to overwrite files: { $count }
+confirm-more = and { $count } more
+confirm-question = Overwrite them? [y/N]
+confirm-yes = yes
+confirm-needs-yes = Nothing was overwritten, there is no terminal to confirm it on, pass --yes to overwrite files without asking
+hook-failed = Warning: the hook { $hook } failed: { $reason }
+
+## REPL
+
+repl-recording = Recording macro { $name }, :stop saves it
+repl-recorded = Saved macro { $name } with { $count } commands
+repl-already-recording = Already recording macro { $name }
+repl-not-recording = No macro is being recorded, start one with :record <name>
+repl-unknown-macro = No macro named { $name }
+repl-invalid-name = Invalid macro name: { $name }, use letters, digits, - and _
+repl-no-config-dir = Macros need HOME or XDG_CONFIG_HOME to be saved
+repl-save-failed = Failed to save macro { $name }: { $reason }
+repl-not-saved = Macro { $name } wasn't saved, the one recorded before is kept
+repl-in-macro = Macros can't use { $command }
+repl-set-usage = Expected :set <name> = <operation> <input>
+repl-cache-stats = Cache: { $entries } entries, { $bytes } bytes, { $hits } hits, { $misses } misses
+repl-cache-cleared = Cache cleared
+repl-cache-off = The cache is off, --no-cache was given
+repl-cache-usage = Expected :cache stats or :cache clear
+repl-unknown-command = Unknown command: { $command }, available commands are: :set, :record, :stop, :play, :cache
+
+## Options
+
+unknown-option = Unknown option: { $option }
+adaptive-chunk-size = --adaptive sizes the chunks itself, leave out --chunk-lines and --chunk-bytes
+option-requires-value = Option { $option } requires a value
+invalid-value = Invalid value for { $option }: { $value }
+invalid-backpressure = Invalid backpressure: { $value }, available policies are: block, drop
+unknown-language = Unsupported language: { $value }, available languages are: en, cs, de
+
+## Errors of operations
+
+invalid-operation = Invalid operation: { $input }
+invalid-operation-suggestions = Invalid operation: { $input }, did you mean { $suggestions }?
+failed-validation = Invalid value { $value }: { $reason }
+# Joins the suggested operation names
+or = or
+timeout = Timed out after { $limit }
+read-failed = Failed to read '{ $path }': { $reason }
+plugin-failed = Failed to load plugin '{ $path }': { $reason }
+too-large = Input is over the limit of { $limit } bytes
+too-many-requests = Too many requests: { $reason }
@@ -0,0 +1,124 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Ident, ItemFn, LitBool, LitStr, Token};
+
+// Turns `fn(&str) -> Result<String, E>` into an operation of the default
+// registry, e.g.
+//
+//     #[text_operation(name = "reverse", aliases("rev"), description = "Reverses text")]
+//     fn reverse(input: &str) -> Result<String, Error> { ... }
+//
+// The name defaults to the function's with `-` for `_`. `text = false` marks
+// operations whose input holds options or file names rather than text. The
+// description is what `describe()` returns, the text the command line and the
+// REPL list the operation with
+#[proc_macro_attribute]
+pub fn text_operation(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(item as ItemFn);
+    let mut options = Options::default();
+    let parser = syn::meta::parser(|meta| options.parse(meta));
+    parse_macro_input!(attr with parser);
+
+    let ident = &function.sig.ident;
+    let Some(description) = options.description else {
+        return syn::Error::new(Span::call_site(), "text_operation requires a description")
+            .to_compile_error()
+            .into();
+    };
+    let name = options
+        .name
+        .unwrap_or_else(|| LitStr::new(&ident.to_string().replace('_', "-"), ident.span()));
+    let aliases = options.aliases;
+    let text = options.text;
+    let vis = &function.vis;
+    let operation = format_ident!("{}Operation", camel_case(ident));
+
+    quote! {
+        #function
+
+        #vis struct #operation;
+
+        impl ::rust_text_transformer::TextOperation for #operation {
+            fn name(&self) -> &str {
+                #name
+            }
+
+            fn aliases(&self) -> &[&str] {
+                &[#(#aliases),*]
+            }
+
+            fn describe(&self) -> &str {
+                #description
+            }
+
+            fn apply(&self, input: &str) -> ::std::result::Result<String, ::rust_text_transformer::Error> {
+                #ident(input).map_err(::std::convert::Into::into)
+            }
+
+            fn is_text_operation(&self) -> bool {
+                #text
+            }
+        }
+
+        ::rust_text_transformer::__private::inventory::submit! {
+            ::rust_text_transformer::__private::DeclaredOperation(|| ::std::sync::Arc::new(#operation))
+        }
+    }
+    .into()
+}
+
+struct Options {
+    name: Option<LitStr>,
+    aliases: Vec<LitStr>,
+    description: Option<LitStr>,
+    text: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            name: None,
+            aliases: Vec::new(),
+            description: None,
+            text: true,
+        }
+    }
+}
+
+impl Options {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("text") {
+            self.text = meta.value()?.parse::<LitBool>()?.value;
+        } else if meta.path.is_ident("aliases") {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            let aliases = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+            self.aliases.extend(aliases);
+        } else {
+            return Err(meta.error("expected name, aliases, description or text"));
+        }
+        Ok(())
+    }
+}
+
+// `no_spaces` -> `NoSpaces`
+fn camel_case(ident: &Ident) -> String {
+    ident
+        .to_string()
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+use crate::Error;
+
+// Options passed inside the input of a command, e.g. `csv --no-header data.csv`
+pub struct OperationArgs {
+    tokens: Vec<String>,
+    // Everything after a bare `--`, never interpreted as options
+    trailing: Vec<String>,
+}
+
+impl OperationArgs {
+    pub fn parse(input: &str) -> OperationArgs {
+        let mut tokens = tokenize(input);
+        let trailing = match tokens.iter().position(|token| token == "--") {
+            Some(index) => tokens.split_off(index).into_iter().skip(1).collect(),
+            None => Vec::new(),
+        };
+
+        OperationArgs { tokens, trailing }
+    }
+
+    // Removes `--name` and reports whether it was present
+    pub fn flag(&mut self, name: &str) -> bool {
+        let option = format!("--{}", name);
+        let before = self.tokens.len();
+        self.tokens.retain(|token| *token != option);
+        self.tokens.len() != before
+    }
+
+    // Removes `--name value` or `--name=value` and returns the value
+    pub fn value(&mut self, name: &str) -> Result<Option<String>, Error> {
+        let option = format!("--{}", name);
+        let prefix = format!("{}=", option);
+
+        let Some(index) = self
+            .tokens
+            .iter()
+            .position(|token| *token == option || token.starts_with(&prefix))
+        else {
+            return Ok(None);
+        };
+
+        let token = self.tokens.remove(index);
+        if let Some(value) = token.strip_prefix(&prefix) {
+            return Ok(Some(value.to_string()));
+        }
+
+        if index < self.tokens.len() {
+            Ok(Some(self.tokens.remove(index)))
+        } else {
+            Err(Error::invalid(format!(
+                "Option {} requires a value",
+                option
+            )))
+        }
+    }
+
+    // Like `value`, parsed into `T`
+    pub fn parsed<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, Error> {
+        match self.value(name)? {
+            Some(value) => match value.parse() {
+                Ok(parsed) => Ok(Some(parsed)),
+                Err(_) => Err(Error::invalid(format!(
+                    "Invalid value for --{}: {}",
+                    name, value
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    pub fn finish(self) -> Result<Vec<String>, Error> {
+        if let Some(unknown) = self
+            .tokens
+            .iter()
+            .find(|token| token.starts_with("--") && token.len() > 2)
+        {
+            return Err(Error::invalid(format!("Unknown option: {}", unknown)));
+        }
+
+        let mut positional = self.tokens;
+        positional.extend(self.trailing);
+        Ok(positional)
+    }
+}
+
+// Joins command line arguments back into input, quoting those that
+// `tokenize` would otherwise split apart
+pub fn join(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            let needs_quotes =
+                arg.is_empty() || arg.contains(char::is_whitespace) || arg.contains(['"', '\'']);
+            if !needs_quotes {
+                arg.clone()
+            } else if !arg.contains('\'') {
+                format!("'{}'", arg)
+            } else if !arg.contains('"') {
+                format!("\"{}\"", arg)
+            } else {
+                // Closes the single quotes around each `'`, which goes in double quotes
+                format!("'{}'", arg.replace('\'', "'\"'\"'"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Splits on whitespace while keeping single or double quoted parts together
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quote: Option<char> = None;
+
+    for c in input.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_token = true;
+            }
+            None if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+
+    if in_token {
+        tokens.push(current);
+    }
+
+    tokens
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio::task::{self, JoinHandle};
+
+use crate::{Command, Error, OperationOutput, OperationRegistry};
+
+// Runs a command through the registry on tokio's blocking pool, since
+// operations read files and keep a CPU busy while they run
+pub async fn process_operation_async(
+    registry: Arc<OperationRegistry>,
+    command: Command,
+) -> Result<OperationOutput, Error> {
+    task::spawn_blocking(move || registry.execute(&command))
+        .await
+        .map_err(|e| Error::invalid(e.to_string()))?
+}
+
+// The async counterpart of the stdin loop: reads `<operation> <input>` lines,
+// runs up to `jobs` of them at once and writes the results in input order.
+// Errors of single commands are written to `writer` and don't stop the loop
+pub async fn process_lines_async<R, W>(
+    registry: Arc<OperationRegistry>,
+    reader: R,
+    mut writer: W,
+    jobs: usize,
+) -> Result<(), Error>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    type Pending = JoinHandle<Result<OperationOutput, Error>>;
+    let (tx, mut rx) = mpsc::channel::<Result<Pending, Error>>(jobs.max(1));
+
+    let read = async move {
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let pending = match parts.as_slice() {
+                [] => continue,
+                [_] => Err(Error::invalid("Expected format: <operation> <input>")),
+                [name, input @ ..] => registry.find(name).map(|operation| {
+                    let command = Command {
+                        operation,
+                        input: input.join(" "),
+                    };
+                    task::spawn(process_operation_async(Arc::clone(&registry), command))
+                }),
+            };
+            // The writer stopped, e.g. because the peer went away
+            if tx.send(pending).await.is_err() {
+                break;
+            }
+        }
+        Ok::<(), Error>(())
+    };
+
+    let write = async {
+        while let Some(pending) = rx.recv().await {
+            let result = match pending {
+                Ok(handle) => handle
+                    .await
+                    .unwrap_or_else(|e| Err(Error::invalid(e.to_string()))),
+                Err(e) => Err(e),
+            };
+            let text = match result {
+                Ok(output) => format!(
+                    "Selected operation: {}\n{}\n",
+                    output.operation, output.text
+                ),
+                Err(e) => format!("Error: {}\n", e),
+            };
+            writer.write_all(text.as_bytes()).await?;
+            writer.flush().await?;
+        }
+        Ok::<(), Error>(())
+    };
+
+    let (read, write) = tokio::join!(read, write);
+    read.and(write)
+}
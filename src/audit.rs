@@ -0,0 +1,116 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+
+#[cfg(any(feature = "server", feature = "jsonrpc"))]
+use rust_text_transformer::{Command, Next, OperationOutput, OperationRegistry};
+use rust_text_transformer::{Error, UtcTime};
+
+use crate::checksum::{self, Sha256Hash};
+use crate::i18n;
+
+// The file of `--audit-log`, for the whole run
+static LOG: OnceLock<Mutex<File>> = OnceLock::new();
+
+// One line of the audit log
+#[derive(Serialize)]
+struct Entry<'a> {
+    timestamp: String,
+    operation: &'a str,
+    input_sha256: String,
+    // None when the command failed
+    result_sha256: Option<String>,
+    duration: f64,
+    // What the command exits with when run on its own, 0 for success
+    exit_status: i32,
+    error: Option<&'static str>,
+}
+
+// Opens the audit log for appending, creating it when it doesn't exist yet
+pub fn open(path: &str) -> Result<(), String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| i18n::text("audit-open-failed", &[("path", &path), ("reason", &e)]))?;
+    // `--audit-log` given twice keeps the first
+    let _ = LOG.set(Mutex::new(file));
+    Ok(())
+}
+
+pub fn enabled() -> bool {
+    LOG.get().is_some()
+}
+
+// Appends a line for a processed command, `output` is the hash of what it wrote
+pub fn record(
+    operation: &str,
+    input: &Sha256Hash,
+    output: &Sha256Hash,
+    duration: Duration,
+    result: Result<(), &Error>,
+) {
+    let Some(log) = LOG.get() else {
+        return;
+    };
+    let entry = Entry {
+        timestamp: timestamp(SystemTime::now()),
+        operation,
+        input_sha256: checksum::hex(input),
+        result_sha256: result.is_ok().then(|| checksum::hex(output)),
+        duration: duration.as_secs_f64(),
+        exit_status: match result {
+            Ok(()) => 0,
+            Err(Error::InvalidOperation { .. }) => 2,
+            Err(_) => 1,
+        },
+        error: result.err().map(Error::code),
+    };
+    let mut line = serde_json::to_vec(&entry).expect("audit entries serialize");
+    line.push(b'\n');
+    // A single write per line, so lines of concurrent commands don't mix
+    let written = log
+        .lock()
+        .expect("audit log lock poisoned")
+        .write_all(&line);
+    if let Err(e) = written {
+        eprintln!("{}", i18n::text("audit-write-failed", &[("reason", &e)]));
+    }
+}
+
+// Records every command run through the registry, for the server modes that
+// get the output of operations whole
+#[cfg(any(feature = "server", feature = "jsonrpc"))]
+pub fn wrap(registry: OperationRegistry) -> OperationRegistry {
+    if !enabled() {
+        return registry;
+    }
+    registry.with_middleware(|command: &Command, next: Next| {
+        let start = std::time::Instant::now();
+        let result = next(command);
+        let output = match &result {
+            Ok(OperationOutput { text, .. }) => checksum::digest(format!("{}\n", text)),
+            Err(_) => checksum::digest(""),
+        };
+        record(
+            command.operation.name(),
+            &checksum::digest(&command.input),
+            &output,
+            start.elapsed(),
+            result.as_ref().map(|_| ()),
+        );
+        result
+    })
+}
+
+// `2026-01-31T12:34:56.789Z`, in UTC
+fn timestamp(time: SystemTime) -> String {
+    let time = UtcTime::new(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        time.year, time.month, time.day, time.hour, time.minute, time.second, time.millisecond
+    )
+}
@@ -0,0 +1,52 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use subtle::ConstantTimeEq;
+
+// The bearer tokens `serve` accepts, `tokens` of the config file, one per line
+// of the token file and TEXT_MODIFIER_TOKEN
+pub struct Tokens(Vec<Vec<u8>>);
+
+impl Tokens {
+    pub fn load(configured: &[String], path: Option<&Path>) -> io::Result<Option<Tokens>> {
+        let mut tokens: Vec<Vec<u8>> = configured
+            .iter()
+            .map(|token| token.trim().as_bytes().to_vec())
+            .collect();
+        if let Some(path) = path {
+            let text = fs::read_to_string(path).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("Failed to read '{}': {}", path.display(), e),
+                )
+            })?;
+            // Blank lines and `#` comments are skipped
+            let before = tokens.len();
+            tokens.extend(
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.as_bytes().to_vec()),
+            );
+            if tokens.len() == before {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("No tokens in '{}'", path.display()),
+                ));
+            }
+        }
+        if let Some(token) = std::env::var_os("TEXT_MODIFIER_TOKEN").filter(|t| !t.is_empty()) {
+            tokens.push(token.into_encoded_bytes());
+        }
+        Ok((!tokens.is_empty()).then_some(Tokens(tokens)))
+    }
+
+    // Every token is compared, each in constant time, so the time taken
+    // doesn't tell how much of a guess was right
+    pub fn accepts(&self, token: &str) -> bool {
+        self.0.iter().fold(0u8, |accepted, known| {
+            accepted | known.as_slice().ct_eq(token.as_bytes()).unwrap_u8()
+        }) == 1
+    }
+}
@@ -0,0 +1,140 @@
+use std::io;
+use std::time::Duration;
+
+use rust_text_transformer::{
+    args, with_timeout, write_operation, Error as OperationError, OperationOutput,
+    OperationRegistry,
+};
+
+use crate::cli::{self, CliOptions};
+use crate::{i18n, shutdown};
+
+// Arguments of `bench`, `<operation> [--iterations N] [--warmup N] <input>...`.
+// The bench options may stand anywhere after the operation, everything else is
+// its input like on the command line
+pub struct BenchOptions {
+    operation: String,
+    input: Vec<String>,
+    iterations: usize,
+    warmup: usize,
+}
+
+impl BenchOptions {
+    pub fn parse(args: &[String]) -> Result<BenchOptions, String> {
+        let mut args = args.iter();
+        let operation = args
+            .next()
+            .ok_or_else(|| i18n::text("bench-usage", &[]))?
+            .to_string();
+        let mut iterations = 10;
+        let mut warmup = 1;
+        let mut input = Vec::new();
+        while let Some(arg) = args.next() {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let mut value = || match &inline {
+                Some(value) => Ok(value.clone()),
+                None => args
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| i18n::text("option-requires-value", &[("option", &name)])),
+            };
+            match name {
+                "--iterations" => iterations = cli::positive(name, &value()?)?,
+                "--warmup" => {
+                    let value = value()?;
+                    warmup = value.parse().map_err(|_| {
+                        i18n::text("invalid-value", &[("option", &name), ("value", &value)])
+                    })?
+                }
+                _ => input.push(arg.clone()),
+            }
+        }
+        Ok(BenchOptions {
+            operation,
+            input,
+            iterations,
+            warmup,
+        })
+    }
+}
+
+// Runs the operation `--warmup` times unmeasured, then `--iterations` times
+// with its output thrown away, and prints the latencies and the throughput
+pub fn run(
+    bench: BenchOptions,
+    registry: &OperationRegistry,
+    options: CliOptions,
+) -> Result<(), OperationError> {
+    let operation = registry.find(&bench.operation)?;
+    let input = if operation.is_text_operation() {
+        bench.input.join(" ")
+    } else {
+        args::join(&bench.input)
+    };
+    let run = || -> Result<OperationOutput, OperationError> {
+        let run =
+            || registry.scope(|| write_operation(operation.as_ref(), &input, &mut io::sink()));
+        match options.timeout {
+            Some(timeout) => with_timeout(timeout, run),
+            None => run(),
+        }
+    };
+
+    for _ in 0..bench.warmup {
+        run()?;
+    }
+    let mut durations = Vec::with_capacity(bench.iterations);
+    let mut bytes_in = 0;
+    for _ in 0..bench.iterations {
+        // Ctrl-C reports the runs so far
+        if shutdown::requested() {
+            break;
+        }
+        let output = run()?;
+        durations.push(output.duration);
+        bytes_in += output.bytes_in;
+    }
+    if durations.is_empty() {
+        return Ok(());
+    }
+
+    durations.sort();
+    let total: Duration = durations.iter().sum();
+    let mean = total / durations.len() as u32;
+    // The smallest duration at least 99% of the runs didn't exceed
+    let p99 = durations[(durations.len() * 99).div_ceil(100) - 1];
+    let per_second = bytes_in as f64 / total.as_secs_f64().max(f64::MIN_POSITIVE);
+    if options.porcelain {
+        println!(
+            "bench\t{}\t{}\t{}\t{}\t{}\t{:.0}",
+            operation.name(),
+            durations.len(),
+            durations[0].as_secs_f64(),
+            mean.as_secs_f64(),
+            p99.as_secs_f64(),
+            per_second
+        );
+    } else {
+        println!(
+            "{}",
+            i18n::text(
+                "bench-result",
+                &[
+                    ("operation", &operation.name()),
+                    ("iterations", &durations.len()),
+                    ("min", &format!("{:.2?}", durations[0])),
+                    ("mean", &format!("{:.2?}", mean)),
+                    ("p99", &format!("{:.2?}", p99)),
+                    (
+                        "throughput",
+                        &format!("{:.1}", per_second / (1 << 20) as f64)
+                    ),
+                ]
+            )
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use std::io::{Read, Write};
+
+#[cfg(feature = "encoding")]
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+#[cfg(feature = "encoding")]
+use encoding_rs::{Encoding, UTF_8};
+
+use memchr::memchr2;
+
+use crate::args::OperationArgs;
+use crate::input::open_source;
+use crate::streaming::StreamingOperation;
+use crate::Error;
+
+// Operations looking at the bytes of their input rather than its text
+pub fn builtins() -> Vec<StreamingOperation> {
+    let operation = StreamingOperation::new;
+
+    vec![
+        operation(
+            "crlf",
+            &[],
+            "Converts all line endings to CRLF",
+            |input, out| write_eol(input, out, "crlf", b"\r\n"),
+        ),
+        #[cfg(feature = "encoding")]
+        operation(
+            "detect-encoding",
+            &[],
+            "Guesses the encoding of a file and how sure the guess is",
+            write_encoding,
+        ),
+        operation(
+            "detect-eol",
+            &[],
+            "Reports the line endings of a file and whether they are mixed",
+            write_detect_eol,
+        ),
+        operation(
+            "hexdump",
+            &["hex"],
+            "Shows the bytes of a file as offsets, hex and ASCII",
+            write_hexdump,
+        ),
+        operation(
+            "lf",
+            &[],
+            "Converts all line endings to LF",
+            |input, out| write_eol(input, out, "lf", b"\n"),
+        ),
+    ]
+}
+
+// The file named in the input, `-` for stdin or with `--inline` the text itself,
+// after the options `operation` took from `args`
+fn source(mut args: OperationArgs, operation: &str) -> Result<Box<dyn Read>, Error> {
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(format!(
+            "{} requires a file, - for stdin or --inline TEXT",
+            operation
+        )));
+    }
+    open_source(&source, inline)
+}
+
+// The columns of `hexdump -C`: the offset, `--width N` bytes in hex, 16 by
+// default with an extra space after every 8, and the bytes again as ASCII
+// with `.` for everything not printable
+pub fn write_hexdump(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let width: usize = args.parsed("width")?.unwrap_or(16);
+    if width == 0 {
+        return Err(Error::invalid("--width must be at least 1"));
+    }
+
+    let mut reader = source(args, "hexdump")?;
+    let mut row = vec![0; width];
+    let mut offset = 0;
+    loop {
+        let length = read_row(&mut reader, &mut row)?;
+        if length == 0 {
+            break;
+        }
+        write!(out, "{:08x} ", offset)?;
+        for i in 0..width {
+            if i.is_multiple_of(8) {
+                write!(out, " ")?;
+            }
+            match row[..length].get(i) {
+                Some(byte) => write!(out, "{:02x} ", byte)?,
+                None => write!(out, "   ")?,
+            }
+        }
+        let ascii: String = row[..length]
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(out, " |{}|", ascii)?;
+        offset += length;
+    }
+    writeln!(out, "{:08x}", offset)?;
+    Ok(())
+}
+
+// Fills the row unless the input ends first, whatever sizes its reads return
+fn read_row(reader: &mut dyn Read, row: &mut [u8]) -> Result<usize, Error> {
+    let mut length = 0;
+    while length < row.len() {
+        match reader.read(&mut row[length..])? {
+            0 => break,
+            read => length += read,
+        }
+    }
+    Ok(length)
+}
+
+// How much of a file `detect-encoding` looks at, more only slows it down
+#[cfg(feature = "encoding")]
+const DETECT_BYTES: u64 = 16 << 20;
+
+// Top-level domains of languages with their own legacy encodings, which
+// chardetng favours when the text leaves a choice
+#[cfg(feature = "encoding")]
+#[rustfmt::skip]
+const HINTS: &[&[u8]] = &[
+    b"com", b"de", b"pl", b"cz", b"ru", b"gr", b"tr", b"il", b"sa", b"lt", b"vn", b"th",
+    b"jp", b"cn", b"tw", b"kr",
+];
+
+// The WHATWG name of the encoding, which encoding_rs and browsers accept as a
+// label, and how sure the guess is: 100% for a byte order mark, valid UTF-8 or
+// plain ASCII, otherwise the share of languages whose hint doesn't change the
+// guess, so text that fits several encodings gets a low one. `--name` prints
+// only the name and `--tld cz` hints at where the text came from
+#[cfg(feature = "encoding")]
+pub fn write_encoding(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let name_only = args.flag("name");
+    let tld = args
+        .value("tld")?
+        .map(|tld| tld.trim_start_matches('.').to_ascii_lowercase());
+    if let Some(tld) = &tld {
+        if tld.is_empty() || !tld.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+            return Err(Error::invalid_value(
+                tld,
+                "is no top-level domain like cz or de",
+            ));
+        }
+    }
+
+    let mut bytes = Vec::new();
+    source(args, "detect-encoding")?
+        .take(DETECT_BYTES)
+        .read_to_end(&mut bytes)?;
+
+    let (encoding, confidence, note) = match Encoding::for_bom(&bytes) {
+        Some((encoding, _)) => (encoding, 100, Some("byte order mark")),
+        None if bytes.is_ascii() => (UTF_8, 100, Some("ASCII only")),
+        None => {
+            let mut detector = EncodingDetector::new(Iso2022JpDetection::Deny);
+            detector.feed(&bytes, true);
+            let encoding = detector.guess(tld.as_deref().map(str::as_bytes), Utf8Detection::Allow);
+            let agreeing = HINTS
+                .iter()
+                .filter(|hint| detector.guess(Some(hint), Utf8Detection::Allow) == encoding)
+                .count();
+            let (_, malformed) = encoding.decode_without_bom_handling(&bytes);
+            let note = malformed.then_some("some bytes don't decode");
+            (encoding, agreeing * 100 / HINTS.len(), note)
+        }
+    };
+
+    if name_only {
+        writeln!(out, "{}", encoding.name())?;
+    } else {
+        let note = note.map(|note| format!(", {}", note)).unwrap_or_default();
+        writeln!(
+            out,
+            "{} (confidence: {}%{})",
+            encoding.name(),
+            confidence,
+            note
+        )?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Eol {
+    Lf,
+    CrLf,
+    // A lone `\r`, the line ending of classic Mac OS files
+    Cr,
+}
+
+// Rewrites every line ending, lone `\r` included, as `eol`. The text between
+// them is copied as it is, in any encoding that keeps ASCII bytes
+fn write_eol(input: &str, out: &mut dyn Write, operation: &str, eol: &[u8]) -> Result<(), Error> {
+    let args = OperationArgs::parse(input);
+    let reader = source(args, operation)?;
+    scan_lines(reader, |text, ending| {
+        out.write_all(text)?;
+        if ending.is_some() {
+            out.write_all(eol)?;
+        }
+        Ok(())
+    })
+}
+
+// `lf`, `crlf` or `cr` when all lines end the same, `none` without line
+// breaks, otherwise `mixed`, then how many of each there are and whether the
+// last line has none
+fn write_detect_eol(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let args = OperationArgs::parse(input);
+    let reader = source(args, "detect-eol")?;
+    let (mut lf, mut crlf, mut cr) = (0, 0, 0);
+    let mut unterminated = false;
+    scan_lines(reader, |text, ending| {
+        match ending {
+            Some(Eol::Lf) => lf += 1,
+            Some(Eol::CrLf) => crlf += 1,
+            Some(Eol::Cr) => cr += 1,
+            None => {}
+        }
+        // Lines can come in pieces, until the one with the ending
+        unterminated = ending.is_none() && (unterminated || !text.is_empty());
+        Ok(())
+    })?;
+
+    let counts = [("lf", lf), ("crlf", crlf), ("cr", cr)];
+    let found: Vec<_> = counts.iter().filter(|(_, count)| *count > 0).collect();
+    let kind = match found.as_slice() {
+        [] => "none",
+        [(kind, _)] => kind,
+        _ => "mixed",
+    };
+    let details: Vec<String> = found
+        .iter()
+        .map(|(kind, count)| format!("{} {}", count, kind.to_ascii_uppercase()))
+        .collect();
+    write!(out, "{}", kind)?;
+    if !details.is_empty() {
+        write!(out, " ({})", details.join(", "))?;
+    }
+    if unterminated {
+        write!(out, ", no line break at the end")?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+// Calls `line` with each line of the input and its ending, which is `None`
+// only for the text after the last line break. A `\r` at the end of one read
+// waits for the next to tell `\r\n` from a lone `\r`
+fn scan_lines(
+    mut reader: Box<dyn Read>,
+    mut line: impl FnMut(&[u8], Option<Eol>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut buffer = vec![0; 64 * 1024];
+    let mut pending_cr = false;
+    loop {
+        let length = reader.read(&mut buffer)?;
+        if length == 0 {
+            break;
+        }
+        let chunk = &buffer[..length];
+        let mut start = 0;
+        if pending_cr {
+            pending_cr = false;
+            if chunk[0] == b'\n' {
+                line(b"", Some(Eol::CrLf))?;
+                start = 1;
+            } else {
+                line(b"", Some(Eol::Cr))?;
+            }
+        }
+        while let Some(found) = memchr2(b'\n', b'\r', &chunk[start..]) {
+            let end = start + found;
+            let text = &chunk[start..end];
+            if chunk[end] == b'\n' {
+                line(text, Some(Eol::Lf))?;
+                start = end + 1;
+            } else if end + 1 == chunk.len() {
+                line(text, None)?;
+                pending_cr = true;
+                start = end + 1;
+            } else if chunk[end + 1] == b'\n' {
+                line(text, Some(Eol::CrLf))?;
+                start = end + 2;
+            } else {
+                line(text, Some(Eol::Cr))?;
+                start = end + 1;
+            }
+        }
+        if start < chunk.len() {
+            line(&chunk[start..], None)?;
+        }
+    }
+    if pending_cr {
+        line(b"", Some(Eol::Cr))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use rust_text_transformer::{OperationOutput, TextOperation};
+
+use crate::checksum::{self, Sha256Hash};
+
+// Entries kept at most, the least recently used one goes first
+const CAPACITY: usize = 1024;
+
+// Output kept at most over all entries, and for a single one
+const MAX_BYTES: usize = 64 << 20;
+const MAX_ENTRY_BYTES: usize = 1 << 20;
+
+// An operation, the options in front of its text and the hash of the text
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Key {
+    operation: String,
+    options: Vec<String>,
+    input: Sha256Hash,
+}
+
+impl Key {
+    fn new(operation: &dyn TextOperation, input: &str) -> Key {
+        let mut options = Vec::new();
+        let mut rest = input.trim_start();
+        while let Some(option) = rest
+            .split_whitespace()
+            .next()
+            .filter(|arg| arg.starts_with("--"))
+        {
+            options.push(option.to_string());
+            rest = rest[option.len()..].trim_start();
+        }
+        Key {
+            operation: operation.name().to_string(),
+            options,
+            input: checksum::digest(rest),
+        }
+    }
+}
+
+struct Entry {
+    text: Vec<u8>,
+    output: OperationOutput,
+    // When it was last used, the smallest is evicted first
+    used: u64,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<Key, Entry>,
+    bytes: usize,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+// What `:cache stats` prints
+pub struct Stats {
+    pub entries: usize,
+    pub bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+// The output of commands run before, so running them again with the same
+// input is only a lookup. Only text operations that read nothing but their
+// text are cached, files and stdin could have changed since
+#[derive(Default)]
+pub struct Cache {
+    state: Mutex<State>,
+}
+
+impl Cache {
+    // The output written and the figures of an earlier run of the command
+    pub fn get(
+        &self,
+        operation: &dyn TextOperation,
+        input: &str,
+    ) -> Option<(Vec<u8>, OperationOutput)> {
+        if !cacheable(operation, input) {
+            return None;
+        }
+        let key = Key::new(operation, input);
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        state.clock += 1;
+        let clock = state.clock;
+        match state.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.used = clock;
+                let found = (entry.text.clone(), entry.output.clone());
+                state.hits += 1;
+                Some(found)
+            }
+            None => {
+                state.misses += 1;
+                None
+            }
+        }
+    }
+
+    // Keeps the output of a finished command, unless it read files or stdin
+    pub fn insert(
+        &self,
+        operation: &dyn TextOperation,
+        input: &str,
+        text: Vec<u8>,
+        output: &OperationOutput,
+    ) {
+        if !cacheable(operation, input) || output.bytes_in != input.len() as u64 {
+            return;
+        }
+        let key = Key::new(operation, input);
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        state.clock += 1;
+        let entry = Entry {
+            used: state.clock,
+            text,
+            output: output.clone(),
+        };
+        state.bytes += entry.text.len();
+        if let Some(replaced) = state.entries.insert(key, entry) {
+            state.bytes -= replaced.text.len();
+        }
+        while state.entries.len() > CAPACITY || state.bytes > MAX_BYTES {
+            let Some(oldest) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some(evicted) = state.entries.remove(&oldest) {
+                state.bytes -= evicted.text.len();
+            }
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        let state = self.state.lock().expect("cache lock poisoned");
+        Stats {
+            entries: state.entries.len(),
+            bytes: state.bytes,
+            hits: state.hits,
+            misses: state.misses,
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        state.entries.clear();
+        state.bytes = 0;
+    }
+}
+
+// `--file` reads a file, which could be empty now and not later
+fn cacheable(operation: &dyn TextOperation, input: &str) -> bool {
+    operation.is_text_operation()
+        && !input
+            .split_whitespace()
+            .any(|arg| arg == "--file" || arg.starts_with("--file="))
+}
+
+// Passes output through while keeping a copy of it for the cache, given up
+// once it is too large for an entry
+pub struct Recorder<'a> {
+    out: &'a mut dyn Write,
+    copy: Option<Vec<u8>>,
+}
+
+impl<'a> Recorder<'a> {
+    pub fn new(out: &'a mut dyn Write) -> Recorder<'a> {
+        Recorder {
+            out,
+            copy: Some(Vec::new()),
+        }
+    }
+
+    pub fn into_copy(self) -> Option<Vec<u8>> {
+        self.copy
+    }
+}
+
+impl Write for Recorder<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.out.write(buf)?;
+        if let Some(copy) = &mut self.copy {
+            if copy.len() + written > MAX_ENTRY_BYTES {
+                self.copy = None;
+            } else {
+                copy.extend_from_slice(&buf[..written]);
+            }
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,137 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{output, Error};
+
+// The most bytes a command may take as input, 0 for no limit
+static MAX_INPUT_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // When the operation running on this thread has to stop, with its time limit
+    static DEADLINE: Cell<Option<(Instant, Duration)>> = const { Cell::new(None) };
+}
+
+// Runs `f` with a time limit. Operations check it while reading input and
+// writing rows, and fail with `Error::Timeout` once it has passed
+pub fn with_timeout<T>(timeout: Duration, f: impl FnOnce() -> T) -> T {
+    let previous = DEADLINE.replace(Some((Instant::now() + timeout, timeout)));
+    // Put back on panics too, the thread may run other commands after
+    struct Restore(Option<(Instant, Duration)>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DEADLINE.set(self.0);
+        }
+    }
+    let _restore = Restore(previous);
+    f()
+}
+
+pub fn check_timeout() -> Result<(), Error> {
+    match DEADLINE.get() {
+        Some((deadline, limit)) if Instant::now() >= deadline => Err(Error::Timeout { limit }),
+        _ => Ok(()),
+    }
+}
+
+// Limits the input text of every operation and what it reads from each file,
+// URL or stdin for the whole process. Larger input fails with
+// `Error::TooLarge`, files as soon as they are opened
+pub fn set_max_input_bytes(limit: Option<usize>) {
+    MAX_INPUT_BYTES.store(limit.unwrap_or(0), Ordering::Relaxed);
+}
+
+pub fn max_input_bytes() -> Option<usize> {
+    match MAX_INPUT_BYTES.load(Ordering::Relaxed) {
+        0 => None,
+        limit => Some(limit),
+    }
+}
+
+pub fn check_input_size(bytes: u64) -> Result<(), Error> {
+    match max_input_bytes() {
+        Some(limit) if bytes > limit as u64 => Err(Error::TooLarge { limit }),
+        _ => Ok(()),
+    }
+}
+
+// Carried inside the `io::Error` of a read that hit the deadline, so it can be
+// turned back into `Error::Timeout` after passing through the csv reader
+#[derive(Debug)]
+pub(crate) struct TimedOut(pub Duration);
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "timed out")
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+// Like `TimedOut`, for a read going over `set_max_input_bytes`
+#[derive(Debug)]
+pub(crate) struct OverLimit(pub usize);
+
+impl fmt::Display for OverLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "input over the limit of {} bytes", self.0)
+    }
+}
+
+impl std::error::Error for OverLimit {}
+
+// Checks the deadline before every read of the wrapped input and counts what
+// was read, failing once it is over the input limit
+pub(crate) struct CheckedReader<R> {
+    inner: R,
+    read: u64,
+}
+
+impl<R: Read> CheckedReader<R> {
+    pub fn new(inner: R) -> CheckedReader<R> {
+        CheckedReader { inner, read: 0 }
+    }
+}
+
+impl<R: Read> Read for CheckedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Err(Error::Timeout { limit }) = check_timeout() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, TimedOut(limit)));
+        }
+        let read = self.inner.read(buf)?;
+        output::count_read(read);
+        self.read += read as u64;
+        if let Err(Error::TooLarge { limit }) = check_input_size(self.read) {
+            return Err(io::Error::other(OverLimit(limit)));
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::*;
+
+    #[test]
+    fn the_deadline_ends_with_the_limited_code() {
+        let inner = with_timeout(Duration::from_secs(60), || {
+            with_timeout(Duration::ZERO, check_timeout).unwrap_err();
+            check_timeout()
+        });
+        assert!(inner.is_ok());
+        assert!(DEADLINE.get().is_none());
+    }
+
+    #[test]
+    fn the_deadline_ends_when_the_limited_code_panics() {
+        let panicked =
+            panic::catch_unwind(|| with_timeout(Duration::ZERO, || panic!("in the operation")));
+        assert!(panicked.is_err());
+        assert!(DEADLINE.get().is_none());
+        assert!(check_timeout().is_ok());
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use rust_text_transformer::{Error, TextOperation};
+
+pub type Sha256Hash = [u8; 32];
+
+// 64 hex digits, as printed by `sha256sum`
+pub fn parse(value: &str) -> Option<Sha256Hash> {
+    let value = value.trim();
+    if value.len() != 64 {
+        return None;
+    }
+    let mut hash = [0; 32];
+    for (byte, digits) in hash.iter_mut().zip(value.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    Some(hash)
+}
+
+pub fn digest(bytes: impl AsRef<[u8]>) -> Sha256Hash {
+    Sha256::digest(bytes).into()
+}
+
+pub fn hex(hash: &Sha256Hash) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// The file a command reads: the one of `--file PATH`, or for operations
+// other than text ones the only argument naming an existing file
+pub fn input_file(operation: &dyn TextOperation, args: &[String]) -> Result<String, Error> {
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--file" {
+            if let Some(path) = rest.next() {
+                return local(path);
+            }
+        } else if let Some(path) = arg.strip_prefix("--file=") {
+            return local(path);
+        }
+    }
+
+    if !operation.is_text_operation() {
+        let files: Vec<&String> = args
+            .iter()
+            .filter(|arg| !arg.starts_with("--") && Path::new(arg).is_file())
+            .collect();
+        match files.as_slice() {
+            [file] => return local(file),
+            [] if args.iter().any(|arg| arg == "-") => return local("-"),
+            [] => {}
+            _ => {
+                return Err(Error::invalid(format!(
+                    "--verify-sha256 can't tell which of {} is the input",
+                    files
+                        .iter()
+                        .map(|file| file.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )))
+            }
+        }
+    }
+    Err(Error::invalid(format!(
+        "--verify-sha256 needs a file for {} to read",
+        operation.name()
+    )))
+}
+
+fn local(path: &str) -> Result<String, Error> {
+    if path == "-" {
+        return Err(Error::invalid(
+            "--verify-sha256 can't check stdin, only a file",
+        ));
+    }
+    Ok(path.to_string())
+}
+
+// Fails unless the file has the expected hash
+pub fn verify(path: &str, expected: &Sha256Hash) -> Result<(), Error> {
+    let mut hasher = Hashing::new(io::sink());
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    let actual = hasher.finish();
+    if &actual != expected {
+        return Err(Error::invalid_value(
+            path,
+            format!("has SHA-256 {}, expected {}", hex(&actual), hex(expected)),
+        ));
+    }
+    Ok(())
+}
+
+// Hashes what is written or read through it
+pub struct Hashing<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W> Hashing<W> {
+    pub fn new(inner: W) -> Hashing<W> {
+        Hashing {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    pub fn finish(self) -> Sha256Hash {
+        self.hasher.finalize().into()
+    }
+}
+
+impl<W: Write> Write for Hashing<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<R: Read> Read for Hashing<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for Hashing<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    // What is consumed is still in the buffer of the inner reader
+    fn consume(&mut self, amount: usize) {
+        if let Ok(buffer) = self.inner.fill_buf() {
+            self.hasher.update(&buffer[..amount.min(buffer.len())]);
+        }
+        self.inner.consume(amount);
+    }
+}
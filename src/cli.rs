@@ -0,0 +1,205 @@
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use crate::audit;
+use crate::checksum::{self, Sha256Hash};
+use crate::i18n::{self, Language};
+
+// Options given in front of the operation, e.g. `--jobs 4 --unordered`
+#[derive(Clone, Copy)]
+pub struct CliOptions {
+    // Number of workers processing commands read from stdin
+    pub jobs: usize,
+    // Print results as workers finish them rather than in submission order
+    pub unordered: bool,
+    // Commands read ahead of the workers before `backpressure` applies
+    pub capacity: usize,
+    pub backpressure: Backpressure,
+    // Time limit of each command
+    pub timeout: Option<Duration>,
+    // Report how long each command took and how much it read and wrote
+    pub timing: bool,
+    // Speak JSON-RPC on stdin and stdout instead of the text protocol
+    #[cfg_attr(not(feature = "jsonrpc"), allow(dead_code))]
+    pub jsonrpc: bool,
+    // Print errors as `error<TAB>code<TAB>class<TAB>message` lines for scripts
+    pub porcelain: bool,
+    // How piped stdin is split into chunks that are transformed and written
+    // one after another, and whether its end is waited out like `tail -f`
+    pub chunk_lines: Option<usize>,
+    pub chunk_bytes: Option<usize>,
+    pub follow: bool,
+    // Size the chunks and workers by the throughput measured on the way
+    pub adaptive: bool,
+    // The hash the input file of a single command must have before it runs,
+    // and whether the hash of its output is printed after it
+    pub verify_sha256: Option<Sha256Hash>,
+    pub emit_checksum: bool,
+    // The most bytes of input a command may take, `serve` and `--jsonrpc`
+    // fall back to `SERVER_MAX_INPUT_BYTES` without it
+    pub max_input_bytes: Option<usize>,
+    // Run commands read from stdin again even when their output is cached
+    pub no_cache: bool,
+    // Overwrite files without asking first
+    pub yes: bool,
+}
+
+// What the server modes accept at most unless told otherwise
+#[cfg(any(feature = "server", feature = "jsonrpc"))]
+pub const SERVER_MAX_INPUT_BYTES: usize = 16 << 20;
+
+// What reading stdin does when the workers fall `capacity` commands behind
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backpressure {
+    // Wait until a worker takes the next command
+    Block,
+    // Skip the command with a warning, keeping input flowing
+    Drop,
+}
+
+impl Default for CliOptions {
+    fn default() -> CliOptions {
+        CliOptions {
+            jobs: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            unordered: false,
+            capacity: 64,
+            backpressure: Backpressure::Block,
+            timeout: None,
+            timing: false,
+            jsonrpc: false,
+            porcelain: false,
+            chunk_lines: None,
+            chunk_bytes: None,
+            follow: false,
+            adaptive: false,
+            verify_sha256: None,
+            emit_checksum: false,
+            max_input_bytes: None,
+            no_cache: false,
+            yes: false,
+        }
+    }
+}
+
+impl CliOptions {
+    // Removes the leading options from the arguments
+    pub fn take(args: &mut Vec<String>) -> Result<CliOptions, String> {
+        let mut options = CliOptions::default();
+
+        while let Some(arg) = args.first().filter(|arg| arg.starts_with("--")) {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            args.remove(0);
+            let mut value = || match &inline {
+                Some(value) => Ok(value.clone()),
+                None if !args.is_empty() => Ok(args.remove(0)),
+                None => Err(i18n::text("option-requires-value", &[("option", &name)])),
+            };
+
+            match name.as_str() {
+                "--jobs" => options.jobs = positive(&name, &value()?)?,
+                "--unordered" => options.unordered = true,
+                "--timing" => options.timing = true,
+                "--no-cache" => options.no_cache = true,
+                "--yes" => options.yes = true,
+                "--porcelain" => options.porcelain = true,
+                #[cfg(feature = "jsonrpc")]
+                "--jsonrpc" => options.jsonrpc = true,
+                "--capacity" => options.capacity = positive(&name, &value()?)?,
+                "--backpressure" => {
+                    options.backpressure = match value()?.to_lowercase().as_str() {
+                        "block" => Backpressure::Block,
+                        "drop" => Backpressure::Drop,
+                        other => {
+                            return Err(i18n::text("invalid-backpressure", &[("value", &other)]))
+                        }
+                    }
+                }
+                "--timeout" => options.timeout = Some(duration(&name, &value()?)?),
+                "--chunk-lines" => options.chunk_lines = Some(positive(&name, &value()?)?),
+                "--chunk-bytes" => options.chunk_bytes = Some(bytes(&name, &value()?)?),
+                "--follow" => options.follow = true,
+                "--adaptive" => options.adaptive = true,
+                "--verify-sha256" => {
+                    let value = value()?;
+                    options.verify_sha256 =
+                        Some(checksum::parse(&value).ok_or_else(|| invalid_value(&name, &value))?);
+                }
+                "--emit-checksum" => options.emit_checksum = true,
+                "--max-input-bytes" => options.max_input_bytes = Some(bytes(&name, &value()?)?),
+                "--audit-log" => audit::open(&value()?)?,
+                "--lang" => {
+                    let value = value()?;
+                    match Language::parse(&value) {
+                        Some(language) => i18n::set_language(language),
+                        None => return Err(i18n::text("unknown-language", &[("value", &value)])),
+                    }
+                }
+                _ => return Err(i18n::text("unknown-option", &[("option", &name)])),
+            }
+        }
+
+        if options.adaptive && (options.chunk_lines.is_some() || options.chunk_bytes.is_some()) {
+            return Err(i18n::text("adaptive-chunk-size", &[]));
+        }
+        Ok(options)
+    }
+}
+
+fn invalid_value(name: &str, value: &str) -> String {
+    i18n::text("invalid-value", &[("option", &name), ("value", &value)])
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| invalid_value(name, value))
+}
+
+pub fn positive(name: &str, value: &str) -> Result<usize, String> {
+    match parse(name, value)? {
+        0 => Err(invalid_value(name, value)),
+        number => Ok(number),
+    }
+}
+
+// A number of bytes with an optional `K`, `M` or `G` unit of 1024, 1024² and 1024³
+pub fn bytes(name: &str, value: &str) -> Result<usize, String> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => value.split_at(split),
+        None => (value, ""),
+    };
+    let unit: usize = match unit.to_ascii_uppercase().as_str() {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        _ => return Err(invalid_value(name, value)),
+    };
+    positive(name, number)?
+        .checked_mul(unit)
+        .ok_or_else(|| invalid_value(name, value))
+}
+
+// A number with an `ms`, `s`, `m` or `h` unit, seconds without one
+fn duration(name: &str, value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = parse(name, number)?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(invalid_value(name, value)),
+    };
+
+    Duration::try_from_secs_f64(seconds)
+        .ok()
+        .filter(|duration| !duration.is_zero())
+        .ok_or_else(|| invalid_value(name, value))
+}
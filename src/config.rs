@@ -0,0 +1,10 @@
+use std::path::PathBuf;
+
+// `~/.config/text-modifier`, following `XDG_CONFIG_HOME` when it is set
+pub fn config_dir() -> Option<PathBuf> {
+    let config = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(config) if !config.is_empty() => PathBuf::from(config),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config.join("text-modifier"))
+}
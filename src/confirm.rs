@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+// Files an operation is about to overwrite, asked about before the first one is
+// written
+pub struct Confirmation<'a> {
+    pub operation: &'a str,
+    pub paths: &'a [PathBuf],
+}
+
+type Hook = fn(&Confirmation) -> bool;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+// Sets what decides whether operations may go ahead for the whole process.
+// Without one they always do, like library users and services expect
+pub fn set_confirmation(hook: Hook) {
+    *HOOK.write().expect("confirmation lock poisoned") = Some(hook);
+}
+
+// Whether the change may be made, false leaves every file as it was
+pub fn confirmed(confirmation: &Confirmation) -> bool {
+    let hook = *HOOK.read().expect("confirmation lock poisoned");
+    hook.is_none_or(|hook| hook(confirmation))
+}
@@ -0,0 +1,58 @@
+use std::borrow::Cow;
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::registry::find_nested;
+use crate::Error;
+
+use super::columns::column_index;
+use super::format::{OutputFormat, TableOutput};
+use super::{ReadOptions, PAGE_SIZE};
+
+// Runs a text operation over every value of the selected columns
+pub fn write_csv_apply(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Csv(Default::default()))?;
+    let columns = args.value("column")?;
+    let operation = args.value("op")?;
+    let source = args.finish()?.join(" ");
+
+    let (Some(columns), Some(operation)) = (columns, operation) else {
+        return Err(Error::invalid(
+            "csv-apply requires --column <name> and --op <operation>",
+        ));
+    };
+
+    let operation = find_nested(&operation)?;
+    if !operation.is_text_operation() {
+        return Err(Error::invalid(format!(
+            "{} can't be applied to CSV values",
+            operation.name()
+        )));
+    }
+
+    let mut reader = read_options.open(&source)?;
+    let headers = read_options.headers(&mut reader)?;
+    let indices = columns
+        .split(',')
+        .map(|column| column_index(column.trim(), headers.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut table = TableOutput::new(&format, headers.as_ref(), PAGE_SIZE);
+    for record in reader.records() {
+        let mut fields: Vec<String> = record?.iter().map(String::from).collect();
+        for &index in &indices {
+            if let Some(field) = fields.get_mut(index) {
+                if let Cow::Owned(changed) = operation.apply_cow(field)? {
+                    *field = changed;
+                }
+            }
+        }
+        table.push(out, StringRecord::from(fields))?;
+    }
+
+    table.finish(out)
+}
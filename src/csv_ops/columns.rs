@@ -0,0 +1,80 @@
+use csv::StringRecord;
+
+use crate::Error;
+
+// Columns picked by `--columns`, in output order
+pub struct ColumnSelection {
+    indices: Vec<usize>,
+}
+
+impl ColumnSelection {
+    // Resolves a comma separated list of header names or 1-based indices
+    pub fn resolve(spec: &str, headers: Option<&StringRecord>) -> Result<ColumnSelection, Error> {
+        let indices = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|column| !column.is_empty())
+            .map(|column| column_index(column, headers))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if indices.is_empty() {
+            return Err(Error::invalid("--columns needs at least one column"));
+        }
+
+        Ok(ColumnSelection { indices })
+    }
+
+    // Header row for the selected columns, generated names are kept for headerless input
+    pub fn headers(&self, headers: Option<&StringRecord>) -> StringRecord {
+        match headers {
+            Some(headers) => self.apply(headers),
+            None => self
+                .indices
+                .iter()
+                .map(|index| format!("col{}", index + 1))
+                .collect(),
+        }
+    }
+
+    // Picks the selected fields, missing fields of short records are left empty
+    pub fn apply(&self, record: &StringRecord) -> StringRecord {
+        self.indices
+            .iter()
+            .map(|&index| record.get(index).unwrap_or(""))
+            .collect()
+    }
+}
+
+// Finds a column by header name first, then by its 1-based position
+pub fn column_index(column: &str, headers: Option<&StringRecord>) -> Result<usize, Error> {
+    if let Some(headers) = headers {
+        if let Some(index) = headers.iter().position(|header| header == column) {
+            return Ok(index);
+        }
+    }
+
+    let position = column
+        .strip_prefix("col")
+        .filter(|_| headers.is_none())
+        .unwrap_or(column);
+    match position.parse::<usize>() {
+        Ok(position) if position >= 1 && headers.is_none_or(|h| position <= h.len()) => {
+            Ok(position - 1)
+        }
+        _ => Err(Error::invalid(unknown_column_message(column, headers))),
+    }
+}
+
+fn unknown_column_message(column: &str, headers: Option<&StringRecord>) -> String {
+    match headers {
+        Some(headers) => format!(
+            "Unknown column '{}', available columns: {}",
+            column,
+            headers.iter().collect::<Vec<_>>().join(", ")
+        ),
+        None => format!(
+            "Unknown column '{}', use col1, col2, ... or 1, 2, ... for files without a header",
+            column
+        ),
+    }
+}
@@ -0,0 +1,157 @@
+use std::io::{Read, Write};
+
+use serde::Deserialize;
+use serde_json::{Deserializer, Map, Value};
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::json::JsonOutput;
+use super::open_source;
+
+// Converts YAML into JSON. A file with several `---` documents becomes an
+// array of them, or one line per document with `--lines`
+pub fn write_yaml_to_json(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let lines = args.flag("lines");
+    let text = read_source(args, "yaml2json", "a YAML file")?;
+
+    let mut documents = Vec::new();
+    for document in serde_yaml_ng::Deserializer::from_str(&text) {
+        let value = Value::deserialize(document)
+            .map_err(|e| Error::invalid(format!("Invalid YAML: {}", e)))?;
+        documents.push(value);
+    }
+
+    match documents.len() {
+        1 if !lines => writeln!(out, "{}", serde_json::to_string_pretty(&documents[0])?)?,
+        _ => {
+            let mut output = JsonOutput::new(lines);
+            for document in &documents {
+                output.push(out, document)?;
+            }
+            output.finish(out)?;
+        }
+    }
+    Ok(())
+}
+
+// Converts JSON, or a stream of values like JSON Lines, into YAML documents
+// separated by `---`
+pub fn write_json_to_yaml(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let args = OperationArgs::parse(input);
+    let text = read_source(args, "json2yaml", "a JSON file")?;
+
+    let mut count = 0;
+    for value in Deserializer::from_str(&text).into_iter::<Value>() {
+        let yaml = serde_yaml_ng::to_string(&value?)
+            .map_err(|e| Error::invalid(format!("Can't write the YAML: {}", e)))?;
+        if count > 0 {
+            writeln!(out, "---")?;
+        }
+        out.write_all(yaml.as_bytes())?;
+        count += 1;
+    }
+
+    if count == 0 {
+        return Err(Error::invalid("JSON has no values"));
+    }
+    Ok(())
+}
+
+// Converts a TOML document into a JSON object, dates and times become strings
+pub fn write_toml_to_json(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let args = OperationArgs::parse(input);
+    let text = read_source(args, "toml2json", "a TOML file")?;
+
+    let table: toml::Table =
+        toml::from_str(&text).map_err(|e| Error::invalid(format!("Invalid TOML: {}", e)))?;
+    let value = toml_to_json(toml::Value::Table(table));
+    writeln!(out, "{}", serde_json::to_string_pretty(&value)?)?;
+    Ok(())
+}
+
+// Reformats JSON with `--indent N` spaces, 2 by default, or on a single line
+// with `--compact`. Every value of a stream like JSON Lines is formatted on its own
+pub fn write_json_pretty(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let compact = args.flag("compact");
+    let indent: Option<usize> = args.parsed("indent")?;
+    let sort_keys = args.flag("sort-keys");
+    let text = read_source(args, "json-pretty", "a JSON file")?;
+
+    if compact && indent.is_some() {
+        return Err(Error::invalid("--compact and --indent can't be combined"));
+    }
+    let indent = " ".repeat(indent.unwrap_or(2));
+
+    let mut count = 0;
+    for value in Deserializer::from_str(&text).into_iter::<Value>() {
+        let mut value = value?;
+        if sort_keys {
+            sort(&mut value);
+        }
+        if compact {
+            serde_json::to_writer(&mut *out, &value)?;
+        } else {
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            let mut serializer = serde_json::Serializer::with_formatter(&mut *out, formatter);
+            serde::Serialize::serialize(&value, &mut serializer)?;
+        }
+        writeln!(out)?;
+        count += 1;
+    }
+
+    if count == 0 {
+        return Err(Error::invalid("JSON has no values"));
+    }
+    Ok(())
+}
+
+// The text of the file named by the remaining arguments, `-` for stdin or
+// with `--inline` the arguments themselves
+fn read_source(mut args: OperationArgs, operation: &str, expected: &str) -> Result<String, Error> {
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(format!(
+            "{} requires {}, - for stdin or --inline TEXT",
+            operation, expected
+        )));
+    }
+
+    let mut text = String::new();
+    open_source(&source, inline)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(text) => Value::String(text),
+        toml::Value::Integer(integer) => Value::Number(integer.into()),
+        toml::Value::Float(float) => serde_json::Number::from_f64(float)
+            .map_or_else(|| Value::String(float.to_string()), Value::Number),
+        toml::Value::Boolean(boolean) => Value::Bool(boolean),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect::<Map<String, Value>>(),
+        ),
+    }
+}
+
+// Orders the keys of all objects alphabetically, which otherwise keep the
+// order of the input
+fn sort(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.sort_keys();
+            object.values_mut().for_each(sort);
+        }
+        Value::Array(items) => items.iter_mut().for_each(sort),
+        _ => {}
+    }
+}
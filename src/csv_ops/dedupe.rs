@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use crate::args::OperationArgs;
+use crate::output;
+use crate::Error;
+
+use super::columns::ColumnSelection;
+use super::format::{OutputFormat, TableOutput};
+use super::{ReadOptions, PAGE_SIZE};
+
+// Keeps the first occurrence of every row, compared in full or on the `--by` columns
+pub fn write_csv_dedupe(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Csv(Default::default()))?;
+    let by = args.value("by")?;
+    let source = args.finish()?.join(" ");
+
+    let mut reader = read_options.open(&source)?;
+    let headers = read_options.headers(&mut reader)?;
+    let key_columns = match by {
+        Some(spec) => Some(ColumnSelection::resolve(&spec, headers.as_ref())?),
+        None => None,
+    };
+
+    let mut seen = HashSet::new();
+    let mut dropped = 0;
+    let mut table = TableOutput::new(&format, headers.as_ref(), PAGE_SIZE);
+    for record in reader.records() {
+        let record = record?;
+        let key: Vec<String> = match &key_columns {
+            Some(columns) => columns.apply(&record).iter().map(String::from).collect(),
+            None => record.iter().map(String::from).collect(),
+        };
+
+        if seen.insert(key) {
+            table.push(out, record)?;
+        } else {
+            dropped += 1;
+        }
+    }
+    table.finish(out)?;
+
+    let note = format!("({} duplicate rows dropped)", dropped);
+    if matches!(format, OutputFormat::Table(_)) {
+        writeln!(out, "{}", note)?;
+    } else {
+        // Keeps machine readable output clean
+        output::warn(note);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+// Stands in for a multi character delimiter, the csv crate only splits on single bytes
+pub const UNIT_SEPARATOR: u8 = 0x1f;
+
+// Rewrites a delimiter like `||` to `UNIT_SEPARATOR` line by line before the
+// csv reader sees the data, leaving quoted fields untouched
+pub struct MultiDelimiterReader<R> {
+    inner: BufReader<R>,
+    delimiter: Vec<u8>,
+    quote: Option<u8>,
+    // Quoted fields may continue on the next line
+    in_quotes: bool,
+    line: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> MultiDelimiterReader<R> {
+    pub fn new(inner: R, delimiter: Vec<u8>, quote: Option<u8>) -> MultiDelimiterReader<R> {
+        MultiDelimiterReader {
+            inner: BufReader::new(inner),
+            delimiter,
+            quote,
+            in_quotes: false,
+            line: Vec::new(),
+            position: 0,
+        }
+    }
+
+    fn next_line(&mut self) -> io::Result<()> {
+        let mut raw = Vec::new();
+        self.inner.read_until(b'\n', &mut raw)?;
+
+        self.line.clear();
+        self.position = 0;
+        let mut i = 0;
+        while i < raw.len() {
+            if Some(raw[i]) == self.quote {
+                // A doubled quote toggles twice, so it stays inside the field
+                self.in_quotes = !self.in_quotes;
+            } else if !self.in_quotes && raw[i..].starts_with(&self.delimiter) {
+                self.line.push(UNIT_SEPARATOR);
+                i += self.delimiter.len();
+                continue;
+            }
+            self.line.push(raw[i]);
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for MultiDelimiterReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.line.len() {
+            self.next_line()?;
+        }
+
+        let count = buf.len().min(self.line.len() - self.position);
+        buf[..count].copy_from_slice(&self.line[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::output;
+use crate::{shown, Error};
+
+use super::columns::{column_index, ColumnSelection};
+use super::format::{OutputFormat, TableOutput};
+use super::{ReadOptions, PAGE_SIZE};
+
+// Compares two versions of a CSV keyed on `--key`, listing added (+), removed (-)
+// and changed (~) rows; changed cells are shown as `old -> new`
+pub fn write_csv_diff(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Table(Default::default()))?;
+    let key = args.value("key")?;
+    let sources = args.finish()?;
+
+    let Some(key) = key else {
+        return Err(Error::invalid("csv-diff requires --key <columns>"));
+    };
+    let [old_source, new_source] = sources.as_slice() else {
+        return Err(Error::invalid("csv-diff expects two inputs: <old> <new>"));
+    };
+
+    let mut old_reader = read_options.open(old_source)?;
+    let mut new_reader = read_options.open(new_source)?;
+    let old_headers = read_options.headers(&mut old_reader)?;
+    let new_headers = read_options.headers(&mut new_reader)?;
+    let old_key = ColumnSelection::resolve(&key, old_headers.as_ref())?;
+    let new_key = ColumnSelection::resolve(&key, new_headers.as_ref())?;
+
+    // Old rows are kept in order so removed ones are reported as they appeared
+    let mut old_rows: Vec<Option<StringRecord>> = Vec::new();
+    let mut index: HashMap<Vec<String>, usize> = HashMap::new();
+    for record in old_reader.records() {
+        let record = record?;
+        let key = key_of(&old_key, &record);
+        if index.contains_key(&key) {
+            return Err(duplicate(&key, old_source, &record));
+        }
+        index.insert(key, old_rows.len());
+        old_rows.push(Some(record));
+    }
+
+    let width = new_headers.as_ref().map_or(0, StringRecord::len);
+    let mut diff_headers = vec!["change".to_string()];
+    match &new_headers {
+        Some(headers) => diff_headers.extend(headers.iter().map(String::from)),
+        None => diff_headers.extend((1..=width).map(|i| format!("col{}", i))),
+    }
+    let diff_headers = StringRecord::from(diff_headers);
+
+    // Position of every new column in the old file, matched by name
+    let old_columns: Vec<Option<usize>> = (0..width)
+        .map(|i| match (&new_headers, &old_headers) {
+            (Some(new_headers), Some(old_headers)) => {
+                column_index(&new_headers[i], Some(old_headers)).ok()
+            }
+            _ => Some(i),
+        })
+        .collect();
+
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    let mut table = TableOutput::new(&format, Some(&diff_headers), PAGE_SIZE);
+    // A second row with a key would otherwise show up as added
+    let mut seen = HashSet::new();
+    for record in new_reader.records() {
+        let record = record?;
+        let key = key_of(&new_key, &record);
+        if seen.contains(&key) {
+            return Err(duplicate(&key, new_source, &record));
+        }
+        let old = index.get(&key).and_then(|&row| old_rows[row].take());
+        seen.insert(key);
+
+        match old {
+            None => {
+                added += 1;
+                table.push(out, marked("+", record.iter()))?;
+            }
+            Some(old) => {
+                let mut any_change = false;
+                let cells: Vec<String> = (0..width.max(record.len()))
+                    .map(|i| {
+                        let new_value = record.get(i).unwrap_or("");
+                        let old_value = old_columns
+                            .get(i)
+                            .copied()
+                            .flatten()
+                            .and_then(|column| old.get(column))
+                            .unwrap_or("");
+                        if old_value == new_value {
+                            new_value.to_string()
+                        } else {
+                            any_change = true;
+                            format!("{} -> {}", old_value, new_value)
+                        }
+                    })
+                    .collect();
+
+                if any_change {
+                    changed += 1;
+                    table.push(out, marked("~", cells.iter().map(String::as_str)))?;
+                }
+            }
+        }
+    }
+
+    for old in old_rows.into_iter().flatten() {
+        removed += 1;
+        // Removed rows are laid out in the new file's columns
+        let cells: Vec<&str> = old_columns
+            .iter()
+            .map(|column| column.and_then(|column| old.get(column)).unwrap_or(""))
+            .collect();
+        table.push(out, marked("-", cells.into_iter()))?;
+    }
+    table.finish(out)?;
+
+    let summary = format!(
+        "({} added, {} removed, {} changed)",
+        added, removed, changed
+    );
+    if matches!(format, OutputFormat::Table(_)) {
+        writeln!(out, "{}", summary)?;
+    } else {
+        // Keeps machine readable output clean
+        output::warn(summary);
+    }
+
+    Ok(())
+}
+
+fn key_of(key: &ColumnSelection, record: &StringRecord) -> Vec<String> {
+    key.apply(record).iter().map(String::from).collect()
+}
+
+// Rows are matched by key, so it has to be unique in both files
+fn duplicate(key: &[String], source: &str, record: &StringRecord) -> Error {
+    Error::invalid(format!(
+        "Duplicate key {} in {} on line {}, --key has to be unique",
+        shown(&key.join(",")),
+        source,
+        record.position().map_or(0, |position| position.line())
+    ))
+}
+
+fn marked<'a>(mark: &str, cells: impl Iterator<Item = &'a str>) -> StringRecord {
+    let mut record = StringRecord::new();
+    record.push_field(mark);
+    for cell in cells {
+        record.push_field(cell);
+    }
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> Result<String, Error> {
+        let mut out = Vec::new();
+        let input = format!("--key id --format csv --inline '{}' '{}'", old, new);
+        write_csv_diff(&input, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn rows_are_matched_on_the_key() {
+        let output = diff("id,v\\n1,a\\n2,b", "id,v\\n1,c\\n3,d").unwrap();
+        assert_eq!(output, "change,id,v\n~,1,a -> c\n+,3,d\n-,2,b\n");
+    }
+
+    #[test]
+    fn duplicate_keys_in_the_old_input_are_errors() {
+        let e = diff("id,v\\n1,a\\n1,b", "id,v\\n1,c").unwrap_err();
+        assert!(e.to_string().starts_with("Duplicate key '1' in"), "{}", e);
+        assert!(e.to_string().contains("on line 3"), "{}", e);
+    }
+
+    #[test]
+    fn duplicate_keys_in_the_new_input_are_errors() {
+        // Also when the old input has the key once, which would hide the second row
+        for old in ["id,v\\n2,a", "id,v\\n1,a"] {
+            let e = diff(old, "id,v\\n2,c\\n2,d").unwrap_err();
+            assert!(e.to_string().starts_with("Duplicate key '2' in"), "{}", e);
+        }
+    }
+
+    #[test]
+    fn keys_of_several_columns_are_unique_together() {
+        let mut out = Vec::new();
+        let input = "--key id,part --format csv --inline 'id,part,v\\n1,x,a\\n1,y,b' 'id,part,v\\n1,x,a\\n1,y,c'";
+        write_csv_diff(input, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "change,id,part,v\n~,1,y,b -> c\n"
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use csv::StringRecord;
+
+use crate::redaction::shown;
+use crate::Error;
+
+use super::columns::column_index;
+use super::types::format_number;
+
+// Result of evaluating an expression, cells are always read as text
+#[derive(Clone, Debug)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+}
+
+impl Value {
+    // Text counts as a number when it parses as one, so `price * 2` works on cells
+    fn number(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            Value::Text(text) => text.trim().parse().ok().filter(|n: &f64| n.is_finite()),
+            Value::Bool(_) => None,
+        }
+    }
+
+    pub fn truthy(&self) -> bool {
+        match self {
+            Value::Number(number) => *number != 0.0,
+            Value::Text(text) => !text.is_empty(),
+            Value::Bool(value) => *value,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", format_number(*number)),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Function {
+    Upper,
+    Lower,
+    Trim,
+    Len,
+    Round,
+    Abs,
+    If,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<(Function, usize)> {
+        // Each function with the number of arguments it takes
+        match name {
+            "upper" => Some((Function::Upper, 1)),
+            "lower" => Some((Function::Lower, 1)),
+            "trim" => Some((Function::Trim, 1)),
+            "len" => Some((Function::Len, 1)),
+            "round" => Some((Function::Round, 1)),
+            "abs" => Some((Function::Abs, 1)),
+            "if" => Some((Function::If, 3)),
+            _ => None,
+        }
+    }
+}
+
+// A parsed expression with column names resolved to positions, e.g.
+// `first + ' ' + last`, `price * quantity` or `if(total > 100, 'big', 'small')`
+#[derive(Debug)]
+pub enum Expr {
+    Literal(Value),
+    Column(usize),
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    pub fn parse(source: &str, headers: Option<&StringRecord>) -> Result<Expr, Error> {
+        let parse = || {
+            let tokens = tokenize(source)?;
+            let mut parser = Parser {
+                tokens: &tokens,
+                position: 0,
+                headers,
+            };
+
+            let expr = parser.or()?;
+            match parser.peek() {
+                None => Ok(expr),
+                Some(token) => Err(format!("unexpected {}", token)),
+            }
+        };
+
+        parse().map_err(|reason| {
+            Error::invalid(format!("Invalid expression '{}': {}", source, reason)) as Error
+        })
+    }
+
+    // Errors are plain messages so the caller can say which row failed
+    pub fn eval(&self, record: &StringRecord) -> Result<Value, String> {
+        match self {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Column(index) => Ok(Value::Text(record.get(*index).unwrap_or("").to_string())),
+            Expr::Negate(expr) => {
+                let value = expr.eval(record)?;
+                Ok(Value::Number(-required_number("-", &value)?))
+            }
+            Expr::Not(expr) => Ok(Value::Bool(!expr.eval(record)?.truthy())),
+            Expr::Binary(op, left, right) => {
+                let left = left.eval(record)?;
+                // `&&` and `||` don't evaluate their right side when the left decides
+                match op {
+                    BinaryOp::And if !left.truthy() => return Ok(Value::Bool(false)),
+                    BinaryOp::Or if left.truthy() => return Ok(Value::Bool(true)),
+                    _ => {}
+                }
+                binary(*op, left, right.eval(record)?)
+            }
+            Expr::Call(function, args) => call(*function, args, record),
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, String> {
+    let numbers = left.number().zip(right.number());
+    match op {
+        BinaryOp::Add => match numbers {
+            Some((a, b)) => Ok(Value::Number(a + b)),
+            // Anything that isn't a number is concatenated
+            None => Ok(Value::Text(format!("{}{}", left, right))),
+        },
+        BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => {
+            let symbol = match op {
+                BinaryOp::Sub => "-",
+                BinaryOp::Mul => "*",
+                BinaryOp::Div => "/",
+                _ => "%",
+            };
+            let a = required_number(symbol, &left)?;
+            let b = required_number(symbol, &right)?;
+            if matches!(op, BinaryOp::Div | BinaryOp::Rem) && b == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            Ok(Value::Number(match op {
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                _ => a % b,
+            }))
+        }
+        BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            // Numbers compare numerically, everything else as text
+            let ordering = match numbers {
+                Some((a, b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                None => left.to_string().cmp(&right.to_string()),
+            };
+            Ok(Value::Bool(match op {
+                BinaryOp::Eq => ordering == Ordering::Equal,
+                BinaryOp::Ne => ordering != Ordering::Equal,
+                BinaryOp::Lt => ordering == Ordering::Less,
+                BinaryOp::Le => ordering != Ordering::Greater,
+                BinaryOp::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            }))
+        }
+        BinaryOp::And | BinaryOp::Or => Ok(Value::Bool(right.truthy())),
+    }
+}
+
+fn call(function: Function, args: &[Expr], record: &StringRecord) -> Result<Value, String> {
+    let first = || args[0].eval(record);
+    match function {
+        Function::Upper => Ok(Value::Text(first()?.to_string().to_uppercase())),
+        Function::Lower => Ok(Value::Text(first()?.to_string().to_lowercase())),
+        Function::Trim => Ok(Value::Text(first()?.to_string().trim().to_string())),
+        Function::Len => Ok(Value::Number(first()?.to_string().chars().count() as f64)),
+        Function::Round => Ok(Value::Number(required_number("round", &first()?)?.round())),
+        Function::Abs => Ok(Value::Number(required_number("abs", &first()?)?.abs())),
+        // Only the selected branch is evaluated
+        Function::If if first()?.truthy() => args[1].eval(record),
+        Function::If => args[2].eval(record),
+    }
+}
+
+fn required_number(operation: &str, value: &Value) -> Result<f64, String> {
+    value.number().ok_or_else(|| {
+        format!(
+            "'{}' needs numbers, found {}",
+            operation,
+            shown(&value.to_string())
+        )
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    // Column or function name, `quoted` names can contain any character
+    Name(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "number {}", format_number(*number)),
+            Token::Text(text) => write!(f, "text '{}'", text),
+            Token::Name(name) => write!(f, "'{}'", name),
+            Token::Symbol(symbol) => write!(f, "'{}'", symbol),
+        }
+    }
+}
+
+// Longer symbols first so `<=` isn't read as `<`
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "(", ")", ",",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\'' || c == '"' || c == '`' {
+            let Some(end) = rest[1..].find(c) else {
+                return Err(format!("missing closing {}", c));
+            };
+            let content = rest[1..=end].to_string();
+            tokens.push(if c == '`' {
+                Token::Name(content)
+            } else {
+                Token::Text(content)
+            });
+            rest = &rest[end + 2..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| format!("invalid number {}", &rest[..end]))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '.')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+// Recursive descent, one method per precedence level from loosest to tightest
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    headers: Option<&'a StringRecord>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn symbol(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Symbol(symbol)) if symbols.contains(symbol) => {
+                let symbol = *symbol;
+                self.position += 1;
+                Some(symbol)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), String> {
+        match self.symbol(&[symbol]) {
+            Some(_) => Ok(()),
+            None => Err(format!("expected '{}'", symbol)),
+        }
+    }
+
+    fn binary_level(
+        &mut self,
+        symbols: &[&'static str],
+        operand: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        let mut expr = operand(self)?;
+        while let Some(symbol) = self.symbol(symbols) {
+            let op = match symbol {
+                "||" => BinaryOp::Or,
+                "&&" => BinaryOp::And,
+                "==" => BinaryOp::Eq,
+                "!=" => BinaryOp::Ne,
+                "<" => BinaryOp::Lt,
+                "<=" => BinaryOp::Le,
+                ">" => BinaryOp::Gt,
+                ">=" => BinaryOp::Ge,
+                "+" => BinaryOp::Add,
+                "-" => BinaryOp::Sub,
+                "*" => BinaryOp::Mul,
+                "/" => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(operand(self)?));
+        }
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["||"], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["&&"], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["==", "!=", "<", "<=", ">", ">="], Self::sum)
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["+", "-"], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        self.binary_level(&["*", "/", "%"], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.symbol(&["-", "!"]) {
+            Some("-") => Ok(Expr::Negate(Box::new(self.unary()?))),
+            Some(_) => Ok(Expr::Not(Box::new(self.unary()?))),
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Literal(Value::Number(number))),
+            Some(Token::Text(text)) => Ok(Expr::Literal(Value::Text(text))),
+            Some(Token::Symbol("(")) => {
+                let expr = self.or()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) if self.symbol(&["("]).is_some() => self.call(&name),
+            Some(Token::Name(name)) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                _ => column_index(&name, self.headers)
+                    .map(Expr::Column)
+                    .map_err(|e| e.to_string()),
+            },
+            Some(token) => Err(format!("unexpected {}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Expr, String> {
+        let Some((function, arity)) = Function::from_name(&name.to_lowercase()) else {
+            return Err(format!(
+                "unknown function '{}', available functions are: upper, lower, trim, len, round, abs, if",
+                name
+            ));
+        };
+
+        let mut args = Vec::new();
+        if self.symbol(&[")"]).is_none() {
+            loop {
+                args.push(self.or()?);
+                if self.symbol(&[","]).is_none() {
+                    break;
+                }
+            }
+            self.expect(")")?;
+        }
+
+        if args.len() != arity {
+            return Err(format!(
+                "{}() takes {} arguments, found {}",
+                name,
+                arity,
+                args.len()
+            ));
+        }
+        Ok(Expr::Call(function, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str) -> String {
+        let headers = StringRecord::from(vec!["price", "qty", "name"]);
+        let record = StringRecord::from(vec!["2.5", "4", " ada "]);
+        Expr::parse(source, Some(&headers))
+            .unwrap()
+            .eval(&record)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn products_bind_tighter_than_sums() {
+        assert_eq!(eval("1 + 2 * 3"), "7");
+        assert_eq!(eval("(1 + 2) * 3"), "9");
+        assert_eq!(eval("10 - 4 - 3"), "3");
+        assert_eq!(eval("7 % 4 * 2"), "6");
+        assert_eq!(eval("-price * 2"), "-5");
+    }
+
+    #[test]
+    fn comparisons_bind_tighter_than_and_and_or() {
+        assert_eq!(eval("qty > 3 && price < 3"), "true");
+        assert_eq!(eval("qty < 3 || price < 3 && qty == 4"), "true");
+        assert_eq!(eval("!(qty == 4)"), "false");
+        assert_eq!(eval("price * qty >= 10"), "true");
+    }
+
+    #[test]
+    fn cells_are_numbers_when_they_parse_as_ones() {
+        assert_eq!(eval("price * qty"), "10");
+        assert_eq!(eval("qty + 1"), "5");
+        // Text is concatenated and compared as text
+        assert_eq!(eval("trim(name) + '!'"), "ada!");
+        assert_eq!(eval("'10' < '9'"), "false");
+        assert_eq!(eval("'b' > 'a'"), "true");
+    }
+
+    #[test]
+    fn functions_and_columns() {
+        assert_eq!(eval("upper(trim(name))"), "ADA");
+        assert_eq!(eval("len(name)"), "5");
+        assert_eq!(eval("round(price)"), "3");
+        assert_eq!(eval("if(qty > 3, 'many', 'few')"), "many");
+        assert_eq!(eval("`qty` * 2"), "8");
+    }
+
+    #[test]
+    fn invalid_expressions_are_errors() {
+        let headers = StringRecord::from(vec!["price"]);
+        for source in [
+            "1 +",
+            "(1 + 2",
+            "1 2",
+            "price $ 2",
+            "'open",
+            "missing",
+            "nope(1)",
+        ] {
+            assert!(Expr::parse(source, Some(&headers)).is_err(), "{}", source);
+        }
+        let record = StringRecord::from(vec!["x"]);
+        let expr = Expr::parse("price / 0", Some(&headers)).unwrap();
+        assert_eq!(
+            expr.eval(&record).unwrap_err(),
+            "'/' needs numbers, found 'x'"
+        );
+        let expr = Expr::parse("1 / 0", None).unwrap();
+        assert_eq!(expr.eval(&record).unwrap_err(), "division by zero");
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+use csv::WriterBuilder;
+
+use crate::Error;
+
+// Where the columns of fixed-width input start
+pub enum FixedWidths {
+    // `--widths 10,8,20`
+    Given(Vec<usize>),
+    // `--fixed-width`, taken from a ruler line like `---------- --------` as
+    // the first line or right under the header
+    Ruler,
+}
+
+impl FixedWidths {
+    pub fn parse(spec: &str) -> Result<FixedWidths, Error> {
+        let widths = spec
+            .split(',')
+            .map(|width| match width.trim().parse::<usize>() {
+                Ok(width) if width > 0 => Ok(width),
+                _ => Err(Error::invalid(format!(
+                    "Invalid --widths {}, expected positive numbers like 10,8,20",
+                    spec
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(FixedWidths::Given(widths))
+    }
+}
+
+// Turns fixed-width lines into CSV lines for the csv reader, fields are trimmed
+// and the last column runs to the end of the line
+pub struct FixedWidthReader<R> {
+    inner: BufReader<R>,
+    // Character offset where every column starts, `None` until the ruler was seen
+    starts: Option<Vec<usize>>,
+    // Writes the CSV lines in the dialect the csv reader expects
+    builder: WriterBuilder,
+    line: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> FixedWidthReader<R> {
+    pub fn new(
+        inner: R,
+        widths: &FixedWidths,
+        builder: WriterBuilder,
+    ) -> Result<FixedWidthReader<R>, Error> {
+        let mut reader = FixedWidthReader {
+            inner: BufReader::new(inner),
+            starts: None,
+            builder,
+            line: Vec::new(),
+            position: 0,
+        };
+
+        match widths {
+            FixedWidths::Given(widths) => {
+                let starts = widths
+                    .iter()
+                    .scan(0, |start, width| {
+                        let column = *start;
+                        *start += width;
+                        Some(column)
+                    })
+                    .collect();
+                reader.starts = Some(starts);
+            }
+            FixedWidths::Ruler => {
+                let first = reader.read_line()?;
+                if let Some(starts) = ruler(&first) {
+                    reader.starts = Some(starts);
+                } else {
+                    let second = reader.read_line()?;
+                    let Some(starts) = ruler(&second) else {
+                        return Err(Error::invalid("No ruler line of dashes found in the first two lines, pass --widths instead"));
+                    };
+                    reader.starts = Some(starts);
+                    reader.convert(&first)?;
+                }
+            }
+        }
+        Ok(reader)
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        self.inner.read_line(&mut line)?;
+        Ok(line)
+    }
+
+    fn convert(&mut self, line: &str) -> io::Result<()> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let starts = self.starts.as_deref().unwrap_or(&[0]);
+
+        let fields = starts.iter().enumerate().map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(usize::MAX);
+            let field: String = line.chars().skip(start).take(end - start).collect();
+            field.trim().to_string()
+        });
+        let mut writer = self.builder.from_writer(&mut self.line);
+        writer.write_record(fields)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for FixedWidthReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position >= self.line.len() {
+            self.line.clear();
+            self.position = 0;
+            let line = self.read_line()?;
+            if line.is_empty() {
+                return Ok(0);
+            }
+            if !line.trim().is_empty() {
+                self.convert(&line)?;
+            }
+        }
+
+        let count = buf.len().min(self.line.len() - self.position);
+        buf[..count].copy_from_slice(&self.line[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+// Start of every run of `-` or `=` when the line consists only of those and spaces
+fn ruler(line: &str) -> Option<Vec<usize>> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.trim().is_empty() || !line.chars().all(|c| matches!(c, '-' | '=' | ' ' | '+')) {
+        return None;
+    }
+
+    let mut starts = Vec::new();
+    let mut previous = ' ';
+    for (i, c) in line.chars().enumerate() {
+        if c != ' ' && c != '+' && (previous == ' ' || previous == '+') {
+            starts.push(i);
+        }
+        previous = c;
+    }
+    Some(starts)
+}
@@ -0,0 +1,41 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::Error;
+
+use super::TableFormatter;
+
+// AsciiDoc `|===` table with a header row
+pub struct AsciiDocFormatter;
+
+impl AsciiDocFormatter {
+    fn write_row(out: &mut dyn Write, record: &StringRecord) -> Result<(), Error> {
+        let cells: Vec<String> = record.iter().map(escape).collect();
+        writeln!(out, "| {}", cells.join(" | "))?;
+        Ok(())
+    }
+}
+
+impl TableFormatter for AsciiDocFormatter {
+    fn header(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        writeln!(out, "[options=\"header\"]")?;
+        writeln!(out, "|===")?;
+        AsciiDocFormatter::write_row(out, headers)?;
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        AsciiDocFormatter::write_row(out, &record)
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> Result<(), Error> {
+        writeln!(out, "|===")?;
+        Ok(())
+    }
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " +\n")
+}
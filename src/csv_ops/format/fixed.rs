@@ -0,0 +1,129 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+
+use csv::{Reader, ReaderBuilder, StringRecord, Writer, WriterBuilder};
+
+use crate::{memory, Error};
+
+use super::TableFormatter;
+
+// Fixed-width columns under a ruler line of dashes, which `--fixed-width` reads back.
+// Column widths depend on every value, so rows are held until the end, in a
+// temporary file once they go over the memory budget
+#[derive(Default)]
+pub struct FixedWidthFormatter {
+    headers: StringRecord,
+    widths: Vec<usize>,
+    rows: Vec<StringRecord>,
+    held: usize,
+    spilled: Option<Writer<BufWriter<File>>>,
+}
+
+impl FixedWidthFormatter {
+    fn write_row(
+        out: &mut dyn Write,
+        cells: impl Iterator<Item = String>,
+        widths: &[usize],
+    ) -> Result<(), Error> {
+        let cells: Vec<String> = cells
+            .zip(widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        writeln!(out, "{}", cells.join(" ").trim_end())?;
+        Ok(())
+    }
+
+    fn measure(&mut self, record: &StringRecord) {
+        if self.widths.len() < record.len() {
+            self.widths.resize(record.len(), 1);
+        }
+        for (width, cell) in self.widths.iter_mut().zip(record.iter()) {
+            *width = (*width).max(cell_text(cell).chars().count());
+        }
+    }
+
+    // Moves the held rows to a temporary file, where the rest follow them
+    fn spill(&mut self) -> Result<(), Error> {
+        let file = tempfile::tempfile()?;
+        let mut writer = WriterBuilder::new()
+            .flexible(true)
+            .from_writer(BufWriter::new(file));
+        for record in self.rows.drain(..) {
+            writer.write_record(&record)?;
+        }
+        self.spilled = Some(writer);
+        Ok(())
+    }
+
+    // The spilled rows read back from the start of their file
+    fn spilled_rows(writer: Writer<BufWriter<File>>) -> Result<Reader<File>, Error> {
+        let mut file = writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .into_inner()
+            .map_err(|e| e.into_error())?;
+        file.rewind()?;
+        Ok(ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(file))
+    }
+}
+
+impl TableFormatter for FixedWidthFormatter {
+    fn header(&mut self, _out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        self.headers = headers.clone();
+        self.measure(headers);
+        Ok(())
+    }
+
+    fn widen(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        self.header(out, headers)
+    }
+
+    fn row(&mut self, _out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        self.measure(&record);
+        if let Some(writer) = &mut self.spilled {
+            writer.write_record(&record)?;
+            return Ok(());
+        }
+        self.held += record.as_slice().len();
+        self.rows.push(record);
+        if memory::over_budget(self.held) {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> Result<(), Error> {
+        let widths = &self.widths;
+        let columns = widths.len();
+        let cells = |record: &StringRecord| {
+            (0..columns)
+                .map(|i| cell_text(record.get(i).unwrap_or("")))
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        FixedWidthFormatter::write_row(out, cells(&self.headers), widths)?;
+        FixedWidthFormatter::write_row(out, widths.iter().map(|&width| "-".repeat(width)), widths)?;
+        match self.spilled.take() {
+            Some(writer) => {
+                let mut reader = FixedWidthFormatter::spilled_rows(writer)?;
+                for record in reader.records() {
+                    FixedWidthFormatter::write_row(out, cells(&record?), widths)?;
+                }
+            }
+            None => {
+                for record in &self.rows {
+                    FixedWidthFormatter::write_row(out, cells(record), widths)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Line breaks would end the row
+fn cell_text(cell: &str) -> String {
+    cell.replace("\r\n", " ").replace('\n', " ")
+}
@@ -0,0 +1,71 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::Error;
+
+use super::TableFormatter;
+
+// `<table>` with `<thead>` and `<tbody>`, optionally with odd/even row classes
+pub struct HtmlFormatter {
+    zebra: bool,
+    rows: usize,
+}
+
+impl HtmlFormatter {
+    pub fn new(zebra: bool) -> HtmlFormatter {
+        HtmlFormatter { zebra, rows: 0 }
+    }
+}
+
+impl TableFormatter for HtmlFormatter {
+    fn header(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        writeln!(out, "<table>")?;
+        writeln!(out, "  <thead>")?;
+        write!(out, "    <tr>")?;
+        for header in headers {
+            write!(out, "<th>{}</th>", escape(header))?;
+        }
+        writeln!(out, "</tr>")?;
+        writeln!(out, "  </thead>")?;
+        writeln!(out, "  <tbody>")?;
+        Ok(())
+    }
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        self.rows += 1;
+
+        if self.zebra {
+            let class = if self.rows % 2 == 1 { "odd" } else { "even" };
+            write!(out, "    <tr class=\"{}\">", class)?;
+        } else {
+            write!(out, "    <tr>")?;
+        }
+        for field in &record {
+            write!(out, "<td>{}</td>", escape(field))?;
+        }
+        writeln!(out, "</tr>")?;
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> Result<(), Error> {
+        writeln!(out, "  </tbody>")?;
+        writeln!(out, "</table>")?;
+        Ok(())
+    }
+}
+
+fn escape(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+    for c in cell.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,56 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::Error;
+
+use super::TableFormatter;
+
+// `tabular` environment with left aligned columns
+pub struct LatexFormatter;
+
+impl LatexFormatter {
+    fn write_row(out: &mut dyn Write, record: &StringRecord) -> Result<(), Error> {
+        let cells: Vec<String> = record.iter().map(escape).collect();
+        writeln!(out, "{} \\\\", cells.join(" & "))?;
+        Ok(())
+    }
+}
+
+impl TableFormatter for LatexFormatter {
+    fn header(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        writeln!(out, "\\begin{{tabular}}{{{}}}", "l".repeat(headers.len()))?;
+        writeln!(out, "\\hline")?;
+        LatexFormatter::write_row(out, headers)?;
+        writeln!(out, "\\hline")?;
+        Ok(())
+    }
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        LatexFormatter::write_row(out, &record)
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> Result<(), Error> {
+        writeln!(out, "\\hline")?;
+        writeln!(out, "\\end{{tabular}}")?;
+        Ok(())
+    }
+}
+
+fn escape(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+    for c in cell.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
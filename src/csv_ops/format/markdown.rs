@@ -0,0 +1,45 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::Error;
+
+use super::TableFormatter;
+
+// GitHub flavored Markdown table
+#[derive(Default)]
+pub struct MarkdownFormatter {
+    width: usize,
+}
+
+impl MarkdownFormatter {
+    // Rows are padded or cut to the header width, as Markdown tables require
+    fn write_row(&self, out: &mut dyn Write, record: &StringRecord) -> Result<(), Error> {
+        write!(out, "|")?;
+        for i in 0..self.width {
+            write!(out, " {} |", escape(record.get(i).unwrap_or("")))?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+impl TableFormatter for MarkdownFormatter {
+    fn header(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        self.width = headers.len().max(1);
+        self.write_row(out, headers)?;
+        writeln!(out, "|{}", " --- |".repeat(self.width))?;
+        Ok(())
+    }
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        self.write_row(out, &record)
+    }
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
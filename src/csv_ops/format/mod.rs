@@ -0,0 +1,280 @@
+mod asciidoc;
+mod fixed;
+mod html;
+mod latex;
+mod markdown;
+mod org;
+mod rst;
+
+use std::io::Write;
+
+use csv::{QuoteStyle, StringRecord, Terminator, WriterBuilder};
+
+use crate::args::OperationArgs;
+use crate::{cancel, Error};
+
+use super::render::{PagedTable, TableLayout};
+
+use asciidoc::AsciiDocFormatter;
+use fixed::FixedWidthFormatter;
+use html::HtmlFormatter;
+use latex::LatexFormatter;
+use markdown::MarkdownFormatter;
+use org::OrgFormatter;
+use rst::RstFormatter;
+
+// Output selected with `--format`
+#[derive(Clone)]
+pub enum OutputFormat {
+    Table(TableLayout),
+    Csv(WriteOptions),
+    Markdown,
+    // `--zebra` adds alternating odd/even classes to the body rows
+    Html { zebra: bool },
+    Latex,
+    Org,
+    AsciiDoc,
+    Rst,
+    FixedWidth,
+}
+
+const FORMAT_NAMES: &str = "table, csv, markdown, html, latex, org, asciidoc, rst, fixed";
+
+impl OutputFormat {
+    pub fn from_args(
+        args: &mut OperationArgs,
+        default: OutputFormat,
+    ) -> Result<OutputFormat, Error> {
+        let zebra = args.flag("zebra");
+        let write_options = WriteOptions::from_args(args)?;
+        let layout = TableLayout::from_args(args)?;
+        let format = match args.value("format")? {
+            Some(format) => OutputFormat::from_str(&format)?,
+            None => default,
+        };
+
+        if zebra && !matches!(format, OutputFormat::Html { .. }) {
+            return Err(Error::invalid(
+                "--zebra is only supported with --format html",
+            ));
+        }
+        if write_options.is_some() && !matches!(format, OutputFormat::Csv(_)) {
+            return Err(Error::invalid("--quote-style, --terminator and --out-delimiter are only supported with --format csv"));
+        }
+        if layout.is_some() && !matches!(format, OutputFormat::Table(_)) {
+            return Err(Error::invalid("--max-col-width, --width, --wrap and --truncate are only supported with --format table"));
+        }
+
+        Ok(match format {
+            OutputFormat::Html { .. } => OutputFormat::Html { zebra },
+            OutputFormat::Csv(default) => OutputFormat::Csv(write_options.unwrap_or(default)),
+            OutputFormat::Table(default) => OutputFormat::Table(layout.unwrap_or(default)),
+            format => format,
+        })
+    }
+
+    fn from_str(s: &str) -> Result<OutputFormat, Error> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table(TableLayout::default())),
+            "csv" => Ok(OutputFormat::Csv(WriteOptions::default())),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "html" => Ok(OutputFormat::Html { zebra: false }),
+            "latex" | "tex" => Ok(OutputFormat::Latex),
+            "org" => Ok(OutputFormat::Org),
+            "asciidoc" | "adoc" => Ok(OutputFormat::AsciiDoc),
+            "rst" => Ok(OutputFormat::Rst),
+            "fixed" | "fixed-width" => Ok(OutputFormat::FixedWidth),
+            _ => Err(Error::invalid(format!(
+                "Invalid format: {}, available formats are: {}",
+                s, FORMAT_NAMES
+            ))),
+        }
+    }
+
+    fn formatter(&self, page_size: usize) -> Box<dyn TableFormatter> {
+        match self {
+            OutputFormat::Table(layout) => Box::new(PagedTable::new(page_size, layout.clone())),
+            OutputFormat::Csv(write_options) => Box::new(CsvFormatter {
+                builder: write_options.builder(),
+            }),
+            OutputFormat::Markdown => Box::new(MarkdownFormatter::default()),
+            OutputFormat::Html { zebra } => Box::new(HtmlFormatter::new(*zebra)),
+            OutputFormat::Latex => Box::new(LatexFormatter),
+            OutputFormat::Org => Box::new(OrgFormatter),
+            OutputFormat::AsciiDoc => Box::new(AsciiDocFormatter),
+            OutputFormat::Rst => Box::new(RstFormatter),
+            OutputFormat::FixedWidth => Box::new(FixedWidthFormatter::default()),
+        }
+    }
+}
+
+// How CSV output is written, set with `--quote-style`, `--terminator` and `--out-delimiter`
+#[derive(Clone, Copy)]
+pub struct WriteOptions {
+    quote_style: QuoteStyle,
+    terminator: Terminator,
+    delimiter: u8,
+}
+
+impl Default for WriteOptions {
+    fn default() -> WriteOptions {
+        WriteOptions {
+            quote_style: QuoteStyle::Necessary,
+            terminator: Terminator::Any(b'\n'),
+            delimiter: b',',
+        }
+    }
+}
+
+impl WriteOptions {
+    // `None` when none of the options were given
+    fn from_args(args: &mut OperationArgs) -> Result<Option<WriteOptions>, Error> {
+        let quote_style = args.value("quote-style")?;
+        let terminator = args.value("terminator")?;
+        let delimiter = super::byte_option(args, "out-delimiter")?;
+        if quote_style.is_none() && terminator.is_none() && delimiter.is_none() {
+            return Ok(None);
+        }
+
+        let mut options = WriteOptions::default();
+        if let Some(quote_style) = quote_style {
+            options.quote_style = match quote_style.to_lowercase().as_str() {
+                "necessary" => QuoteStyle::Necessary,
+                "always" => QuoteStyle::Always,
+                "never" => QuoteStyle::Never,
+                "non-numeric" => QuoteStyle::NonNumeric,
+                _ => {
+                    return Err(Error::invalid(format!(
+                        "Invalid quote style: {}, available styles are: necessary, always, never, non-numeric",
+                        quote_style
+                    )))
+                }
+            };
+        }
+        if let Some(terminator) = terminator {
+            options.terminator = match terminator.to_lowercase().as_str() {
+                "lf" => Terminator::Any(b'\n'),
+                "crlf" => Terminator::CRLF,
+                _ => {
+                    return Err(Error::invalid(format!(
+                        "Invalid terminator: {}, available terminators are: lf, crlf",
+                        terminator
+                    )))
+                }
+            };
+        }
+        if let Some(delimiter) = delimiter {
+            options.delimiter = delimiter;
+        }
+
+        Ok(Some(options))
+    }
+
+    fn builder(&self) -> WriterBuilder {
+        let mut builder = WriterBuilder::new();
+        builder
+            .quote_style(self.quote_style)
+            .terminator(self.terminator)
+            .delimiter(self.delimiter);
+        builder
+    }
+}
+
+// Serializes a table row by row, so large inputs never have to be held in memory
+pub trait TableFormatter {
+    // Called once before the first row
+    fn header(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error>;
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error>;
+
+    // Called without a header row when a record is wider than the generated
+    // headers, before it is passed to `row`. Formats that wrote their header
+    // line already keep it
+    fn widen(&mut self, _out: &mut dyn Write, _headers: &StringRecord) -> Result<(), Error> {
+        Ok(())
+    }
+
+    // Called after the last row, only when at least one row was written
+    fn finish(&mut self, _out: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Feeds rows to the formatter of the selected format
+pub struct TableOutput<'a> {
+    formatter: Box<dyn TableFormatter>,
+    headers: Option<&'a StringRecord>,
+    started: bool,
+    // The number of generated headers, when there is no header row
+    generated: usize,
+}
+
+impl<'a> TableOutput<'a> {
+    // Without headers, columns are named col1, col2, ... after the widest row
+    // seen so far
+    pub fn new(
+        format: &OutputFormat,
+        headers: Option<&'a StringRecord>,
+        page_size: usize,
+    ) -> TableOutput<'a> {
+        TableOutput {
+            formatter: format.formatter(page_size),
+            headers,
+            started: false,
+            generated: 0,
+        }
+    }
+
+    pub fn push(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        cancel::check_timeout()?;
+        if !self.started {
+            match self.headers {
+                Some(headers) => self.formatter.header(out, headers)?,
+                None => {
+                    self.generated = record.len();
+                    self.formatter.header(out, &generated(self.generated))?;
+                }
+            }
+            self.started = true;
+        } else if self.headers.is_none() && record.len() > self.generated {
+            self.generated = record.len();
+            self.formatter.widen(out, &generated(self.generated))?;
+        }
+
+        self.formatter.row(out, record)
+    }
+
+    pub fn finish(mut self, out: &mut dyn Write) -> Result<(), Error> {
+        if self.started {
+            self.formatter.finish(out)?;
+        }
+        Ok(())
+    }
+}
+
+fn generated(columns: usize) -> StringRecord {
+    (1..=columns).map(|i| format!("col{}", i)).collect()
+}
+
+struct CsvFormatter {
+    builder: WriterBuilder,
+}
+
+impl CsvFormatter {
+    fn write(&self, out: &mut dyn Write, record: &StringRecord) -> Result<(), Error> {
+        let mut writer = self.builder.from_writer(out);
+        writer.write_record(record)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl TableFormatter for CsvFormatter {
+    fn header(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        self.write(out, headers)
+    }
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        self.write(out, &record)
+    }
+}
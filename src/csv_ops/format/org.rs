@@ -0,0 +1,34 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::Error;
+
+use super::TableFormatter;
+
+// Emacs org-mode table, org aligns the columns itself
+pub struct OrgFormatter;
+
+impl OrgFormatter {
+    fn write_row(out: &mut dyn Write, record: &StringRecord) -> Result<(), Error> {
+        let cells: Vec<String> = record.iter().map(escape).collect();
+        writeln!(out, "| {} |", cells.join(" | "))?;
+        Ok(())
+    }
+}
+
+impl TableFormatter for OrgFormatter {
+    fn header(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        OrgFormatter::write_row(out, headers)?;
+        writeln!(out, "|{}|", vec!["---"; headers.len().max(1)].join("+"))?;
+        Ok(())
+    }
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        OrgFormatter::write_row(out, &record)
+    }
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\vert{}").replace('\n', " ")
+}
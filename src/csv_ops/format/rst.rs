@@ -0,0 +1,35 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::Error;
+
+use super::TableFormatter;
+
+// reStructuredText `csv-table` directive, which unlike grid tables
+// doesn't need column widths up front and can be written row by row
+pub struct RstFormatter;
+
+impl RstFormatter {
+    fn cells(record: &StringRecord) -> String {
+        record
+            .iter()
+            .map(|cell| format!("\"{}\"", cell.replace('"', "\"\"").replace('\n', " ")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl TableFormatter for RstFormatter {
+    fn header(&mut self, out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        writeln!(out, ".. csv-table::")?;
+        writeln!(out, "   :header: {}", RstFormatter::cells(headers))?;
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        writeln!(out, "   {}", RstFormatter::cells(&record))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::redaction::shown;
+use crate::Error;
+
+use super::columns::{column_index, ColumnSelection};
+use super::format::{OutputFormat, TableOutput};
+use super::types::format_number;
+use super::{ReadOptions, PAGE_SIZE};
+
+#[derive(Clone, Copy)]
+enum Aggregator {
+    Sum,
+    Count,
+    Min,
+    Max,
+    Mean,
+}
+
+// One `name(column)` entry of `--agg`, `count()` has no column
+struct Aggregate {
+    aggregator: Aggregator,
+    column: Option<usize>,
+    label: String,
+}
+
+impl Aggregate {
+    fn parse(spec: &str, headers: Option<&StringRecord>) -> Result<Aggregate, Error> {
+        let invalid = || {
+            Error::invalid(format!(
+                "Invalid aggregate: {}, expected e.g. sum(amount) or count()",
+                spec
+            ))
+        };
+
+        let (name, rest) = spec.split_once('(').ok_or_else(invalid)?;
+        let column = rest.strip_suffix(')').ok_or_else(invalid)?.trim();
+
+        let aggregator = match name.trim().to_lowercase().as_str() {
+            "sum" => Aggregator::Sum,
+            "count" => Aggregator::Count,
+            "min" => Aggregator::Min,
+            "max" => Aggregator::Max,
+            "mean" | "avg" => Aggregator::Mean,
+            other => {
+                return Err(Error::invalid(format!(
+                    "Unknown aggregator: {}, available aggregators are: sum, count, min, max, mean",
+                    other
+                )))
+            }
+        };
+
+        let column = match (aggregator, column.is_empty()) {
+            (Aggregator::Count, true) => None,
+            (_, true) => return Err(invalid()),
+            (_, false) => Some(column_index(column, headers)?),
+        };
+
+        Ok(Aggregate {
+            aggregator,
+            column,
+            label: spec.trim().to_string(),
+        })
+    }
+}
+
+// Running values of one aggregate within one group
+#[derive(Clone)]
+struct Accumulator {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Accumulator {
+    fn new() -> Accumulator {
+        Accumulator {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn add(&mut self, aggregate: &Aggregate, record: &StringRecord) -> Result<(), Error> {
+        let Some(column) = aggregate.column else {
+            self.count += 1;
+            return Ok(());
+        };
+
+        let value = record.get(column).unwrap_or("");
+        if value.is_empty() {
+            return Ok(());
+        }
+        self.count += 1;
+
+        if let Aggregator::Count = aggregate.aggregator {
+            return Ok(());
+        }
+        let number: f64 = value.parse().map_err(|_| {
+            Error::invalid(format!(
+                "{} needs numeric values, found {}",
+                aggregate.label,
+                shown(value)
+            ))
+        })?;
+        self.sum += number;
+        self.min = self.min.min(number);
+        self.max = self.max.max(number);
+        Ok(())
+    }
+
+    fn result(&self, aggregator: Aggregator) -> String {
+        match aggregator {
+            Aggregator::Count => self.count.to_string(),
+            _ if self.count == 0 => String::new(),
+            Aggregator::Sum => format_number(self.sum),
+            Aggregator::Min => format_number(self.min),
+            Aggregator::Max => format_number(self.max),
+            Aggregator::Mean => format_number(self.sum / self.count as f64),
+        }
+    }
+}
+
+// Groups rows by the `--by` columns and aggregates each group, in first seen order
+pub fn write_csv_groupby(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Table(Default::default()))?;
+    let by = args.value("by")?;
+    let agg = args.value("agg")?.unwrap_or_else(|| "count()".to_string());
+    let source = args.finish()?.join(" ");
+
+    let Some(by) = by else {
+        return Err(Error::invalid("csv-groupby requires --by <columns>"));
+    };
+
+    let mut reader = read_options.open(&source)?;
+    let headers = read_options.headers(&mut reader)?;
+    let group_columns = ColumnSelection::resolve(&by, headers.as_ref())?;
+    let aggregates = agg
+        .split(',')
+        .filter(|spec| !spec.trim().is_empty())
+        .map(|spec| Aggregate::parse(spec, headers.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Groups in first seen order, with an index by key
+    let mut groups: Vec<(Vec<String>, Vec<Accumulator>)> = Vec::new();
+    let mut index: HashMap<Vec<String>, usize> = HashMap::new();
+    for record in reader.records() {
+        let record = record?;
+        let key: Vec<String> = group_columns
+            .apply(&record)
+            .iter()
+            .map(String::from)
+            .collect();
+
+        let group = *index.entry(key.clone()).or_insert_with(|| {
+            groups.push((key, vec![Accumulator::new(); aggregates.len()]));
+            groups.len() - 1
+        });
+        for (accumulator, aggregate) in groups[group].1.iter_mut().zip(&aggregates) {
+            accumulator.add(aggregate, &record)?;
+        }
+    }
+
+    let mut result_headers: Vec<String> = group_columns
+        .headers(headers.as_ref())
+        .iter()
+        .map(String::from)
+        .collect();
+    result_headers.extend(aggregates.iter().map(|aggregate| aggregate.label.clone()));
+    let result_headers = StringRecord::from(result_headers);
+
+    let mut table = TableOutput::new(&format, Some(&result_headers), PAGE_SIZE);
+    for (key, accumulators) in groups {
+        let mut row = key;
+        row.extend(
+            accumulators
+                .iter()
+                .zip(&aggregates)
+                .map(|(accumulator, aggregate)| accumulator.result(aggregate.aggregator)),
+        );
+        table.push(out, StringRecord::from(row))?;
+    }
+    table.finish(out)
+}
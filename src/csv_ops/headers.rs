@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::registry::find_nested;
+use crate::{Error, TextOperation};
+
+use super::format::{OutputFormat, TableOutput};
+use super::{ReadOptions, PAGE_SIZE};
+
+// Rewrites header names with a text operation (snakecase by default), keeping the rows as they are
+pub fn write_csv_clean_headers(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Csv(Default::default()))?;
+    let operation = match args.value("op")? {
+        Some(operation) => find_nested(&operation)?,
+        None => find_nested("snakecase")?,
+    };
+    let source = args.finish()?.join(" ");
+
+    if read_options.no_header {
+        return Err(Error::invalid("csv-clean-headers needs a header row"));
+    }
+    if !operation.is_text_operation() {
+        return Err(Error::invalid(format!(
+            "{} can't be applied to CSV headers",
+            operation.name()
+        )));
+    }
+
+    let mut reader = read_options.open(&source)?;
+    let headers = read_options.headers(&mut reader)?.unwrap_or_default();
+    let headers = clean_headers(&headers, operation.as_ref())?;
+
+    let mut table = TableOutput::new(&format, Some(&headers), PAGE_SIZE);
+    for record in reader.records() {
+        table.push(out, record?)?;
+    }
+    table.finish(out)
+}
+
+// Duplicate names get a numeric suffix and names that end up empty a positional one
+fn clean_headers(
+    headers: &StringRecord,
+    operation: &dyn TextOperation,
+) -> Result<StringRecord, Error> {
+    let mut seen = HashSet::new();
+    let mut cleaned = Vec::with_capacity(headers.len());
+
+    for (i, header) in headers.iter().enumerate() {
+        let mut name = operation.apply(header.trim())?;
+        if name.is_empty() {
+            name = format!("column_{}", i + 1);
+        }
+
+        let mut unique = name.clone();
+        let mut suffix = 2;
+        while !seen.insert(unique.clone()) {
+            unique = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        cleaned.push(unique);
+    }
+
+    Ok(StringRecord::from(cleaned))
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::input::readable;
+use crate::Error;
+
+use super::columns::column_index;
+use super::format::{OutputFormat, TableOutput};
+use super::{ReadOptions, PAGE_SIZE};
+
+#[derive(Clone, Copy, PartialEq)]
+enum JoinKind {
+    Inner,
+    // Unmatched left rows are kept with empty right columns
+    Left,
+}
+
+// One input of the join with the positions of its key columns
+struct Side {
+    headers: Option<StringRecord>,
+    keys: Vec<usize>,
+    // Widest record of the file, used to pad missing fields
+    width: usize,
+}
+
+impl Side {
+    fn key(&self, record: &StringRecord) -> Vec<String> {
+        self.keys
+            .iter()
+            .map(|&index| record.get(index).unwrap_or("").to_string())
+            .collect()
+    }
+
+    fn see(&mut self, record: &StringRecord) {
+        self.width = self.width.max(record.len());
+    }
+}
+
+// Joins two CSVs on key columns; the smaller file is loaded into a hash map
+// and the larger one is read past it
+pub fn write_csv_join(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Csv(Default::default()))?;
+    let on = args.value("on")?;
+    let kind = match args.value("how")?.as_deref() {
+        None | Some("inner") => JoinKind::Inner,
+        Some("left") => JoinKind::Left,
+        Some(other) => {
+            return Err(Error::invalid(format!(
+                "Invalid join: {}, available joins are: inner, left",
+                other
+            )))
+        }
+    };
+    let sources = args.finish()?;
+
+    let Some(on) = on else {
+        return Err(Error::invalid("csv-join requires --on <columns>"));
+    };
+    let [left_source, right_source] = sources.as_slice() else {
+        return Err(Error::invalid(
+            "csv-join expects two inputs: <left> <right>",
+        ));
+    };
+
+    let mut left_reader = read_options.open(left_source)?;
+    let mut right_reader = read_options.open(right_source)?;
+    let mut left = side(&read_options.headers(&mut left_reader)?, &on)?;
+    let mut right = side(&read_options.headers(&mut right_reader)?, &on)?;
+
+    // Rows are written in the order of the left file, each with its matches in
+    // the order of the right file. Both are read first, so every row gets the
+    // widths of the widest records of each side
+    let mut rows: Vec<(StringRecord, Vec<StringRecord>)> = Vec::new();
+    if source_size(left_source) <= source_size(right_source) {
+        // The left file is held with the right rows matching each of its rows
+        let mut index: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+        for record in left_reader.records() {
+            let record = record?;
+            left.see(&record);
+            index.entry(left.key(&record)).or_default().push(rows.len());
+            rows.push((record, Vec::new()));
+        }
+        for record in right_reader.records() {
+            let record = record?;
+            right.see(&record);
+            for &row in index.get(&right.key(&record)).into_iter().flatten() {
+                rows[row].1.push(record.clone());
+            }
+        }
+    } else {
+        // The right file is held and the left one is read past it
+        let mut index: HashMap<Vec<String>, Vec<StringRecord>> = HashMap::new();
+        for record in right_reader.records() {
+            let record = record?;
+            right.see(&record);
+            index.entry(right.key(&record)).or_default().push(record);
+        }
+        for record in left_reader.records() {
+            let record = record?;
+            left.see(&record);
+            let matches = index.get(&left.key(&record)).cloned().unwrap_or_default();
+            if !matches.is_empty() || kind == JoinKind::Left {
+                rows.push((record, matches));
+            }
+        }
+    }
+
+    let headers = match (&left.headers, &right.headers) {
+        (Some(left_headers), Some(right_headers)) => Some(combine(
+            &left,
+            Some(left_headers),
+            &right,
+            Some(right_headers),
+        )),
+        _ => None,
+    };
+    let mut table = TableOutput::new(&format, headers.as_ref(), PAGE_SIZE);
+    for (record, matches) in &rows {
+        if matches.is_empty() && kind == JoinKind::Left {
+            table.push(out, combine(&left, Some(record), &right, None))?;
+        }
+        for matched in matches {
+            table.push(out, combine(&left, Some(record), &right, Some(matched)))?;
+        }
+    }
+
+    table.finish(out)
+}
+
+fn side(headers: &Option<StringRecord>, on: &str) -> Result<Side, Error> {
+    let keys = on
+        .split(',')
+        .map(|column| column_index(column.trim(), headers.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Side {
+        headers: headers.clone(),
+        keys,
+        width: headers.as_ref().map_or(0, StringRecord::len),
+    })
+}
+
+// All left fields followed by the right fields that aren't part of the key
+fn combine(
+    left: &Side,
+    left_record: Option<&StringRecord>,
+    right: &Side,
+    right_record: Option<&StringRecord>,
+) -> StringRecord {
+    let mut fields = Vec::with_capacity(left.width + right.width);
+    for i in 0..left.width {
+        fields.push(left_record.and_then(|record| record.get(i)).unwrap_or(""));
+    }
+    for i in (0..right.width).filter(|i| !right.keys.contains(i)) {
+        fields.push(right_record.and_then(|record| record.get(i)).unwrap_or(""));
+    }
+    StringRecord::from(fields)
+}
+
+// Streams like stdin count as infinitely large so they are never loaded
+fn source_size(source: &str) -> u64 {
+    readable(Path::new(source))
+        .ok()
+        .and_then(|path| fs::metadata(path).ok())
+        .map_or(u64::MAX, |metadata| metadata.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Joins the files both ways of holding one of them, which have to agree
+    fn join(options: &str, left: &str, right: &str) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let padded = |text: &str| format!("{}{}", text, "\n".repeat(100));
+        let mut outputs = Vec::new();
+        for (left, right) in [
+            (left.to_string(), padded(right)),
+            (padded(left), right.to_string()),
+        ] {
+            let left_path = dir.path().join("left.csv");
+            let right_path = dir.path().join("right.csv");
+            fs::write(&left_path, left).unwrap();
+            fs::write(&right_path, right).unwrap();
+            let input = format!(
+                "{} {} {}",
+                options,
+                left_path.display(),
+                right_path.display()
+            );
+            let mut out = Vec::new();
+            write_csv_join(&input, &mut out).unwrap();
+            outputs.push(String::from_utf8(out).unwrap());
+        }
+        assert_eq!(outputs[0], outputs[1]);
+        outputs.remove(0)
+    }
+
+    #[test]
+    fn rows_follow_the_left_file() {
+        let left = "id,name\n3,c\n1,a\n4,d\n2,b\n";
+        let right = "id,score\n1,10\n2,20\n3,30\n3,31\n";
+        assert_eq!(
+            join("--on id", left, right),
+            "id,name,score\n3,c,30\n3,c,31\n1,a,10\n2,b,20\n"
+        );
+        assert_eq!(
+            join("--on id --how left", left, right),
+            "id,name,score\n3,c,30\n3,c,31\n1,a,10\n4,d,\n2,b,20\n"
+        );
+    }
+
+    #[test]
+    fn uneven_records_get_the_widths_of_the_widest() {
+        let left = "1,a\n2,b,extra\n";
+        let right = "1,x\n2,y,z,more\n9,w,v,u,t\n";
+        assert_eq!(
+            join("--on 1 --no-header --how left", left, right),
+            "col1,col2,col3,col4,col5,col6,col7\n1,a,,x,,,\n2,b,extra,y,z,more,\n"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use serde_json::{Deserializer, Map, Number, Value};
+
+use crate::args::OperationArgs;
+use crate::redaction::redacted;
+use crate::Error;
+
+use super::format::{OutputFormat, TableOutput};
+use super::{open_source, ReadOptions, PAGE_SIZE};
+
+// Writes the CSV as a JSON array of objects, or one object per line with `--lines`
+pub fn write_csv_to_json(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let lines = args.flag("lines");
+    let infer_types = args.flag("infer-types");
+    let source = args.finish()?.join(" ");
+
+    let mut reader = read_options.open(&source)?;
+    let headers = read_options.headers(&mut reader)?;
+
+    let mut output = JsonOutput::new(lines);
+    for record in reader.records() {
+        output.push(out, &to_object(headers.as_ref(), &record?, infer_types))?;
+    }
+
+    if output.count == 0 {
+        return Err(Error::invalid("CSV has no data rows"));
+    }
+    output.finish(out)
+}
+
+// Streams objects as a pretty printed array, or JSON Lines with `lines`
+pub struct JsonOutput {
+    lines: bool,
+    count: usize,
+}
+
+impl JsonOutput {
+    pub fn new(lines: bool) -> JsonOutput {
+        JsonOutput { lines, count: 0 }
+    }
+
+    pub fn push(&mut self, out: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        if self.lines {
+            writeln!(out, "{}", serde_json::to_string(value)?)?;
+        } else {
+            write!(out, "{}", if self.count == 0 { "[\n" } else { ",\n" })?;
+            let pretty = serde_json::to_string_pretty(value)?;
+            for (i, line) in pretty.lines().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                write!(out, "  {}", line)?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn finish(self, out: &mut dyn Write) -> Result<(), Error> {
+        match (self.lines, self.count) {
+            (false, 0) => writeln!(out, "[]")?,
+            (false, _) => writeln!(out, "\n]")?,
+            (true, _) => {}
+        }
+        Ok(())
+    }
+}
+
+fn to_object(headers: Option<&StringRecord>, record: &StringRecord, infer_types: bool) -> Value {
+    let mut object = Map::new();
+    for (i, field) in record.iter().enumerate() {
+        let key = match headers.and_then(|headers| headers.get(i)) {
+            Some(header) => header.to_string(),
+            None => format!("col{}", i + 1),
+        };
+        let value = if infer_types {
+            infer_value(field)
+        } else {
+            Value::String(field.to_string())
+        };
+        object.insert(key, value);
+    }
+    Value::Object(object)
+}
+
+// Numbers and booleans become native JSON values, everything else stays a string
+pub fn infer_value(field: &str) -> Value {
+    match field {
+        "true" | "TRUE" | "True" => return Value::Bool(true),
+        "false" | "FALSE" | "False" => return Value::Bool(false),
+        _ => {}
+    }
+
+    // Keep identifiers like zip codes "01234" intact
+    let digits = field.trim_start_matches('-');
+    if digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.") {
+        return Value::String(field.to_string());
+    }
+
+    if let Ok(integer) = field.parse::<i64>() {
+        return Value::Number(integer.into());
+    }
+    if let Some(number) = field.parse::<f64>().ok().and_then(Number::from_f64) {
+        if field
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            return Value::Number(number);
+        }
+    }
+
+    Value::String(field.to_string())
+}
+
+// Flattens JSON objects into CSV rows, nested fields become dotted columns like `address.city`
+pub fn write_json_to_csv(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let inline = args.flag("inline");
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Csv(Default::default()))?;
+    let source = args.finish()?.join(" ");
+
+    let mut headers: Vec<String> = Vec::new();
+    let mut rows: Vec<Map<String, Value>> = Vec::new();
+
+    // Accepts a single array of objects as well as a stream of objects (JSON Lines)
+    for value in Deserializer::from_reader(open_source(&source, inline)?).into_iter::<Value>() {
+        let objects = match value? {
+            Value::Array(items) => items,
+            object => vec![object],
+        };
+
+        for object in objects {
+            let mut row = Map::new();
+            match object {
+                Value::Object(_) => flatten("", object, &mut row),
+                other => {
+                    return Err(Error::invalid(format!(
+                        "Expected JSON objects, found: {}",
+                        redacted(&other.to_string())
+                    )))
+                }
+            }
+
+            for key in row.keys() {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+            rows.push(row);
+        }
+    }
+
+    if rows.is_empty() {
+        return Err(Error::invalid("JSON has no objects"));
+    }
+
+    let headers = StringRecord::from(headers);
+    let mut table = TableOutput::new(&format, Some(&headers), PAGE_SIZE);
+    for row in &rows {
+        let record = headers
+            .iter()
+            .map(|key| match row.get(key) {
+                Some(Value::String(text)) => text.clone(),
+                Some(Value::Null) | None => String::new(),
+                Some(value) => value.to_string(),
+            })
+            .collect();
+        table.push(out, record)?;
+    }
+    table.finish(out)
+}
+
+fn flatten(prefix: &str, value: Value, row: &mut Map<String, Value>) {
+    let key = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", prefix, name)
+        }
+    };
+
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (name, value) in object {
+                flatten(&key(&name), value, row);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, value) in items.into_iter().enumerate() {
+                flatten(&key(&i.to_string()), value, row);
+            }
+        }
+        value => {
+            row.insert(prefix.to_string(), value);
+        }
+    }
+}
@@ -0,0 +1,432 @@
+mod apply;
+mod columns;
+mod convert;
+mod dedupe;
+mod delimiter;
+mod diff;
+mod expr;
+mod fixed;
+mod format;
+mod groupby;
+mod headers;
+mod join;
+mod json;
+#[cfg(feature = "parquet")]
+mod parquet;
+mod pointer;
+mod render;
+mod reshape;
+mod sql;
+mod stats;
+mod transpose;
+mod types;
+mod validate;
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+
+use csv::{ByteRecord, Reader, ReaderBuilder, StringRecord, WriterBuilder};
+
+use crate::args::OperationArgs;
+use crate::input::{open_mapped, open_source};
+use crate::streaming::StreamingOperation;
+use crate::Error;
+
+use apply::write_csv_apply;
+use columns::ColumnSelection;
+use convert::{write_json_pretty, write_json_to_yaml, write_toml_to_json, write_yaml_to_json};
+use dedupe::write_csv_dedupe;
+use delimiter::{MultiDelimiterReader, UNIT_SEPARATOR};
+use diff::write_csv_diff;
+use fixed::{FixedWidthReader, FixedWidths};
+use format::{OutputFormat, TableOutput};
+use groupby::write_csv_groupby;
+use headers::write_csv_clean_headers;
+use join::write_csv_join;
+use json::{write_csv_to_json, write_json_to_csv};
+#[cfg(feature = "parquet")]
+use parquet::write_parquet;
+use pointer::write_jget;
+use reshape::ReshapeOptions;
+use sql::write_csv_to_sql;
+use stats::write_csv_stats;
+use transpose::write_csv_transpose;
+use validate::write_csv_validate;
+
+// Default number of records rendered per table, so memory use doesn't grow with the file
+const PAGE_SIZE: usize = 1000;
+
+// How the CSV input is located and parsed, shared by all CSV operations
+struct ReadOptions {
+    no_header: bool,
+    inline: bool,
+    // Files are read through a memory map instead of buffered reads
+    mmap: bool,
+    delimiter: u8,
+    // Set for delimiters like `||`, which are rewritten to `delimiter` while reading
+    multi_delimiter: Option<Vec<u8>>,
+    // Fixed-width input is converted to CSV before parsing
+    fixed_widths: Option<FixedWidths>,
+    quote: u8,
+    // Escape character used inside quoted fields instead of doubling the quote
+    escape: Option<u8>,
+    quoting: bool,
+    // Lines starting with this character are skipped
+    comment: Option<u8>,
+}
+
+impl ReadOptions {
+    fn from_args(args: &mut OperationArgs) -> Result<ReadOptions, Error> {
+        let (delimiter, multi_delimiter) = match args.value("delimiter")? {
+            None => (b',', None),
+            Some(value) if value.chars().count() > 1 && !is_tab(&value) => {
+                if value.contains(['\n', '\r', '"']) {
+                    return Err(Error::invalid(format!(
+                        "Invalid --delimiter '{}', it can't contain quotes or line breaks",
+                        value
+                    )));
+                }
+                (UNIT_SEPARATOR, Some(value.into_bytes()))
+            }
+            Some(value) => (parse_byte("delimiter", &value)?, None),
+        };
+
+        let fixed_widths = match (args.value("widths")?, args.flag("fixed-width")) {
+            (Some(_), true) => {
+                return Err(Error::invalid(
+                    "--widths and --fixed-width can't be combined",
+                ))
+            }
+            (Some(spec), false) => Some(FixedWidths::parse(&spec)?),
+            (None, true) => Some(FixedWidths::Ruler),
+            (None, false) => None,
+        };
+        if fixed_widths.is_some() && multi_delimiter.is_some() {
+            return Err(Error::invalid(
+                "Fixed-width input has no delimiter, drop --delimiter",
+            ));
+        }
+
+        Ok(ReadOptions {
+            no_header: args.flag("no-header"),
+            inline: args.flag("inline"),
+            mmap: args.flag("mmap"),
+            delimiter,
+            multi_delimiter,
+            fixed_widths,
+            quote: byte_option(args, "quote")?.unwrap_or(b'"'),
+            escape: byte_option(args, "escape")?,
+            quoting: !args.flag("no-quoting"),
+            comment: byte_option(args, "comment")?,
+        })
+    }
+
+    // The raw data of a source, with multi character delimiters already replaced
+    fn source(&self, source: &str) -> Result<Box<dyn Read>, Error> {
+        let data = if self.mmap && !self.inline {
+            open_mapped(source)?
+        } else {
+            open_source(source, self.inline)?
+        };
+        if let Some(widths) = &self.fixed_widths {
+            let mut builder = WriterBuilder::new();
+            builder.delimiter(self.delimiter).quote(self.quote);
+            return Ok(Box::new(FixedWidthReader::new(data, widths, builder)?));
+        }
+
+        match &self.multi_delimiter {
+            Some(delimiter) => Ok(Box::new(MultiDelimiterReader::new(
+                data,
+                delimiter.clone(),
+                self.quoting.then_some(self.quote),
+            ))),
+            None => Ok(data),
+        }
+    }
+
+    fn open(&self, source: &str) -> Result<Reader<Box<dyn Read>>, Error> {
+        let data = self.source(source)?;
+
+        Ok(ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .has_headers(!self.no_header)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.escape.is_none())
+            .quoting(self.quoting)
+            .comment(self.comment)
+            .from_reader(data))
+    }
+
+    // Header row of the reader, or `None` when columns get generated names
+    fn headers(&self, reader: &mut Reader<Box<dyn Read>>) -> Result<Option<StringRecord>, Error> {
+        if self.no_header {
+            return Ok(None);
+        }
+
+        let headers = reader.headers()?.clone();
+        if headers.is_empty() {
+            return Err(Error::invalid("CSV has no headers"));
+        }
+        Ok(Some(headers))
+    }
+}
+
+// Renders the CSV page by page into `out` as records are read
+pub fn write_csv(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let head: Option<usize> = args.parsed("head")?;
+    let tail: Option<usize> = args.parsed("tail")?;
+    let page_size: usize = args.parsed("page")?.unwrap_or(PAGE_SIZE);
+    let columns = args.value("columns")?;
+    let reshape_options = ReshapeOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Table(Default::default()))?;
+    let source = args.finish()?.join(" ");
+
+    if head.is_some() && tail.is_some() {
+        return Err(Error::invalid("--head and --tail can't be combined"));
+    }
+    if page_size == 0 {
+        return Err(Error::invalid("--page must be at least 1"));
+    }
+
+    let mut reader = read_options.open(&source)?;
+    let mut headers = read_options.headers(&mut reader)?;
+    let reshape = match (&headers, reshape_options.is_empty()) {
+        (_, true) => None,
+        (Some(input_headers), false) => {
+            let (reshaped_headers, reshape) = reshape_options.resolve(input_headers)?;
+            headers = Some(reshaped_headers);
+            Some(reshape)
+        }
+        (None, false) => {
+            return Err(Error::invalid(
+                "--rename and --add-column need a header row",
+            ))
+        }
+    };
+    let selection = match columns {
+        Some(spec) => Some(ColumnSelection::resolve(&spec, headers.as_ref())?),
+        None => None,
+    };
+    if let Some(selection) = &selection {
+        headers = Some(selection.headers(headers.as_ref()));
+    }
+    let select = |record: StringRecord| -> Result<StringRecord, Error> {
+        let record = match &reshape {
+            Some(reshape) => reshape.apply(record)?,
+            None => record,
+        };
+        Ok(match &selection {
+            Some(selection) => selection.apply(&record),
+            None => record,
+        })
+    };
+
+    let mut table = TableOutput::new(&format, headers.as_ref(), page_size);
+
+    let mut total = 0;
+    let mut shown = 0;
+    if let Some(tail) = tail {
+        let mut last = VecDeque::with_capacity(tail);
+        for record in reader.records() {
+            let record = record?;
+            total += 1;
+
+            if tail == 0 {
+                continue;
+            }
+            if last.len() == tail {
+                last.pop_front();
+            }
+            last.push_back(select(record)?);
+        }
+
+        shown = last.len();
+        for record in last {
+            table.push(out, record)?;
+        }
+    } else {
+        let limit = head.unwrap_or(usize::MAX);
+        let mut records = reader.records();
+        while shown < limit {
+            match records.next() {
+                Some(record) => {
+                    table.push(out, select(record?)?)?;
+                    shown += 1;
+                }
+                None => break,
+            }
+        }
+        total = shown + count_remaining(&mut reader)?;
+    }
+
+    if total == 0 {
+        return Err(Error::invalid("CSV has no data rows"));
+    }
+
+    table.finish(out)?;
+
+    // Machine readable formats stay parseable without the footer
+    let omitted = total - shown;
+    if omitted > 0 && matches!(format, OutputFormat::Table(_)) {
+        let position = if tail.is_some() { "earlier" } else { "more" };
+        writeln!(out, "({} {} rows not shown)", omitted, position)?;
+    }
+
+    Ok(())
+}
+
+pub fn builtins() -> Vec<StreamingOperation> {
+    let operation = StreamingOperation::new;
+
+    vec![
+        operation("csv", &[], "Renders a CSV file as a table", write_csv),
+        operation(
+            "csv2json",
+            &["csv-to-json"],
+            "Converts CSV into a JSON array of objects",
+            write_csv_to_json,
+        ),
+        operation(
+            "csv2sql",
+            &["csv-to-sql"],
+            "Converts CSV into CREATE TABLE and INSERT statements",
+            write_csv_to_sql,
+        ),
+        operation(
+            "csv-apply",
+            &[],
+            "Runs a text operation over the values of a column",
+            write_csv_apply,
+        ),
+        operation(
+            "csv-clean-headers",
+            &[],
+            "Normalizes header names for database import",
+            write_csv_clean_headers,
+        ),
+        operation(
+            "csv-dedupe",
+            &[],
+            "Removes duplicate rows",
+            write_csv_dedupe,
+        ),
+        operation(
+            "csv-diff",
+            &[],
+            "Lists added, removed and changed rows between two CSV files",
+            write_csv_diff,
+        ),
+        operation(
+            "csv-groupby",
+            &[],
+            "Groups rows and aggregates each group",
+            write_csv_groupby,
+        ),
+        operation(
+            "csv-join",
+            &[],
+            "Joins two CSV files on key columns",
+            write_csv_join,
+        ),
+        operation(
+            "csv-stats",
+            &[],
+            "Per column counts, min/max, mean and sum",
+            write_csv_stats,
+        ),
+        operation(
+            "csv-transpose",
+            &[],
+            "Swaps rows and columns",
+            write_csv_transpose,
+        ),
+        operation(
+            "csv-validate",
+            &[],
+            "Reports malformed rows with their line numbers",
+            write_csv_validate,
+        ),
+        operation(
+            "jget",
+            &[],
+            "Prints the value at a JSON Pointer or path",
+            write_jget,
+        ),
+        operation(
+            "json2csv",
+            &["json-to-csv"],
+            "Flattens JSON objects into CSV",
+            write_json_to_csv,
+        ),
+        operation(
+            "json2yaml",
+            &["json-to-yaml"],
+            "Converts JSON into YAML",
+            write_json_to_yaml,
+        ),
+        operation(
+            "json-pretty",
+            &[],
+            "Reformats JSON, indented or compact",
+            write_json_pretty,
+        ),
+        operation(
+            "toml2json",
+            &["toml-to-json"],
+            "Converts TOML into JSON",
+            write_toml_to_json,
+        ),
+        operation(
+            "yaml2json",
+            &["yaml-to-json"],
+            "Converts YAML into JSON",
+            write_yaml_to_json,
+        ),
+        #[cfg(feature = "parquet")]
+        operation(
+            "parquet",
+            &[],
+            "Renders a Parquet file as a table or converts it",
+            write_parquet,
+        ),
+    ]
+}
+
+// Single byte option such as `--delimiter ';'`, `tab` is accepted for a tab
+fn byte_option(args: &mut OperationArgs, name: &str) -> Result<Option<u8>, Error> {
+    let Some(value) = args.value(name)? else {
+        return Ok(None);
+    };
+
+    parse_byte(name, &value).map(Some)
+}
+
+fn parse_byte(name: &str, value: &str) -> Result<u8, Error> {
+    match value {
+        _ if is_tab(value) => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(Error::invalid(format!(
+            "--{} needs a single ASCII character, found '{}'",
+            name, value
+        ))),
+    }
+}
+
+fn is_tab(value: &str) -> bool {
+    matches!(value, "tab" | "\\t" | "\t")
+}
+
+// Counts the records left in the reader without decoding them
+fn count_remaining(reader: &mut Reader<Box<dyn Read>>) -> Result<usize, Error> {
+    let mut record = ByteRecord::new();
+    let mut count = 0;
+    while reader.read_byte_record(&mut record)? {
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,68 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use csv::StringRecord;
+
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+
+use crate::args::OperationArgs;
+use crate::input::readable;
+use crate::Error;
+
+use super::format::{OutputFormat, TableOutput};
+use super::json::JsonOutput;
+use super::PAGE_SIZE;
+
+// Reads a Parquet file and renders it like a CSV, or as JSON with `--json`
+pub fn write_parquet(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Table(Default::default()))?;
+    let json = args.flag("json");
+    let lines = args.flag("lines");
+    let head = args.parsed::<usize>("head")?;
+    let source = args.finish()?.join(" ");
+
+    // Parquet keeps its metadata at the end of the file, so stdin can't be streamed
+    let file = File::open(readable(Path::new(&source))?).map_err(|e| Error::io(source, e))?;
+    let reader = SerializedFileReader::new(file)?;
+    let rows = reader.get_row_iter(None)?.take(head.unwrap_or(usize::MAX));
+
+    if json || lines {
+        let mut output = JsonOutput::new(lines);
+        for row in rows {
+            output.push(out, &row?.to_json_value())?;
+        }
+        return output.finish(out);
+    }
+
+    let headers: StringRecord = reader
+        .metadata()
+        .file_metadata()
+        .schema_descr()
+        .root_schema()
+        .get_fields()
+        .iter()
+        .map(|field| field.name())
+        .collect();
+
+    let mut table = TableOutput::new(&format, Some(&headers), PAGE_SIZE);
+    for row in rows {
+        let record = row?
+            .get_column_iter()
+            .map(|(_, field)| cell(field))
+            .collect();
+        table.push(out, record)?;
+    }
+    table.finish(out)
+}
+
+// Strings are shown without the quotes `Field` displays them with
+fn cell(field: &Field) -> String {
+    match field {
+        Field::Null => String::new(),
+        Field::Str(value) => value.clone(),
+        field => field.to_string(),
+    }
+}
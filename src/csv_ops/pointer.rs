@@ -0,0 +1,144 @@
+use std::io::{Read, Write};
+
+use serde_json::{Deserializer, Value};
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::open_source;
+
+// Prints the value at a JSON Pointer like `/items/0/name`, or a path like
+// `items[0].name`, of every JSON value in the input. Strings are printed as
+// they are unless `--json` asks for JSON
+pub fn write_jget(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let json = args.flag("json");
+    let inline = args.flag("inline");
+    let positional = args.finish()?;
+    let Some((path, source)) = positional
+        .split_first()
+        .filter(|(_, source)| !source.is_empty())
+    else {
+        return Err(Error::invalid(
+            "jget requires a path like /items/0/name and a JSON file, - for stdin or --inline TEXT",
+        ));
+    };
+    let source = source.join(" ");
+    let tokens = parse_path(path)?;
+
+    let mut text = String::new();
+    open_source(&source, inline)?.read_to_string(&mut text)?;
+    let mut count = 0;
+    for value in Deserializer::from_str(&text).into_iter::<Value>() {
+        let value = value?;
+        match lookup(&value, &tokens)? {
+            Value::String(text) if !json => writeln!(out, "{}", text)?,
+            found => writeln!(out, "{}", serde_json::to_string_pretty(found)?)?,
+        }
+        count += 1;
+    }
+
+    if count == 0 {
+        return Err(Error::invalid("JSON has no values"));
+    }
+    Ok(())
+}
+
+// The keys and indexes of a path. `/` starts a JSON Pointer (RFC 6901) with
+// `~1` for `/` and `~0` for `~` in keys, anything else is split at `.` and `[n]`
+fn parse_path(path: &str) -> Result<Vec<String>, Error> {
+    // The whole value
+    if path.is_empty() || path == "." {
+        return Ok(Vec::new());
+    }
+    if let Some(pointer) = path.strip_prefix('/') {
+        return Ok(pointer
+            .split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect());
+    }
+
+    let mut tokens = Vec::new();
+    for part in path.strip_prefix('.').unwrap_or(path).split('.') {
+        let (key, mut indexes) = match part.find('[') {
+            Some(start) => (&part[..start], &part[start..]),
+            None => (part, ""),
+        };
+        if key.is_empty() && indexes.is_empty() {
+            return Err(Error::invalid(format!(
+                "Invalid path '{}', it has an empty key",
+                path
+            )));
+        }
+        if !key.is_empty() {
+            tokens.push(key.to_string());
+        }
+        while !indexes.is_empty() {
+            let index = indexes
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .filter(|(index, _)| {
+                    !index.is_empty() && index.chars().all(|c| c.is_ascii_digit())
+                });
+            let Some((index, rest)) = index else {
+                return Err(Error::invalid(format!(
+                    "Invalid path '{}', indexes are written like [0]",
+                    path
+                )));
+            };
+            tokens.push(index.to_string());
+            indexes = rest;
+        }
+    }
+    Ok(tokens)
+}
+
+// Follows the tokens into the value, errors name the pointer up to the token
+// that failed and the type found there
+fn lookup<'a>(value: &'a Value, tokens: &[String]) -> Result<&'a Value, Error> {
+    let mut current = value;
+    let mut pointer = String::new();
+    for token in tokens {
+        let at = if pointer.is_empty() { "/" } else { &pointer };
+        current = match current {
+            Value::Object(object) => object.get(token).ok_or_else(|| {
+                Error::invalid(format!("{}: the object has no key '{}'", at, token))
+            })?,
+            Value::Array(items) => {
+                let index: usize = token.parse().map_err(|_| {
+                    Error::invalid(format!("{}: '{}' is no index of an array", at, token))
+                })?;
+                items.get(index).ok_or_else(|| {
+                    Error::invalid(format!(
+                        "{}: index {} is out of range, the array has {} items",
+                        at,
+                        index,
+                        items.len()
+                    ))
+                })?
+            }
+            scalar => {
+                return Err(Error::invalid(format!(
+                    "{}: {} has no key '{}'",
+                    at,
+                    type_name(scalar),
+                    token
+                )))
+            }
+        };
+        pointer.push('/');
+        pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+    }
+    Ok(current)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
@@ -0,0 +1,476 @@
+use std::fmt;
+use std::io::Write;
+
+use csv::StringRecord;
+
+use terminal_size::{terminal_size, Width};
+
+use prettytable::format::{consts, FormatBuilder, LinePosition, LineSeparator, TableFormat};
+use prettytable::{Cell, Row, Table};
+
+use crate::args::OperationArgs;
+use crate::{memory, Error};
+
+use super::format::TableFormatter;
+use super::types::ColumnType;
+
+const NULL_MARK: char = '\u{e000}';
+
+// Narrowest a column is squeezed to when fitting the table to the terminal
+const MIN_COLUMN_WIDTH: usize = 5;
+
+#[derive(Clone, Copy)]
+pub enum Overflow {
+    // Cut long cells and end them with `…`
+    Truncate,
+    // Break long cells over several lines
+    Wrap,
+}
+
+// Border and separator style selected with `--table-style`
+#[derive(Clone, Copy)]
+pub enum TableStyle {
+    Ascii,
+    Unicode,
+    // Borders without lines between the rows
+    Compact,
+    Borderless,
+    // A GitHub flavored Markdown table
+    Markdown,
+}
+
+const TABLE_STYLE_NAMES: &str = "ascii, unicode, compact, borderless, markdown";
+
+impl TableStyle {
+    fn from_str(s: &str) -> Result<TableStyle, Error> {
+        match s.to_lowercase().as_str() {
+            "ascii" => Ok(TableStyle::Ascii),
+            "unicode" => Ok(TableStyle::Unicode),
+            "compact" => Ok(TableStyle::Compact),
+            "borderless" => Ok(TableStyle::Borderless),
+            "markdown" | "md" => Ok(TableStyle::Markdown),
+            _ => Err(Error::invalid(format!(
+                "Invalid table style: {}, available styles are: {}",
+                s, TABLE_STYLE_NAMES
+            ))),
+        }
+    }
+
+    fn format(self) -> TableFormat {
+        match self {
+            TableStyle::Ascii => *consts::FORMAT_NO_TITLE,
+            TableStyle::Unicode => *consts::FORMAT_BOX_CHARS,
+            TableStyle::Compact => *consts::FORMAT_NO_LINESEP_WITH_TITLE,
+            TableStyle::Borderless => *consts::FORMAT_CLEAN,
+            TableStyle::Markdown => FormatBuilder::new()
+                .column_separator('|')
+                .borders('|')
+                .separator(LinePosition::Title, LineSeparator::new('-', '|', '|', '|'))
+                .padding(1, 1)
+                .build(),
+        }
+    }
+}
+
+// How the table is drawn, set with `--table-style`, `--plain-header`, `--max-col-width`,
+// `--width`, `--wrap`, `--truncate`, `--thousands`, `--decimals`, `--null` and `--null-color`
+#[derive(Clone)]
+pub struct TableLayout {
+    style: TableStyle,
+    // Header cells are rendered like any other cell instead of bold and centered
+    plain_header: bool,
+    max_column_width: Option<usize>,
+    // Total table width, detected from the terminal when not given
+    width: Option<usize>,
+    overflow: Overflow,
+    // Numeric columns are right aligned, these format them further
+    thousands: bool,
+    decimals: Option<usize>,
+    // Shown in place of empty cells, optionally wrapped in an ANSI color
+    null: Option<String>,
+    null_color: Option<&'static str>,
+}
+
+impl Default for TableLayout {
+    fn default() -> TableLayout {
+        TableLayout {
+            style: TableStyle::Ascii,
+            plain_header: false,
+            max_column_width: None,
+            width: None,
+            overflow: Overflow::Truncate,
+            thousands: false,
+            decimals: None,
+            null: None,
+            null_color: None,
+        }
+    }
+}
+
+impl TableLayout {
+    // `None` when none of the options were given
+    pub fn from_args(args: &mut OperationArgs) -> Result<Option<TableLayout>, Error> {
+        let mut layout = TableLayout::default();
+        let mut given = false;
+
+        if let Some(style) = args.value("table-style")? {
+            layout.style = TableStyle::from_str(&style)?;
+            given = true;
+        }
+        if args.flag("plain-header") {
+            layout.plain_header = true;
+            given = true;
+        }
+        if let Some(width) = args.parsed::<usize>("max-col-width")? {
+            layout.max_column_width = Some(width);
+            given = true;
+        }
+        if let Some(width) = args.parsed::<usize>("width")? {
+            layout.width = Some(width);
+            given = true;
+        }
+        match (args.flag("wrap"), args.flag("truncate")) {
+            (true, true) => return Err(Error::invalid("--wrap and --truncate cannot be combined")),
+            (true, false) => {
+                layout.overflow = Overflow::Wrap;
+                given = true;
+            }
+            (false, true) => {
+                layout.overflow = Overflow::Truncate;
+                given = true;
+            }
+            (false, false) => {}
+        }
+        if args.flag("thousands") {
+            layout.thousands = true;
+            given = true;
+        }
+        if let Some(decimals) = args.parsed::<usize>("decimals")? {
+            layout.decimals = Some(decimals);
+            given = true;
+        }
+
+        if let Some(null) = args.value("null")? {
+            layout.null = Some(null);
+            given = true;
+        }
+        if let Some(color) = args.value("null-color")? {
+            layout.null_color = Some(ansi_color(&color)?);
+            // A colored empty string wouldn't be visible
+            layout.null.get_or_insert_with(|| "∅".to_string());
+            given = true;
+        }
+
+        Ok(given.then_some(layout))
+    }
+
+    // Largest width of any column, so the whole table fits the available width
+    fn column_limit(&self, widths: &[usize]) -> Option<usize> {
+        let available = self
+            .width
+            .or_else(|| terminal_size().map(|(Width(width), _)| width as usize));
+
+        let fitted = available.and_then(|available| {
+            // Every column adds `| ` and ` ` around its content, plus the closing `|`
+            let available = available.saturating_sub(3 * widths.len() + 1);
+            if widths.iter().sum::<usize>() <= available {
+                return None;
+            }
+
+            // Lower the limit until the capped columns fit
+            let mut limit = widths.iter().copied().max().unwrap_or(0);
+            while limit > MIN_COLUMN_WIDTH
+                && widths.iter().map(|&width| width.min(limit)).sum::<usize>() > available
+            {
+                limit -= 1;
+            }
+            Some(limit)
+        });
+
+        match (fitted, self.max_column_width) {
+            (Some(fitted), Some(max)) => Some(fitted.min(max)),
+            (fitted, max) => fitted.or(max),
+        }
+    }
+
+    // Colored placeholders are laid out as same width runs of a private use character,
+    // which `colorize` swaps for the escape sequences prettytable would miscount
+    fn empty(&self) -> String {
+        match (&self.null, self.null_color) {
+            (Some(null), Some(_)) => NULL_MARK.to_string().repeat(text_width(null)),
+            (Some(null), None) => null.clone(),
+            (None, _) => String::new(),
+        }
+    }
+
+    fn colorize(&self, rendered: String) -> String {
+        match (&self.null, self.null_color) {
+            (Some(null), Some(color)) => {
+                rendered.replace(&self.empty(), &format!("\x1b[{}m{}\x1b[0m", color, null))
+            }
+            _ => rendered,
+        }
+    }
+
+    fn number(&self, value: &str, column_type: ColumnType) -> String {
+        let value = match (column_type, self.decimals, value.parse::<f64>()) {
+            (ColumnType::Float, Some(decimals), Ok(number)) => format!("{:.*}", decimals, number),
+            _ => value.to_string(),
+        };
+        if self.thousands {
+            group_thousands(&value)
+        } else {
+            value
+        }
+    }
+
+    fn cell(&self, text: &str, limit: Option<usize>) -> String {
+        let Some(limit) = limit.filter(|&limit| limit > 0) else {
+            return text.to_string();
+        };
+
+        let lines = text.lines().map(|line| match self.overflow {
+            Overflow::Truncate => truncate(line, limit),
+            Overflow::Wrap => wrap(line, limit),
+        });
+        lines.collect::<Vec<_>>().join("\n")
+    }
+}
+
+// Borrowed view of a page of CSV records, rendered as a pretty table
+pub struct CsvTable<'a> {
+    pub headers: &'a StringRecord,
+    pub records: &'a [StringRecord],
+    pub layout: &'a TableLayout,
+}
+
+impl fmt::Display for CsvTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Columns where every value of this page is a number are right aligned
+        let mut types = vec![ColumnType::Empty; self.headers.len()];
+        for record in self.records {
+            for (i, field) in record.iter().enumerate() {
+                match types.get_mut(i) {
+                    Some(column_type) => *column_type = column_type.merge(field),
+                    None => types.push(ColumnType::of(field)),
+                }
+            }
+        }
+        let numeric =
+            |i: usize| matches!(types.get(i), Some(ColumnType::Integer | ColumnType::Float));
+
+        let rows: Vec<Vec<String>> = self
+            .records
+            .iter()
+            .map(|record| {
+                record
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| {
+                        if numeric(i) && !field.is_empty() {
+                            self.layout.number(field, types[i])
+                        } else {
+                            field.to_string()
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let null_width = self.layout.null.as_deref().map_or(0, text_width);
+        let mut widths: Vec<usize> = self.headers.iter().map(text_width).collect();
+        for row in &rows {
+            for (i, field) in row.iter().enumerate() {
+                let field_width = text_width(field).max(null_width);
+                match widths.get_mut(i) {
+                    Some(width) => *width = (*width).max(field_width),
+                    None => widths.push(field_width),
+                }
+            }
+        }
+        let limit = self.layout.column_limit(&widths);
+
+        let mut table = Table::new();
+        table.set_format(self.layout.style.format());
+
+        let mut header_cells = Vec::new();
+        for header in self.headers {
+            let cell = Cell::new(&self.layout.cell(header, limit));
+            if self.layout.plain_header {
+                header_cells.push(cell);
+            } else {
+                header_cells.push(cell.style_spec("bcB"));
+            }
+        }
+        table.set_titles(Row::new(header_cells));
+
+        for row in &rows {
+            let mut row_cells = Vec::new();
+            for (i, field) in row.iter().enumerate() {
+                let cell = if field.is_empty() {
+                    Cell::new(&self.layout.empty())
+                } else {
+                    Cell::new(&self.layout.cell(field, limit))
+                };
+                if numeric(i) {
+                    row_cells.push(cell.style_spec("r"));
+                } else {
+                    row_cells.push(cell);
+                }
+            }
+            table.add_row(Row::new(row_cells));
+        }
+
+        write!(f, "{}", self.layout.colorize(table.to_string()))
+    }
+}
+
+fn ansi_color(name: &str) -> Result<&'static str, Error> {
+    match name.to_lowercase().as_str() {
+        "red" => Ok("31"),
+        "green" => Ok("32"),
+        "yellow" => Ok("33"),
+        "blue" => Ok("34"),
+        "magenta" => Ok("35"),
+        "cyan" => Ok("36"),
+        "dim" | "grey" | "gray" => Ok("2"),
+        _ => Err(Error::invalid(format!(
+            "Invalid color: {}, available colors are: red, green, yellow, blue, magenta, cyan, dim",
+            name
+        ))),
+    }
+}
+
+// Width of the longest line of a cell
+fn text_width(text: &str) -> usize {
+    text.lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+// Inserts `,` between groups of three digits of the integer part, e.g. `-1234.5` -> `-1,234.5`
+fn group_thousands(value: &str) -> String {
+    let (sign, unsigned) = match value.strip_prefix(['-', '+']) {
+        Some(rest) => value.split_at(value.len() - rest.len()),
+        None => ("", value),
+    };
+    let end = unsigned
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(unsigned.len());
+    let (integer, rest) = unsigned.split_at(end);
+
+    let mut grouped = String::from(sign);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(rest);
+    grouped
+}
+
+fn truncate(line: &str, limit: usize) -> String {
+    if line.chars().count() <= limit {
+        return line.to_string();
+    }
+    let mut truncated: String = line.chars().take(limit.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+// Breaks at spaces where possible, words longer than the limit are split
+fn wrap(line: &str, limit: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+
+    for word in line.split(' ') {
+        let mut word: Vec<char> = word.chars().collect();
+        if current_width > 0 && current_width + 1 + word.len() <= limit {
+            current.push(' ');
+            current_width += 1;
+        } else if current_width > 0 {
+            lines.push(std::mem::take(&mut current));
+            current_width = 0;
+        }
+
+        while word.len() > limit {
+            let rest = word.split_off(limit);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        current_width += word.len();
+        current.extend(word);
+    }
+    lines.push(current);
+    lines.join("\n")
+}
+
+// Collects records into pages and writes each full page as its own table. A
+// page is cut short when its text goes over the memory budget
+pub struct PagedTable {
+    layout: TableLayout,
+    headers: StringRecord,
+    page_size: usize,
+    page: Vec<StringRecord>,
+    page_bytes: usize,
+}
+
+impl PagedTable {
+    pub fn new(page_size: usize, layout: TableLayout) -> PagedTable {
+        PagedTable {
+            layout,
+            headers: StringRecord::new(),
+            page_size,
+            page: Vec::with_capacity(page_size.min(1024)),
+            page_bytes: 0,
+        }
+    }
+
+    fn flush(&mut self, out: &mut dyn Write) -> Result<(), Error> {
+        if self.page.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(
+            out,
+            "{}",
+            CsvTable {
+                headers: &self.headers,
+                records: &self.page,
+                layout: &self.layout,
+            }
+        )?;
+        self.page.clear();
+        self.page_bytes = 0;
+        Ok(())
+    }
+}
+
+impl TableFormatter for PagedTable {
+    fn header(&mut self, _out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        self.headers = headers.clone();
+        Ok(())
+    }
+
+    // The page being collected and the ones after it get the wider headers
+    fn widen(&mut self, _out: &mut dyn Write, headers: &StringRecord) -> Result<(), Error> {
+        self.headers = headers.clone();
+        Ok(())
+    }
+
+    fn row(&mut self, out: &mut dyn Write, record: StringRecord) -> Result<(), Error> {
+        self.page_bytes += record.as_slice().len();
+        self.page.push(record);
+        if self.page.len() >= self.page_size || memory::over_budget(self.page_bytes) {
+            self.flush(out)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> Result<(), Error> {
+        self.flush(out)
+    }
+}
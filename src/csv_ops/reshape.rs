@@ -0,0 +1,123 @@
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::expr::Expr;
+
+// `--rename old=new` and `--add-column "name = expression"`, both can be repeated
+pub struct ReshapeOptions {
+    renames: Vec<(String, String)>,
+    added: Vec<(String, String)>,
+}
+
+impl ReshapeOptions {
+    pub fn from_args(args: &mut OperationArgs) -> Result<ReshapeOptions, Error> {
+        let mut renames = Vec::new();
+        while let Some(spec) = args.value("rename")? {
+            for rename in spec.split(',').filter(|rename| !rename.trim().is_empty()) {
+                let Some((old, new)) = rename.split_once('=') else {
+                    return Err(Error::invalid(format!(
+                        "Invalid --rename {}, expected old=new",
+                        rename
+                    )));
+                };
+                renames.push((old.trim().to_string(), new.trim().to_string()));
+            }
+        }
+
+        let mut added = Vec::new();
+        while let Some(spec) = args.value("add-column")? {
+            let Some((name, expression)) = split_assignment(&spec) else {
+                return Err(Error::invalid(format!(
+                    "Invalid --add-column {}, expected \"name = expression\"",
+                    spec
+                )));
+            };
+            added.push((name.to_string(), expression.to_string()));
+        }
+
+        Ok(ReshapeOptions { renames, added })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renames.is_empty() && self.added.is_empty()
+    }
+
+    // Computed columns are appended after the input columns and may refer to
+    // them by their original names; renames apply to both
+    pub fn resolve(self, headers: &StringRecord) -> Result<(StringRecord, Reshape), Error> {
+        let mut columns = Vec::with_capacity(self.added.len());
+        let mut names: Vec<String> = headers.iter().map(String::from).collect();
+        for (name, expression) in &self.added {
+            columns.push(Expr::parse(expression, Some(headers))?);
+            names.push(name.clone());
+        }
+
+        for (old, new) in &self.renames {
+            let Some(position) = names.iter().position(|name| name == old) else {
+                return Err(Error::invalid(format!(
+                    "Unknown column '{}' in --rename, available columns: {}",
+                    old,
+                    names.join(", ")
+                )));
+            };
+            names[position] = new.clone();
+        }
+
+        let reshape = Reshape {
+            width: headers.len(),
+            columns,
+        };
+        Ok((StringRecord::from(names), reshape))
+    }
+}
+
+pub struct Reshape {
+    // Records are padded to the header width so computed values line up
+    width: usize,
+    columns: Vec<Expr>,
+}
+
+impl Reshape {
+    pub fn apply(&self, mut record: StringRecord) -> Result<StringRecord, Error> {
+        if self.columns.is_empty() {
+            return Ok(record);
+        }
+
+        let values = self
+            .columns
+            .iter()
+            .map(|column| column.eval(&record).map(|value| value.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|reason| {
+                let line = record.position().map_or(0, |position| position.line());
+                Error::invalid(format!("--add-column failed on line {}: {}", line, reason))
+            })?;
+        record.truncate(self.width);
+        while record.len() < self.width {
+            record.push_field("");
+        }
+        for value in values {
+            record.push_field(&value);
+        }
+        Ok(record)
+    }
+}
+
+// Splits at the first `=` that isn't part of `==`, `!=`, `<=` or `>=`
+fn split_assignment(spec: &str) -> Option<(&str, &str)> {
+    let bytes = spec.as_bytes();
+    let position = (0..bytes.len()).find(|&i| {
+        bytes[i] == b'='
+            && bytes.get(i + 1) != Some(&b'=')
+            && (i == 0 || !b"=!<>".contains(&bytes[i - 1]))
+    })?;
+
+    let name = spec[..position].trim();
+    let expression = spec[position + 1..].trim();
+    if name.is_empty() || expression.is_empty() {
+        return None;
+    }
+    Some((name, expression))
+}
@@ -0,0 +1,164 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::types::{parse_bool, ColumnType};
+use super::ReadOptions;
+
+// Rows per INSERT statement
+const BATCH_SIZE: usize = 100;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Dialect {
+    Sqlite,
+    Postgres,
+    MySql,
+}
+
+impl Dialect {
+    fn from_str(s: &str) -> Result<Dialect, Error> {
+        match s.to_lowercase().as_str() {
+            "sqlite" => Ok(Dialect::Sqlite),
+            "postgres" | "postgresql" => Ok(Dialect::Postgres),
+            "mysql" | "mariadb" => Ok(Dialect::MySql),
+            _ => Err(Error::invalid(format!(
+                "Invalid dialect: {}, available dialects are: sqlite, postgres, mysql",
+                s
+            ))),
+        }
+    }
+
+    fn quote_identifier(self, name: &str) -> String {
+        match self {
+            Dialect::MySql => format!("`{}`", name.replace('`', "``")),
+            Dialect::Sqlite | Dialect::Postgres => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+
+    fn column_type(self, column_type: ColumnType) -> &'static str {
+        match (self, column_type) {
+            (Dialect::Sqlite, ColumnType::Integer | ColumnType::Boolean) => "INTEGER",
+            (Dialect::Sqlite, ColumnType::Float) => "REAL",
+            (_, ColumnType::Integer) => "BIGINT",
+            (Dialect::Postgres, ColumnType::Float) => "DOUBLE PRECISION",
+            (Dialect::MySql, ColumnType::Float) => "DOUBLE",
+            (_, ColumnType::Boolean) => "BOOLEAN",
+            (_, ColumnType::Empty | ColumnType::Text) => "TEXT",
+        }
+    }
+
+    fn value(self, value: &str, column_type: ColumnType) -> String {
+        if value.is_empty() {
+            return "NULL".to_string();
+        }
+
+        match column_type {
+            ColumnType::Integer | ColumnType::Float => value.to_string(),
+            ColumnType::Boolean => {
+                let value = parse_bool(value).unwrap_or(false);
+                match (self, value) {
+                    (Dialect::Sqlite, true) => "1".to_string(),
+                    (Dialect::Sqlite, false) => "0".to_string(),
+                    (_, true) => "TRUE".to_string(),
+                    (_, false) => "FALSE".to_string(),
+                }
+            }
+            ColumnType::Empty | ColumnType::Text => {
+                let mut escaped = value.replace('\'', "''");
+                // MySQL treats backslashes in string literals as escapes by default
+                if self == Dialect::MySql {
+                    escaped = escaped.replace('\\', "\\\\");
+                }
+                format!("'{}'", escaped)
+            }
+        }
+    }
+}
+
+// CREATE TABLE with inferred column types followed by batched INSERT statements
+pub fn write_csv_to_sql(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let table = args.value("table")?;
+    let dialect = match args.value("dialect")? {
+        Some(dialect) => Dialect::from_str(&dialect)?,
+        None => Dialect::Sqlite,
+    };
+    let batch_size: usize = args.parsed("batch")?.unwrap_or(BATCH_SIZE).max(1);
+    let source = args.finish()?.join(" ");
+
+    let Some(table) = table else {
+        return Err(Error::invalid("csv2sql requires --table <name>"));
+    };
+
+    let mut reader = read_options.open(&source)?;
+    let headers = read_options.headers(&mut reader)?;
+
+    // Types are only known after every value was seen
+    let records = reader.records().collect::<Result<Vec<StringRecord>, _>>()?;
+    if records.is_empty() {
+        return Err(Error::invalid("CSV has no data rows"));
+    }
+
+    let width = records
+        .iter()
+        .map(StringRecord::len)
+        .chain(headers.as_ref().map(StringRecord::len))
+        .max()
+        .unwrap_or(0);
+    let names: Vec<String> = (0..width)
+        .map(
+            |i| match headers.as_ref().and_then(|headers| headers.get(i)) {
+                Some(header) => header.to_string(),
+                None => format!("col{}", i + 1),
+            },
+        )
+        .collect();
+
+    let mut types = vec![ColumnType::Empty; width];
+    for record in &records {
+        for (column_type, value) in types.iter_mut().zip(record.iter()) {
+            *column_type = column_type.merge(value);
+        }
+    }
+
+    let table = dialect.quote_identifier(&table);
+    let columns: Vec<String> = names
+        .iter()
+        .map(|name| dialect.quote_identifier(name))
+        .collect();
+
+    writeln!(out, "CREATE TABLE {} (", table)?;
+    for (i, (column, column_type)) in columns.iter().zip(&types).enumerate() {
+        let separator = if i + 1 < width { "," } else { "" };
+        writeln!(
+            out,
+            "  {} {}{}",
+            column,
+            dialect.column_type(*column_type),
+            separator
+        )?;
+    }
+    writeln!(out, ");")?;
+
+    for batch in records.chunks(batch_size) {
+        writeln!(out)?;
+        writeln!(out, "INSERT INTO {} ({}) VALUES", table, columns.join(", "))?;
+        for (i, record) in batch.iter().enumerate() {
+            let values: Vec<String> = types
+                .iter()
+                .enumerate()
+                .map(|(column, column_type)| {
+                    dialect.value(record.get(column).unwrap_or(""), *column_type)
+                })
+                .collect();
+            let terminator = if i + 1 < batch.len() { "," } else { ";" };
+            writeln!(out, "  ({}){}", values.join(", "), terminator)?;
+        }
+    }
+
+    Ok(())
+}
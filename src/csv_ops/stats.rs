@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::format::{OutputFormat, TableOutput};
+use super::types::format_number;
+use super::{ReadOptions, PAGE_SIZE};
+
+// Running statistics of one column
+#[derive(Default)]
+struct ColumnStats {
+    count: usize,
+    distinct: HashSet<String>,
+    min: Option<String>,
+    max: Option<String>,
+    // Cleared as soon as a non-numeric value shows up
+    numeric: bool,
+    min_number: f64,
+    max_number: f64,
+    sum: f64,
+}
+
+impl ColumnStats {
+    fn new() -> ColumnStats {
+        ColumnStats {
+            numeric: true,
+            min_number: f64::INFINITY,
+            max_number: f64::NEG_INFINITY,
+            ..Default::default()
+        }
+    }
+
+    fn add(&mut self, value: &str) {
+        if value.is_empty() {
+            return;
+        }
+
+        self.count += 1;
+        if !self.distinct.contains(value) {
+            self.distinct.insert(value.to_string());
+        }
+
+        if self.min.as_deref().is_none_or(|min| value < min) {
+            self.min = Some(value.to_string());
+        }
+        if self.max.as_deref().is_none_or(|max| value > max) {
+            self.max = Some(value.to_string());
+        }
+
+        if self.numeric {
+            match value.parse::<f64>() {
+                Ok(number) if number.is_finite() => {
+                    self.sum += number;
+                    self.min_number = self.min_number.min(number);
+                    self.max_number = self.max_number.max(number);
+                }
+                _ => self.numeric = false,
+            }
+        }
+    }
+
+    fn row(&self, name: &str) -> StringRecord {
+        let mut row = vec![
+            name.to_string(),
+            self.count.to_string(),
+            self.distinct.len().to_string(),
+        ];
+
+        if self.numeric && self.count > 0 {
+            row.push(format_number(self.min_number));
+            row.push(format_number(self.max_number));
+            row.push(format_number(self.sum / self.count as f64));
+            row.push(format_number(self.sum));
+        } else {
+            row.push(self.min.clone().unwrap_or_default());
+            row.push(self.max.clone().unwrap_or_default());
+            row.push(String::new());
+            row.push(String::new());
+        }
+
+        row.into_iter().collect()
+    }
+}
+
+pub fn write_csv_stats(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Table(Default::default()))?;
+    let source = args.finish()?.join(" ");
+
+    let mut reader = read_options.open(&source)?;
+    let headers = read_options.headers(&mut reader)?;
+
+    let width = headers.as_ref().map_or(0, |headers| headers.len());
+    let mut columns: Vec<ColumnStats> = (0..width).map(|_| ColumnStats::new()).collect();
+    let mut any_records = false;
+    for record in reader.records() {
+        let record = record?;
+        any_records = true;
+        if columns.len() < record.len() {
+            columns.resize_with(record.len(), ColumnStats::new);
+        }
+        for (column, value) in columns.iter_mut().zip(record.iter()) {
+            column.add(value);
+        }
+    }
+
+    if !any_records {
+        return Err(Error::invalid("CSV has no data rows"));
+    }
+
+    let names: Vec<String> = (0..columns.len())
+        .map(
+            |i| match headers.as_ref().and_then(|headers| headers.get(i)) {
+                Some(header) => header.to_string(),
+                None => format!("col{}", i + 1),
+            },
+        )
+        .collect();
+
+    let stats_headers = StringRecord::from(vec![
+        "column", "count", "distinct", "min", "max", "mean", "sum",
+    ]);
+    let mut table = TableOutput::new(&format, Some(&stats_headers), PAGE_SIZE);
+    for (column, name) in columns.iter().zip(&names) {
+        table.push(out, column.row(name))?;
+    }
+    table.finish(out)
+}
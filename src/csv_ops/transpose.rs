@@ -0,0 +1,52 @@
+use std::io::Write;
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::format::{OutputFormat, TableOutput};
+use super::{ReadOptions, PAGE_SIZE};
+
+// Swaps rows and columns, every column becomes a row led by its header name
+pub fn write_csv_transpose(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Table(Default::default()))?;
+    let source = args.finish()?.join(" ");
+
+    let mut reader = read_options.open(&source)?;
+    let headers = read_options.headers(&mut reader)?;
+    let records = reader.records().collect::<Result<Vec<StringRecord>, _>>()?;
+    if records.is_empty() {
+        return Err(Error::invalid("CSV has no data rows"));
+    }
+
+    let width = records
+        .iter()
+        .map(StringRecord::len)
+        .chain(headers.as_ref().map(StringRecord::len))
+        .max()
+        .unwrap_or(0);
+
+    let mut transposed_headers = vec!["column".to_string()];
+    transposed_headers.extend((1..=records.len()).map(|i| format!("row{}", i)));
+    let transposed_headers = StringRecord::from(transposed_headers);
+
+    let mut table = TableOutput::new(&format, Some(&transposed_headers), PAGE_SIZE);
+    for column in 0..width {
+        let name = match headers.as_ref().and_then(|headers| headers.get(column)) {
+            Some(header) => header.to_string(),
+            None => format!("col{}", column + 1),
+        };
+
+        let mut row = vec![name];
+        row.extend(
+            records
+                .iter()
+                .map(|record| record.get(column).unwrap_or("").to_string()),
+        );
+        table.push(out, StringRecord::from(row))?;
+    }
+    table.finish(out)
+}
@@ -0,0 +1,67 @@
+// Type of a column inferred from all of its non-empty values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnType {
+    // No non-empty value seen yet
+    Empty,
+    Integer,
+    Float,
+    Boolean,
+    Text,
+}
+
+impl ColumnType {
+    pub fn of(value: &str) -> ColumnType {
+        if value.is_empty() {
+            ColumnType::Empty
+        } else if value.parse::<i64>().is_ok() {
+            ColumnType::Integer
+        } else if value.parse::<f64>().is_ok_and(f64::is_finite) && looks_numeric(value) {
+            ColumnType::Float
+        } else if parse_bool(value).is_some() {
+            ColumnType::Boolean
+        } else {
+            ColumnType::Text
+        }
+    }
+
+    // Widens the type so that it also covers `value`
+    pub fn merge(self, value: &str) -> ColumnType {
+        match (self, ColumnType::of(value)) {
+            (current, ColumnType::Empty) => current,
+            (ColumnType::Empty, other) => other,
+            (current, other) if current == other => current,
+            (ColumnType::Integer, ColumnType::Float) | (ColumnType::Float, ColumnType::Integer) => {
+                ColumnType::Float
+            }
+            _ => ColumnType::Text,
+        }
+    }
+}
+
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "TRUE" | "True" => Some(true),
+        "false" | "FALSE" | "False" => Some(false),
+        _ => None,
+    }
+}
+
+// Rejects words `f64` happily parses, like "inf" or "NaN"
+fn looks_numeric(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+}
+
+// Whole numbers without a fraction, others rounded to 4 decimals
+pub fn format_number(number: f64) -> String {
+    if number.fract() == 0.0 && number.abs() < 1e15 {
+        format!("{}", number as i64)
+    } else {
+        let formatted = format!("{:.4}", number);
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
@@ -0,0 +1,275 @@
+use std::io::{BufRead, BufReader, Write};
+
+use csv::StringRecord;
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::format::{OutputFormat, TableOutput};
+use super::{ReadOptions, PAGE_SIZE};
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    FieldStart,
+    Unquoted,
+    Quoted,
+    // A quote inside a quoted field, either closing it or the first half of `""`
+    QuoteInQuoted,
+    // The character after `--escape` inside a quoted field
+    Escaped,
+}
+
+// Walks the raw bytes of a CSV and collects every problem instead of
+// stopping at the first malformed record like the csv reader does
+struct Validator {
+    delimiter: u8,
+    // `None` with `--no-quoting`
+    quote: Option<u8>,
+    escape: Option<u8>,
+    comment: Option<u8>,
+    state: State,
+    // Physical line the current record started on
+    record_line: usize,
+    fields: usize,
+    has_content: bool,
+    expected_fields: Option<usize>,
+    records: usize,
+    line_endings: [usize; 3],
+    issues: Vec<(usize, String)>,
+}
+
+const LF: usize = 0;
+const CRLF: usize = 1;
+const CR: usize = 2;
+
+impl Validator {
+    // The first record, usually the header, sets the expected field count
+    fn new(read_options: &ReadOptions) -> Validator {
+        Validator {
+            delimiter: read_options.delimiter,
+            quote: read_options.quoting.then_some(read_options.quote),
+            escape: read_options.escape,
+            comment: read_options.comment,
+            state: State::FieldStart,
+            record_line: 1,
+            fields: 0,
+            has_content: false,
+            expected_fields: None,
+            records: 0,
+            line_endings: [0; 3],
+            issues: Vec::new(),
+        }
+    }
+
+    fn line(&mut self, number: usize, mut line: &[u8]) {
+        if let Err(e) = std::str::from_utf8(line) {
+            self.issues.push((
+                number,
+                format!("invalid UTF-8 at byte {}", e.valid_up_to() + 1),
+            ));
+        }
+
+        let ending = if line.ends_with(b"\r\n") {
+            line = &line[..line.len() - 2];
+            Some(CRLF)
+        } else if line.ends_with(b"\n") {
+            line = &line[..line.len() - 1];
+            Some(LF)
+        } else {
+            None
+        };
+        if let Some(ending) = ending {
+            self.line_endings[ending] += 1;
+        }
+
+        // Comment lines are skipped by the reader, but only between records
+        if self.state == State::FieldStart
+            && !self.has_content
+            && self
+                .comment
+                .is_some_and(|comment| line.first() == Some(&comment))
+        {
+            self.record_line = number + 1;
+            return;
+        }
+
+        for &byte in line {
+            self.byte(number, byte);
+        }
+        self.end_of_line(number);
+    }
+
+    fn byte(&mut self, number: usize, byte: u8) {
+        if byte == b'\r' && !matches!(self.state, State::Quoted | State::Escaped) {
+            self.line_endings[CR] += 1;
+        }
+        self.has_content = true;
+
+        let is_quote = Some(byte) == self.quote;
+        let is_delimiter = byte == self.delimiter;
+        self.state = match self.state {
+            State::FieldStart if is_quote => State::Quoted,
+            State::FieldStart | State::Unquoted if is_delimiter => {
+                self.fields += 1;
+                State::FieldStart
+            }
+            State::FieldStart => State::Unquoted,
+            State::Unquoted if is_quote => {
+                self.issues
+                    .push((number, "quote inside an unquoted field".to_string()));
+                State::Unquoted
+            }
+            State::Unquoted => State::Unquoted,
+            State::Quoted if Some(byte) == self.escape => State::Escaped,
+            State::Quoted if is_quote => State::QuoteInQuoted,
+            State::Quoted | State::Escaped => State::Quoted,
+            // Doubled quotes only escape when no `--escape` character is set
+            State::QuoteInQuoted if is_quote && self.escape.is_none() => State::Quoted,
+            State::QuoteInQuoted if is_delimiter => {
+                self.fields += 1;
+                State::FieldStart
+            }
+            State::QuoteInQuoted => {
+                self.issues.push((
+                    number,
+                    "unexpected character after closing quote".to_string(),
+                ));
+                State::Unquoted
+            }
+        };
+    }
+
+    fn end_of_line(&mut self, number: usize) {
+        if matches!(self.state, State::Quoted | State::Escaped) {
+            // The quoted field continues on the next line
+            return;
+        }
+
+        if self.has_content {
+            self.end_of_record();
+        }
+        self.state = State::FieldStart;
+        self.fields = 0;
+        self.has_content = false;
+        self.record_line = number + 1;
+    }
+
+    fn end_of_record(&mut self) {
+        let fields = self.fields + 1;
+        self.records += 1;
+
+        match self.expected_fields {
+            None => self.expected_fields = Some(fields),
+            Some(expected) if expected != fields => self.issues.push((
+                self.record_line,
+                format!("expected {} fields, found {}", expected, fields),
+            )),
+            Some(_) => {}
+        }
+    }
+
+    fn finish(&mut self) {
+        if matches!(self.state, State::Quoted | State::Escaped) {
+            // The rest of the file was read as this one record
+            self.records += 1;
+            self.issues
+                .push((self.record_line, "quoted field is never closed".to_string()));
+        } else if self.has_content {
+            self.end_of_record();
+        }
+
+        let used = self.line_endings.iter().filter(|&&count| count > 0).count();
+        if used > 1 {
+            self.issues.push((
+                0,
+                format!(
+                    "mixed line endings: {} LF, {} CRLF, {} CR",
+                    self.line_endings[LF], self.line_endings[CRLF], self.line_endings[CR]
+                ),
+            ));
+        }
+
+        // By line, with the problems of the whole file, on line 0, last
+        self.issues
+            .sort_by_key(|&(line, _)| if line == 0 { usize::MAX } else { line });
+    }
+}
+
+// Reports malformed rows with their line numbers, failing when any were found
+pub fn write_csv_validate(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let read_options = ReadOptions::from_args(&mut args)?;
+    let format = OutputFormat::from_args(&mut args, OutputFormat::Table(Default::default()))?;
+    let source = args.finish()?.join(" ");
+
+    let mut reader = BufReader::new(read_options.source(&source)?);
+    let mut validator = Validator::new(&read_options);
+    let mut line = Vec::new();
+    let mut number = 0;
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        number += 1;
+        validator.line(number, &line);
+        line.clear();
+    }
+    validator.finish();
+
+    let records = if read_options.no_header {
+        validator.records
+    } else {
+        validator.records.saturating_sub(1)
+    };
+
+    if validator.issues.is_empty() {
+        writeln!(out, "No problems found in {} records", records)?;
+        return Ok(());
+    }
+
+    let headers = StringRecord::from(vec!["line", "problem"]);
+    let mut table = TableOutput::new(&format, Some(&headers), PAGE_SIZE);
+    for (line, issue) in &validator.issues {
+        let line = if *line == 0 {
+            String::new()
+        } else {
+            line.to_string()
+        };
+        table.push(out, StringRecord::from(vec![line, issue.clone()]))?;
+    }
+    table.finish(out)?;
+
+    Err(Error::invalid(format!(
+        "Found {} problems in {} records",
+        validator.issues.len(),
+        records
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(csv: &[u8]) -> (usize, Vec<(usize, String)>) {
+        let options = ReadOptions::from_args(&mut OperationArgs::parse("")).unwrap();
+        let mut validator = Validator::new(&options);
+        for (number, line) in csv.split_inclusive(|&b| b == b'\n').enumerate() {
+            validator.line(number + 1, line);
+        }
+        validator.finish();
+        (validator.records, validator.issues)
+    }
+
+    #[test]
+    fn problems_are_sorted_by_line() {
+        let (records, issues) = issues(b"a,b\n1,\"open\n2,3\r\n4,5,6\n\xff\n");
+        assert_eq!(records, 2);
+        let lines: Vec<usize> = issues.iter().map(|&(line, _)| line).collect();
+        assert_eq!(lines, [2, 5, 0]);
+        assert_eq!(issues[0].1, "quoted field is never closed");
+    }
+
+    #[test]
+    fn fields_are_counted_per_record() {
+        let (records, issues) = issues(b"a,b\n1,2\n3\n\"4\n5\",6\n");
+        assert_eq!(records, 4);
+        assert_eq!(issues, [(3, "expected 2 fields, found 1".to_string())]);
+    }
+}
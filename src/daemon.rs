@@ -0,0 +1,128 @@
+use std::env;
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use rust_text_transformer::{args, OperationRegistry};
+
+use crate::i18n;
+
+// Where `daemon` listens and `client` connects unless `--socket` or
+// TEXT_MODIFIER_SOCKET says otherwise
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = env::var_os("TEXT_MODIFIER_SOCKET").filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("text-modifier.sock"),
+        _ => {
+            let user = env::var("USER").unwrap_or_else(|_| "default".to_string());
+            env::temp_dir().join(format!("text-modifier-{}.sock", user))
+        }
+    }
+}
+
+// The `serve` arguments of `daemon`, which listens on the default socket
+// unless one is given
+pub fn serve_args(args: &[String]) -> Vec<String> {
+    let mut args = args.to_vec();
+    if !args
+        .iter()
+        .any(|arg| arg == "--socket" || arg.starts_with("--socket="))
+    {
+        args.push("--socket".to_string());
+        args.push(socket_path().display().to_string());
+    }
+    args
+}
+
+// Arguments of `client`, `[--socket PATH] <operation> <input>...`
+pub struct ClientOptions {
+    socket: PathBuf,
+    operation: String,
+    input: Vec<String>,
+}
+
+impl ClientOptions {
+    pub fn parse(args: &[String]) -> Result<ClientOptions, String> {
+        let mut socket = None;
+        let mut args = args.iter();
+        let operation = loop {
+            match args.next().map(String::as_str) {
+                Some("--socket") => {
+                    let path = args.next().ok_or("Option --socket requires a value")?;
+                    socket = Some(PathBuf::from(path));
+                }
+                Some(arg) if arg.starts_with("--socket=") => {
+                    socket = Some(PathBuf::from(&arg["--socket=".len()..]));
+                }
+                Some(operation) => break operation.to_string(),
+                None => return Err(i18n::text("client-usage", &[])),
+            }
+        };
+        let input: Vec<String> = args.cloned().collect();
+        if input.is_empty() {
+            return Err(i18n::text("client-usage", &[]));
+        }
+        Ok(ClientOptions {
+            socket: socket.unwrap_or_else(socket_path),
+            operation,
+            input,
+        })
+    }
+}
+
+// Sends one command to the daemon and prints its output. False when the
+// daemon answered with an error, which is printed on stderr
+pub fn client(options: ClientOptions) -> io::Result<bool> {
+    // Text is sent as given and other operations get their arguments quoted,
+    // like on the command line. Operations the daemon loaded from plugins are
+    // unknown here, plugins only provide text operations
+    let text = OperationRegistry::builtin()
+        .find(&options.operation)
+        .map_or(true, |operation| operation.is_text_operation());
+    let input = if text {
+        options.input.join(" ")
+    } else {
+        args::join(&options.input)
+    };
+    // The protocol takes one command per line
+    let line = format!(
+        "{} {}\n",
+        options.operation,
+        input.replace(['\r', '\n'], " ")
+    );
+
+    let mut stream = UnixStream::connect(&options.socket).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!(
+                "Could not connect to the daemon on {}: {}",
+                options.socket.display(),
+                e
+            ),
+        )
+    })?;
+    stream.write_all(line.as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    if response.is_empty() {
+        eprintln!("Error: The daemon closed the connection without answering");
+        return Ok(false);
+    }
+    if let Some(error) = response.strip_prefix("Error\t") {
+        // `code<TAB>class<TAB>message`
+        let message = error.splitn(3, '\t').nth(2).unwrap_or(error);
+        eprint!("Error: {}", message);
+        return Ok(false);
+    }
+    let text = match response.split_once('\n') {
+        Some((banner, text)) if banner.starts_with("Selected operation: ") => text,
+        _ => &response,
+    };
+    io::stdout().write_all(text.as_bytes())?;
+    Ok(true)
+}
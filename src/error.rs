@@ -0,0 +1,269 @@
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::cancel::{OverLimit, TimedOut};
+use crate::redaction::shown;
+
+// Everything an operation can fail with, so library callers can match on the kind
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Invalid operation: {input}{}", did_you_mean(suggestions))]
+    InvalidOperation {
+        input: String,
+        // Registered names close to the input
+        suggestions: Vec<String>,
+    },
+
+    // `path` is `None` for stdin, stdout and in-memory data
+    #[error("{}", io_message(path, source))]
+    Io {
+        path: Option<PathBuf>,
+        #[source]
+        source: io::Error,
+    },
+
+    // `line` is where the offending record started, when the reader knows it
+    #[cfg(feature = "csv")]
+    #[error("{source}")]
+    Csv {
+        line: Option<u64>,
+        #[source]
+        source: csv::Error,
+    },
+
+    #[error("{reason}")]
+    InvalidInput { reason: String },
+
+    // A value an operation validates, like a card number or an IBAN, is not
+    // one, e.g. to report the rows to fix in an export. Shown as
+    // `set_redaction` says
+    #[error("Invalid value {}: {reason}", shown(value))]
+    InvalidValue { value: String, reason: String },
+
+    // The operation ran longer than the limit given to `with_timeout`
+    #[error("Timed out after {limit:?}")]
+    Timeout { limit: Duration },
+
+    // Input over `--max-input-bytes`, refused before the operation runs or for
+    // a file as soon as it is opened
+    #[error("Input is over the limit of {limit} bytes")]
+    TooLarge { limit: usize },
+
+    // Refused by a server before running, e.g. `serve --rate-limit`
+    #[error("Too many requests: {reason}")]
+    TooManyRequests { reason: String },
+
+    // A server refused the client, which gave no valid token
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    // Input or files `set_input_policy` doesn't allow, e.g. files on a server
+    #[error("Not allowed: {reason}")]
+    Forbidden { reason: String },
+
+    #[error("Failed to load plugin '{}': {reason}", path.display())]
+    Plugin { path: PathBuf, reason: String },
+
+    #[cfg(any(feature = "csv", feature = "markdown"))]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "parquet")]
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+impl Error {
+    pub fn invalid(reason: impl Into<String>) -> Error {
+        Error::InvalidInput {
+            reason: reason.into(),
+        }
+    }
+
+    pub fn invalid_value(value: impl Into<String>, reason: impl Into<String>) -> Error {
+        Error::InvalidValue {
+            value: value.into(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Error {
+        Error::Io {
+            path: Some(path.into()),
+            source,
+        }
+    }
+
+    // A code that stays the same across versions, for automation to branch on
+    // instead of the message. Codes are never reused for another class
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidOperation { .. } => "E001",
+            Error::InvalidInput { .. } => "E002",
+            Error::Timeout { .. } => "E003",
+            Error::InvalidValue { .. } => "E004",
+            Error::Io { .. } => "E010",
+            #[cfg(feature = "csv")]
+            Error::Csv { .. } => "E020",
+            #[cfg(any(feature = "csv", feature = "markdown"))]
+            Error::Json(_) => "E021",
+            #[cfg(feature = "parquet")]
+            Error::Parquet(_) => "E022",
+            Error::Plugin { .. } => "E030",
+            Error::TooLarge { .. } => "E040",
+            Error::TooManyRequests { .. } => "E041",
+            Error::Unauthorized => "E042",
+            Error::Forbidden { .. } => "E043",
+        }
+    }
+
+    // The name of the class `code` stands for
+    pub fn class(&self) -> &'static str {
+        match self {
+            Error::InvalidOperation { .. } => "UnknownOperation",
+            Error::InvalidInput { .. } => "InvalidInput",
+            Error::Timeout { .. } => "Timeout",
+            Error::InvalidValue { .. } => "InvalidValue",
+            Error::Io { .. } => "IoError",
+            #[cfg(feature = "csv")]
+            Error::Csv { .. } => "CsvMalformedRow",
+            #[cfg(any(feature = "csv", feature = "markdown"))]
+            Error::Json(_) => "JsonInvalid",
+            #[cfg(feature = "parquet")]
+            Error::Parquet(_) => "ParquetInvalid",
+            Error::Plugin { .. } => "PluginLoadFailed",
+            Error::TooLarge { .. } => "InputTooLarge",
+            Error::TooManyRequests { .. } => "TooManyRequests",
+            Error::Unauthorized => "Unauthorized",
+            Error::Forbidden { .. } => "Forbidden",
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Error {
+        match limit_reached(&source) {
+            Some(e) => e,
+            None => Error::Io { path: None, source },
+        }
+    }
+}
+
+#[cfg(feature = "csv")]
+impl From<csv::Error> for Error {
+    fn from(source: csv::Error) -> Error {
+        if let csv::ErrorKind::Io(e) = source.kind() {
+            if let Some(e) = limit_reached(e) {
+                return e;
+            }
+        }
+        Error::Csv {
+            line: source.position().map(csv::Position::line),
+            source,
+        }
+    }
+}
+
+// The time or input limit a read stopped at
+fn limit_reached(e: &io::Error) -> Option<Error> {
+    let inner = e.get_ref()?;
+    if let Some(timed_out) = inner.downcast_ref::<TimedOut>() {
+        return Some(Error::Timeout { limit: timed_out.0 });
+    }
+    inner
+        .downcast_ref::<OverLimit>()
+        .map(|over| Error::TooLarge { limit: over.0 })
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(", did you mean {}?", suggestions.join(" or "))
+    }
+}
+
+fn io_message(path: &Option<PathBuf>, source: &io::Error) -> String {
+    match path {
+        Some(path) => format!("Failed to read '{}': {}", path.display(), source),
+        None => source.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_name_what_failed() {
+        let e = Error::InvalidOperation {
+            input: "uppercse".to_string(),
+            suggestions: vec!["uppercase".to_string()],
+        };
+        assert_eq!(
+            e.to_string(),
+            "Invalid operation: uppercse, did you mean uppercase?"
+        );
+        let e = Error::io("data.csv", io::Error::from(io::ErrorKind::NotFound));
+        assert!(
+            e.to_string().starts_with("Failed to read 'data.csv': "),
+            "{}",
+            e
+        );
+        let e = Error::Timeout {
+            limit: Duration::from_millis(500),
+        };
+        assert_eq!(e.to_string(), "Timed out after 500ms");
+    }
+
+    #[test]
+    fn reads_stopped_at_a_limit_become_that_limit() {
+        let e = Error::from(io::Error::other(TimedOut(Duration::from_secs(2))));
+        assert!(matches!(e, Error::Timeout { limit } if limit == Duration::from_secs(2)));
+        let e = Error::from(io::Error::other(OverLimit(10)));
+        assert!(matches!(e, Error::TooLarge { limit: 10 }));
+        let e = Error::from(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert!(matches!(e, Error::Io { path: None, .. }));
+    }
+
+    #[test]
+    fn codes_and_classes_are_unique() {
+        let errors = [
+            Error::InvalidOperation {
+                input: String::new(),
+                suggestions: Vec::new(),
+            },
+            Error::Io {
+                path: None,
+                source: io::Error::from(io::ErrorKind::NotFound),
+            },
+            Error::invalid(""),
+            Error::Timeout {
+                limit: Duration::ZERO,
+            },
+            Error::invalid_value("", ""),
+            Error::Plugin {
+                path: PathBuf::new(),
+                reason: String::new(),
+            },
+            Error::TooLarge { limit: 0 },
+            Error::TooManyRequests {
+                reason: String::new(),
+            },
+            Error::Unauthorized,
+            Error::Forbidden {
+                reason: String::new(),
+            },
+        ];
+        let codes: std::collections::HashSet<_> = errors.iter().map(Error::code).collect();
+        let classes: std::collections::HashSet<_> = errors.iter().map(Error::class).collect();
+        assert_eq!(codes.len(), errors.len());
+        assert_eq!(classes.len(), errors.len());
+        assert!(codes
+            .iter()
+            .all(|code| code.len() == 4 && code.starts_with('E')));
+    }
+}
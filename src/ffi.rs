@@ -0,0 +1,75 @@
+// The C ABI of the built-in operations, declared in include/text_modifier.h.
+// Doc comments here are copied into the header by cbindgen
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic;
+use std::ptr;
+
+use crate::OperationRegistry;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Runs the built-in operation `op_name`, e.g. "slugify", on `input`.
+///
+/// Returns the output, to be released with `tm_free`, or NULL when the
+/// operation failed, with the message in `tm_last_error`.
+///
+/// # Safety
+///
+/// `op_name` and `input` must be NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn tm_transform(op_name: *const c_char, input: *const c_char) -> *mut c_char {
+    // A panic must not unwind into C
+    let result = panic::catch_unwind(|| unsafe { transform(op_name, input) })
+        .unwrap_or_else(|_| Err("The operation panicked".to_string()));
+    let (output, error) = match result {
+        Ok(output) => (output.into_raw(), None),
+        Err(message) => (ptr::null_mut(), Some(message)),
+    };
+    LAST_ERROR.with_borrow_mut(|last| {
+        // A message with a NUL byte would be cut short in C
+        *last = error.map(|message| CString::new(message.replace('\0', "")).unwrap_or_default())
+    });
+    output
+}
+
+/// Releases a string returned by `tm_transform`. NULL is ignored.
+///
+/// # Safety
+///
+/// `text` must come from `tm_transform` and not have been released before.
+#[no_mangle]
+pub unsafe extern "C" fn tm_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(unsafe { CString::from_raw(text) });
+    }
+}
+
+/// The message of the last `tm_transform` on this thread that failed, or NULL
+/// when it succeeded. Valid until the next `tm_transform` on the thread.
+#[no_mangle]
+pub extern "C" fn tm_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|last| {
+        last.as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+unsafe fn transform(op_name: *const c_char, input: *const c_char) -> Result<CString, String> {
+    if op_name.is_null() || input.is_null() {
+        return Err("op_name and input must not be NULL".to_string());
+    }
+    let op_name = unsafe { CStr::from_ptr(op_name) }
+        .to_str()
+        .map_err(|_| "op_name is not valid UTF-8".to_string())?;
+    let input = unsafe { CStr::from_ptr(input) }
+        .to_str()
+        .map_err(|_| "input is not valid UTF-8".to_string())?;
+
+    let output = OperationRegistry::builtin()
+        .run(op_name, input)
+        .map_err(|e| e.to_string())?;
+    CString::new(output.text).map_err(|_| "The output contains a NUL byte".to_string())
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::{self, JoinHandle};
+use tokio::time;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::MetadataValue;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Request, Response, Status, Streaming};
+
+use rust_text_transformer::{
+    args, process_operation_async, Command, Error, OperationOutput, OperationRegistry,
+};
+
+use crate::auth::Tokens;
+use crate::limits::Limits;
+use crate::{shutdown, SHUTDOWN_POLL};
+
+mod proto {
+    tonic::include_proto!("text_modifier.v1");
+}
+
+use proto::text_modifier_server::{TextModifier, TextModifierServer};
+use proto::{
+    ListOperationsRequest, ListOperationsResponse, Operation, TransformRequest, TransformResponse,
+};
+
+// Serves the TextModifier service until Ctrl-C or SIGTERM
+pub async fn serve(
+    listener: TcpListener,
+    registry: Arc<OperationRegistry>,
+    jobs: usize,
+    limits: Arc<Limits>,
+    tokens: Option<Arc<Tokens>>,
+) -> std::io::Result<()> {
+    let service = Service {
+        registry,
+        jobs,
+        limits,
+    };
+    // Calls need `authorization: Bearer <token>` metadata with a known token
+    let authenticate = move |request: Request<()>| match &tokens {
+        Some(tokens) => {
+            let token = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+            if token.is_some_and(|token| tokens.accepts(token.trim())) {
+                Ok(request)
+            } else {
+                Err(status(Error::Unauthorized))
+            }
+        }
+        None => Ok(request),
+    };
+    tonic::transport::Server::builder()
+        .add_service(TextModifierServer::with_interceptor(service, authenticate))
+        .serve_with_incoming_shutdown(TcpIncoming::from(listener), async {
+            while !shutdown::requested() {
+                time::sleep(SHUTDOWN_POLL).await;
+            }
+        })
+        .await
+        .map_err(std::io::Error::other)
+}
+
+struct Service {
+    registry: Arc<OperationRegistry>,
+    // Requests of one stream running at once
+    jobs: usize,
+    limits: Arc<Limits>,
+}
+
+#[tonic::async_trait]
+impl TextModifier for Service {
+    async fn transform(
+        &self,
+        request: Request<TransformRequest>,
+    ) -> Result<Response<TransformResponse>, Status> {
+        let client = client(&request);
+        let command = command(&self.registry, request.get_ref()).map_err(status)?;
+        let admitted = self
+            .limits
+            .admit(&client, command.input.len())
+            .map_err(status)?;
+        let output = process_operation_async(Arc::clone(&self.registry), command)
+            .await
+            .map_err(status)?;
+        drop(admitted);
+        Ok(Response::new(response(output)))
+    }
+
+    type TransformStreamStream = ReceiverStream<Result<TransformResponse, Status>>;
+
+    async fn transform_stream(
+        &self,
+        request: Request<Streaming<TransformRequest>>,
+    ) -> Result<Response<Self::TransformStreamStream>, Status> {
+        type Pending = Result<JoinHandle<Result<OperationOutput, Error>>, Error>;
+        let client = client(&request);
+        let mut requests = request.into_inner();
+        let (pending_tx, mut pending_rx) = mpsc::channel::<Pending>(self.jobs.max(1));
+        let (tx, rx) = mpsc::channel(self.jobs.max(1));
+
+        let registry = Arc::clone(&self.registry);
+        let limits = Arc::clone(&self.limits);
+        let error_tx = tx.clone();
+        task::spawn(async move {
+            loop {
+                let request = match requests.message().await {
+                    Ok(Some(request)) => request,
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = error_tx.send(Err(e)).await;
+                        break;
+                    }
+                };
+                let pending = command(&registry, &request).and_then(|command| {
+                    let admitted = limits.admit(&client, command.input.len())?;
+                    let registry = Arc::clone(&registry);
+                    Ok(task::spawn(async move {
+                        let output = process_operation_async(registry, command).await;
+                        drop(admitted);
+                        output
+                    }))
+                });
+                // The client stopped reading responses
+                if pending_tx.send(pending).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Responses are sent in request order while later requests keep running
+        task::spawn(async move {
+            while let Some(pending) = pending_rx.recv().await {
+                let result = match pending {
+                    Ok(handle) => handle
+                        .await
+                        .unwrap_or_else(|e| Err(Error::invalid(e.to_string()))),
+                    Err(e) => Err(e),
+                };
+                let response = match result {
+                    Ok(output) => response(output),
+                    Err(e) => TransformResponse {
+                        error: e.to_string(),
+                        error_code: e.code().to_string(),
+                        ..TransformResponse::default()
+                    },
+                };
+                if tx.send(Ok(response)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn list_operations(
+        &self,
+        _request: Request<ListOperationsRequest>,
+    ) -> Result<Response<ListOperationsResponse>, Status> {
+        let operations = self
+            .registry
+            .operations()
+            .iter()
+            .map(|operation| Operation {
+                name: operation.name().to_string(),
+                aliases: operation
+                    .aliases()
+                    .iter()
+                    .map(|alias| alias.to_string())
+                    .collect(),
+                description: operation.describe().to_string(),
+                text: operation.is_text_operation(),
+            })
+            .collect();
+        Ok(Response::new(ListOperationsResponse { operations }))
+    }
+}
+
+// Who the rate limit is counted for
+fn client<T>(request: &Request<T>) -> String {
+    match request.remote_addr() {
+        Some(address) => address.ip().to_string(),
+        None => String::new(),
+    }
+}
+
+// Options become `--name value` arguments in front of the input, an empty value a flag
+fn command(registry: &OperationRegistry, request: &TransformRequest) -> Result<Command, Error> {
+    let operation = registry.find(&request.operation)?;
+    let input = format!("{} {}", options(&request.options), request.input);
+    Ok(Command {
+        operation,
+        input: input.trim_start().to_string(),
+    })
+}
+
+fn options(options: &HashMap<String, String>) -> String {
+    let mut names: Vec<&String> = options.keys().collect();
+    names.sort();
+
+    let mut tokens = Vec::new();
+    for name in names {
+        tokens.push(format!("--{}", name));
+        if !options[name].is_empty() {
+            tokens.push(options[name].clone());
+        }
+    }
+    args::join(&tokens)
+}
+
+fn response(output: OperationOutput) -> TransformResponse {
+    TransformResponse {
+        duration_seconds: output.duration.as_secs_f64(),
+        bytes_in: output.bytes_in,
+        bytes_out: output.bytes_out,
+        warnings: output.warnings,
+        operation: output.operation,
+        text: output.text,
+        error: String::new(),
+        error_code: String::new(),
+    }
+}
+
+// With the stable code of the error as `x-error-code` and `x-error-class` metadata
+fn status(e: Error) -> Status {
+    let mut status = match e {
+        Error::InvalidOperation { .. } => Status::not_found(e.to_string()),
+        Error::Timeout { .. } => Status::deadline_exceeded(e.to_string()),
+        Error::TooLarge { .. } | Error::TooManyRequests { .. } => {
+            Status::resource_exhausted(e.to_string())
+        }
+        Error::Unauthorized => Status::unauthenticated(e.to_string()),
+        Error::Forbidden { .. } => Status::permission_denied(e.to_string()),
+        // A path in the error is one given in the request
+        Error::Io { path: None, .. } | Error::Plugin { .. } => Status::internal(e.to_string()),
+        _ => Status::invalid_argument(e.to_string()),
+    };
+    let metadata = status.metadata_mut();
+    metadata.insert("x-error-code", MetadataValue::from_static(e.code()));
+    metadata.insert("x-error-class", MetadataValue::from_static(e.class()));
+    status
+}
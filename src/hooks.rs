@@ -0,0 +1,105 @@
+use std::io;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use rust_text_transformer::Error;
+
+use crate::i18n;
+
+// `on_success` and `on_error` of the config file, shell commands run once a
+// command line or the commands of stdin are done
+struct Hooks {
+    on_success: Option<String>,
+    on_error: Option<String>,
+    start: Instant,
+}
+
+static HOOKS: OnceLock<Hooks> = OnceLock::new();
+
+// Commands run and failed so far, and the last error
+static COMMANDS: AtomicU64 = AtomicU64::new(0);
+static FAILED: AtomicU64 = AtomicU64::new(0);
+static LAST_ERROR: Mutex<Option<(&'static str, String)>> = Mutex::new(None);
+
+pub fn install(on_success: Option<String>, on_error: Option<String>) {
+    if on_success.is_none() && on_error.is_none() {
+        return;
+    }
+    let _ = HOOKS.set(Hooks {
+        on_success,
+        on_error,
+        start: Instant::now(),
+    });
+}
+
+pub fn ran() {
+    COMMANDS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn failed(e: &Error) {
+    FAILED.fetch_add(1, Ordering::Relaxed);
+    if HOOKS.get().is_some() {
+        *LAST_ERROR.lock().expect("hooks lock poisoned") = Some((e.code(), e.to_string()));
+    }
+}
+
+// Runs `on_error` when the run exits with an error or any command failed,
+// `on_success` otherwise. What happened is in `TM_*` environment variables
+pub fn finished(exit_status: i32) {
+    let Some(hooks) = HOOKS.get() else {
+        return;
+    };
+    let failed = FAILED.load(Ordering::Relaxed);
+    let success = exit_status == 0 && failed == 0;
+    let hook = if success {
+        &hooks.on_success
+    } else {
+        &hooks.on_error
+    };
+    let Some(hook) = hook else {
+        return;
+    };
+
+    // Its output goes to stderr, stdout only has what the commands wrote
+    let mut command = shell(hook);
+    command
+        .stdout(Stdio::from(io::stderr()))
+        .env("TM_RESULT", if success { "success" } else { "error" })
+        .env("TM_EXIT_STATUS", exit_status.to_string())
+        .env("TM_COMMANDS", COMMANDS.load(Ordering::Relaxed).to_string())
+        .env("TM_FAILED", failed.to_string())
+        .env(
+            "TM_DURATION",
+            hooks.start.elapsed().as_secs_f64().to_string(),
+        );
+    if let Some((code, message)) = &*LAST_ERROR.lock().expect("hooks lock poisoned") {
+        command.env("TM_ERROR_CODE", code).env("TM_ERROR", message);
+    }
+    match command.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!(
+            "{}",
+            i18n::text("hook-failed", &[("hook", &hook), ("reason", &status)])
+        ),
+        Err(e) => eprintln!(
+            "{}",
+            i18n::text("hook-failed", &[("hook", &hook), ("reason", &e)])
+        ),
+    }
+}
+
+#[cfg(windows)]
+fn shell(hook: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(hook);
+    command
+}
+
+#[cfg(not(windows))]
+fn shell(hook: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(hook);
+    command
+}
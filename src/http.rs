@@ -0,0 +1,168 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Json, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tokio::net::TcpListener;
+use tokio::time;
+
+use rust_text_transformer::{
+    args, process_operation_async, Command, Error, OperationOutput, OperationRegistry,
+};
+
+use crate::auth::Tokens;
+use crate::limits::Limits;
+use crate::request::{self, OperationInfo, TransformRequest};
+use crate::{shutdown, SHUTDOWN_POLL};
+
+#[derive(Clone)]
+struct Server {
+    registry: Arc<OperationRegistry>,
+    limits: Arc<Limits>,
+}
+
+type Shared = State<Server>;
+type Client = ConnectInfo<SocketAddr>;
+
+// Serves the JSON API until Ctrl-C or SIGTERM
+pub async fn serve(
+    listener: TcpListener,
+    registry: Arc<OperationRegistry>,
+    limits: Arc<Limits>,
+    tokens: Option<Arc<Tokens>>,
+) -> std::io::Result<()> {
+    let mut router = Router::new()
+        .route("/v1/transform", post(transform))
+        .route("/v1/operations", get(operations))
+        .route("/v1/csv/render", post(render_csv));
+    // JSON escapes and options make the body larger than the input, which is
+    // checked against the limit itself for a JSON error
+    if let Some(limit) = limits.max_input_bytes() {
+        router = router.layer(DefaultBodyLimit::max(limit.saturating_mul(2).max(1 << 16)));
+    }
+    if let Some(tokens) = tokens {
+        router = router.layer(middleware::from_fn_with_state(tokens, authenticate));
+    }
+    let router = router.with_state(Server { registry, limits });
+
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async {
+        while !shutdown::requested() {
+            time::sleep(SHUTDOWN_POLL).await;
+        }
+    })
+    .await
+}
+
+async fn transform(
+    State(server): Shared,
+    ConnectInfo(client): Client,
+    Json(request): Json<TransformRequest>,
+) -> Result<Json<OperationOutput>, ApiError> {
+    let command = request.command(&server.registry)?;
+    server.run(client, command).await
+}
+
+// The CSV text itself, rendered like the `csv` operation with `--inline`
+#[derive(Deserialize)]
+struct RenderRequest {
+    input: String,
+    #[serde(default)]
+    options: Map<String, Value>,
+}
+
+async fn render_csv(
+    State(server): Shared,
+    ConnectInfo(client): Client,
+    Json(request): Json<RenderRequest>,
+) -> Result<Json<OperationOutput>, ApiError> {
+    let command = Command {
+        operation: server.registry.find("csv")?,
+        input: format!(
+            "{} --inline {}",
+            request::options(&request.options)?,
+            args::join(&[request.input])
+        )
+        .trim_start()
+        .to_string(),
+    };
+    server.run(client, command).await
+}
+
+async fn operations(State(server): Shared) -> Json<Vec<OperationInfo>> {
+    Json(OperationInfo::list(&server.registry))
+}
+
+// Requests need an `Authorization: Bearer <token>` header with a known token
+async fn authenticate(State(tokens): State<Arc<Tokens>>, request: Request, next: Next) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if token.is_some_and(|token| tokens.accepts(token.trim())) {
+        return next.run(request).await;
+    }
+    (
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        ApiError(Error::Unauthorized),
+    )
+        .into_response()
+}
+
+impl Server {
+    async fn run(
+        &self,
+        client: SocketAddr,
+        command: Command,
+    ) -> Result<Json<OperationOutput>, ApiError> {
+        let admitted = self
+            .limits
+            .admit(&client.ip().to_string(), command.input.len())?;
+        let output = process_operation_async(Arc::clone(&self.registry), command).await?;
+        drop(admitted);
+        Ok(Json(output))
+    }
+}
+
+// Errors are answered with `{"error": message}` and a status telling the kinds apart
+struct ApiError(Error);
+
+impl From<Error> for ApiError {
+    fn from(e: Error) -> ApiError {
+        ApiError(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            Error::InvalidOperation { .. } => StatusCode::NOT_FOUND,
+            Error::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            Error::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Error::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Error::Unauthorized => StatusCode::UNAUTHORIZED,
+            Error::Forbidden { .. } => StatusCode::FORBIDDEN,
+            // A path in the error is one given in the request
+            Error::Io { path: None, .. } | Error::Plugin { .. } => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            _ => StatusCode::BAD_REQUEST,
+        };
+        let body = serde_json::json!({
+            "error": self.0.to_string(),
+            "code": self.0.code(),
+            "class": self.0.class(),
+        });
+        (status, Json(body)).into_response()
+    }
+}
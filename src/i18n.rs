@@ -0,0 +1,152 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+use rust_text_transformer::{shown, Error as OperationError, TextOperation};
+
+// The languages of the messages in locales/, English where one has no translation
+#[derive(Clone, Copy, PartialEq)]
+pub enum Language {
+    English,
+    Czech,
+    German,
+}
+
+impl Language {
+    // A language tag or locale like `cs`, `de-AT` or `cs_CZ.UTF-8`
+    pub fn parse(value: &str) -> Option<Language> {
+        let tag = value.split(['_', '-', '.', '@']).next()?;
+        match tag.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Language::English),
+            "cs" => Some(Language::Czech),
+            "de" => Some(Language::German),
+            _ => None,
+        }
+    }
+
+    // Like gettext: LC_ALL, then LC_MESSAGES, then LANG
+    fn from_env() -> Language {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Language::parse(&value))
+            .unwrap_or(Language::English)
+    }
+
+    fn messages(self) -> (&'static str, &'static str) {
+        match self {
+            Language::English => ("en", include_str!("../locales/en/text-modifier.ftl")),
+            Language::Czech => ("cs", include_str!("../locales/cs/text-modifier.ftl")),
+            Language::German => ("de", include_str!("../locales/de/text-modifier.ftl")),
+        }
+    }
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+// The bundle of the language followed by the English one
+static BUNDLES: OnceLock<Vec<FluentBundle<FluentResource>>> = OnceLock::new();
+
+// `--lang`, which has to come before the first message
+pub fn set_language(language: Language) {
+    let _ = LANGUAGE.set(language);
+}
+
+// The message `id` with its `{ $name }` arguments filled in
+pub fn text(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.to_string());
+    }
+    message(id, &fluent_args).unwrap_or_else(|| id.to_string())
+}
+
+fn message(id: &str, args: &FluentArgs) -> Option<String> {
+    bundles().iter().find_map(|bundle| {
+        let pattern = bundle.get_message(id)?.value()?;
+        let mut errors = Vec::new();
+        Some(
+            bundle
+                .format_pattern(pattern, Some(args), &mut errors)
+                .into_owned(),
+        )
+    })
+}
+
+fn bundles() -> &'static [FluentBundle<FluentResource>] {
+    BUNDLES.get_or_init(|| {
+        let language = *LANGUAGE.get_or_init(Language::from_env);
+        let mut languages = vec![language];
+        if language != Language::English {
+            languages.push(Language::English);
+        }
+        languages
+            .into_iter()
+            .map(|language| {
+                let (tag, source) = language.messages();
+                let tag: LanguageIdentifier = tag.parse().expect("invalid language tag");
+                let resource = FluentResource::try_new(source.to_string())
+                    .expect("invalid messages in locales/");
+                let mut bundle = FluentBundle::new_concurrent(vec![tag]);
+                // Keeps the output plain for terminals and logs
+                bundle.set_use_isolating(false);
+                bundle
+                    .add_resource(resource)
+                    .expect("duplicate messages in locales/");
+                bundle
+            })
+            .collect()
+    })
+}
+
+// The message of an error in the language. Reasons given by operations and
+// the system stay as they are
+pub fn error(e: &OperationError) -> String {
+    match e {
+        OperationError::InvalidOperation { input, suggestions } if suggestions.is_empty() => {
+            text("invalid-operation", &[("input", input)])
+        }
+        OperationError::InvalidOperation { input, suggestions } => {
+            let suggestions = suggestions.join(&format!(" {} ", text("or", &[])));
+            text(
+                "invalid-operation-suggestions",
+                &[("input", input), ("suggestions", &suggestions)],
+            )
+        }
+        OperationError::InvalidValue { value, reason } => text(
+            "failed-validation",
+            &[("value", &shown(value)), ("reason", reason)],
+        ),
+        OperationError::Timeout { limit } => text("timeout", &[("limit", &format!("{:?}", limit))]),
+        OperationError::Io {
+            path: Some(path),
+            source,
+        } => text(
+            "read-failed",
+            &[("path", &path.display()), ("reason", source)],
+        ),
+        OperationError::Plugin { path, reason } => text(
+            "plugin-failed",
+            &[("path", &path.display()), ("reason", reason)],
+        ),
+        OperationError::TooLarge { limit } => text("too-large", &[("limit", limit)]),
+        OperationError::TooManyRequests { reason } => {
+            text("too-many-requests", &[("reason", reason)])
+        }
+        e => e.to_string(),
+    }
+}
+
+// The description of an operation in the language, its own one without a translation
+pub fn describe(operation: &dyn TextOperation) -> String {
+    message(
+        &format!("operation-{}", operation.name()),
+        &FluentArgs::new(),
+    )
+    .unwrap_or_else(|| operation.describe().to_string())
+}
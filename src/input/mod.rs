@@ -0,0 +1,176 @@
+use std::fs::File;
+use std::io::{self, Cursor, Read};
+use std::path::PathBuf;
+
+#[cfg(feature = "compression")]
+use std::io::{BufRead, BufReader};
+
+#[cfg(feature = "compression")]
+use flate2::read::MultiGzDecoder;
+use memmap2::Mmap;
+
+use crate::cancel::{check_input_size, CheckedReader};
+use crate::Error;
+
+mod policy;
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "s3")]
+mod s3;
+
+#[cfg(feature = "compression")]
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+#[cfg(feature = "compression")]
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+pub(crate) use policy::readable;
+#[cfg(feature = "markdown")]
+pub(crate) use policy::writable;
+pub use policy::{input_policy, set_input_policy, InputPolicy};
+
+// Where an operation reads its input from, given as the text of `--file`
+// and the file arguments of the CSV operations
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputSource {
+    Inline(String),
+    Stdin,
+    File(PathBuf),
+    // `http://` and `https://` URLs, fetched with a time and size limit
+    #[cfg(feature = "remote")]
+    Url(String),
+    #[cfg(feature = "s3")]
+    S3 {
+        bucket: String,
+        key: String,
+    },
+}
+
+impl InputSource {
+    // Inline text, `-` for stdin, a URL or a file path
+    pub fn parse(source: &str, inline: bool) -> InputSource {
+        if inline {
+            // Literal "\n" separates lines so inline input fits on a single line
+            return InputSource::Inline(source.replace("\\n", "\n"));
+        }
+        if source == "-" {
+            return InputSource::Stdin;
+        }
+        #[cfg(feature = "remote")]
+        if source.starts_with("http://") || source.starts_with("https://") {
+            return InputSource::Url(source.to_string());
+        }
+        #[cfg(feature = "s3")]
+        if let Some((bucket, key)) = source
+            .strip_prefix("s3://")
+            .and_then(|path| path.split_once('/'))
+        {
+            return InputSource::S3 {
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+            };
+        }
+        InputSource::File(PathBuf::from(source))
+    }
+
+    // Compressed input is unpacked while reading, whatever the source. What
+    // `set_input_policy` doesn't allow fails before anything is read
+    pub fn open(&self) -> Result<Box<dyn Read>, Error> {
+        match self {
+            InputSource::Inline(text) => Ok(Box::new(Cursor::new(text.clone()))),
+            InputSource::Stdin => {
+                policy::streams("reading stdin")?;
+                decompress(Box::new(io::stdin()))
+            }
+            InputSource::File(path) => {
+                let file = File::open(readable(path)?).map_err(|e| Error::io(path, e))?;
+                check_file_size(&file)?;
+                decompress(Box::new(file))
+            }
+            #[cfg(feature = "remote")]
+            InputSource::Url(url) => {
+                policy::streams("fetching URLs")?;
+                decompress(remote::fetch(url)?)
+            }
+            #[cfg(feature = "s3")]
+            InputSource::S3 { bucket, key } => {
+                policy::streams("fetching URLs")?;
+                decompress(s3::fetch(bucket, key)?)
+            }
+        }
+    }
+}
+
+// The source is either inline text, `-` for stdin, a URL or a file path
+pub fn open_source(source: &str, inline: bool) -> Result<Box<dyn Read>, Error> {
+    InputSource::parse(source, inline).open()
+}
+
+// Like `open_source` for a file, reading it through a memory map when possible
+pub fn open_mapped(source: &str) -> Result<Box<dyn Read>, Error> {
+    match map_file(source)? {
+        Some(map) => decompress(Box::new(Cursor::new(map))),
+        None => open_source(source, false),
+    }
+}
+
+// `None` when the file can't be mapped, e.g. stdin, URLs, pipes or some
+// network filesystems, so callers fall back to buffered reads
+pub fn map_file(source: &str) -> Result<Option<Mmap>, Error> {
+    let InputSource::File(path) = InputSource::parse(source, false) else {
+        return Ok(None);
+    };
+
+    let file = File::open(readable(&path)?).map_err(|e| Error::io(&path, e))?;
+    check_file_size(&file)?;
+    // SAFETY: the map is only read, changing the file while it is mapped can
+    // garble the output but not break memory safety of the reads themselves
+    match unsafe { Mmap::map(&file) } {
+        Ok(map) => Ok(Some(map)),
+        Err(_) => Ok(None),
+    }
+}
+
+// Files over the input limit are refused before reading any of them, a
+// compressed one holds at least as much as it takes on disk
+fn check_file_size(file: &File) -> Result<(), Error> {
+    match file.metadata() {
+        Ok(metadata) if metadata.is_file() => check_input_size(metadata.len()),
+        _ => Ok(()),
+    }
+}
+
+// Whether the data starts like a stream `decompress` unpacks
+#[cfg(feature = "compression")]
+pub fn is_compressed(data: &[u8]) -> bool {
+    data.starts_with(GZIP_MAGIC) || data.starts_with(ZSTD_MAGIC)
+}
+
+// Without the `compression` feature all input is read as it is
+#[cfg(not(feature = "compression"))]
+pub fn is_compressed(_data: &[u8]) -> bool {
+    false
+}
+
+// Gzip and zstd streams are recognized by their magic bytes rather than the
+// extension, so compressed stdin works too, and decompressed while reading
+#[cfg(feature = "compression")]
+fn decompress(data: Box<dyn Read>) -> Result<Box<dyn Read>, Error> {
+    let mut reader = BufReader::new(data);
+    let start = reader.fill_buf()?;
+
+    let data: Box<dyn Read> = if start.starts_with(GZIP_MAGIC) {
+        Box::new(MultiGzDecoder::new(reader))
+    } else if start.starts_with(ZSTD_MAGIC) {
+        Box::new(zstd::Decoder::with_buffer(reader)?)
+    } else {
+        Box::new(reader)
+    };
+    // Long reads stop once the time limit of `with_timeout` has passed
+    Ok(Box::new(CheckedReader::new(data)))
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(data: Box<dyn Read>) -> Result<Box<dyn Read>, Error> {
+    // Long reads stop once the time limit of `with_timeout` has passed
+    Ok(Box::new(CheckedReader::new(data)))
+}
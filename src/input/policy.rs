@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::Error;
+
+// What operations may read besides their inline input and which files they may
+// write, so a server doesn't hand its files, stdin or network to its clients
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum InputPolicy {
+    // Files, stdin and URLs, like on the command line
+    #[default]
+    Unrestricted,
+    // Only inline input, no file is read or written
+    InlineOnly,
+    // Files in the directory, which relative paths start from, but no stdin
+    // or URLs. The directory has to be canonical, like `fs::canonicalize` gives
+    Root(PathBuf),
+}
+
+static POLICY: RwLock<InputPolicy> = RwLock::new(InputPolicy::Unrestricted);
+
+// Sets what operations may read and write for the whole process
+pub fn set_input_policy(policy: InputPolicy) {
+    *POLICY.write().expect("input policy lock poisoned") = policy;
+}
+
+pub fn input_policy() -> InputPolicy {
+    POLICY.read().expect("input policy lock poisoned").clone()
+}
+
+// Where to read the file at `path` from, if it may be read
+pub(crate) fn readable(path: &Path) -> Result<PathBuf, Error> {
+    allowed(path, "reading")
+}
+
+// Where to write the file at `path` to, if it may be written
+#[cfg(feature = "markdown")]
+pub(crate) fn writable(path: &Path) -> Result<PathBuf, Error> {
+    allowed(path, "writing")
+}
+
+// Stdin and URLs are only read without restrictions
+pub(crate) fn streams(what: &str) -> Result<(), Error> {
+    match &*POLICY.read().expect("input policy lock poisoned") {
+        InputPolicy::Unrestricted => Ok(()),
+        _ => Err(Error::Forbidden {
+            reason: what.to_string(),
+        }),
+    }
+}
+
+fn allowed(path: &Path, access: &str) -> Result<PathBuf, Error> {
+    let root = match &*POLICY.read().expect("input policy lock poisoned") {
+        InputPolicy::Unrestricted => return Ok(path.to_path_buf()),
+        InputPolicy::InlineOnly => {
+            return Err(Error::Forbidden {
+                reason: format!("{} files, like {}", access, path.display()),
+            })
+        }
+        InputPolicy::Root(root) => root.clone(),
+    };
+    // Canonical, so neither `..` nor a symlink leads out of the directory
+    let resolved = root
+        .join(path)
+        .canonicalize()
+        .map_err(|e| Error::io(path, e))?;
+    if !resolved.starts_with(&root) {
+        return Err(Error::Forbidden {
+            reason: format!(
+                "{} files outside of the root directory, like {}",
+                access,
+                path.display()
+            ),
+        });
+    }
+    Ok(resolved)
+}
@@ -0,0 +1,43 @@
+use std::io::{self, Read};
+use std::time::Duration;
+
+use crate::Error;
+
+// How long fetching remote input may take before the operation fails, and
+// how much of it is read, so a wrong URL can't hang or exhaust the process
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+const FETCH_LIMIT: u64 = 1 << 30;
+
+pub fn fetch(url: &str) -> Result<Box<dyn Read>, Error> {
+    get(url, &[])
+}
+
+// A GET request with extra headers, whose body is read while the operation runs
+pub fn get(url: &str, headers: &[(&str, String)]) -> Result<Box<dyn Read>, Error> {
+    let agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .build()
+        .new_agent();
+    let mut request = agent.get(url);
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+    let response = request.call().map_err(|e| fetch_error(url, e))?;
+
+    let body = response
+        .into_body()
+        .into_with_config()
+        .limit(FETCH_LIMIT)
+        .reader();
+    Ok(Box::new(body))
+}
+
+fn fetch_error(url: &str, e: ureq::Error) -> Error {
+    let source = match e {
+        ureq::Error::StatusCode(status) => {
+            io::Error::other(format!("the server answered with status {}", status))
+        }
+        e => e.into_io(),
+    };
+    Error::io(url, source)
+}
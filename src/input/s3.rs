@@ -0,0 +1,161 @@
+use std::env;
+use std::fmt::Write;
+use std::io::Read;
+use std::time::SystemTime;
+
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::{Digest, Sha256};
+
+use super::remote;
+use crate::{Error, UtcTime};
+
+// The payload of a GET is empty, so it's left out of the signature
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+// Fetches `s3://bucket/key` with the credentials of the AWS environment
+// variables, signed with Signature Version 4. Without credentials the
+// request is sent unsigned, which works for public buckets
+pub fn fetch(bucket: &str, key: &str) -> Result<Box<dyn Read>, Error> {
+    let region = env::var("AWS_REGION")
+        .or_else(|_| env::var("AWS_DEFAULT_REGION"))
+        .unwrap_or_else(|_| "us-east-1".to_string());
+    let key = encode_key(key);
+    // Other S3 compatible stores take the bucket in the path
+    let (url, host, path) = match env::var("AWS_ENDPOINT_URL") {
+        Ok(endpoint) if !endpoint.is_empty() => {
+            let endpoint = endpoint.trim_end_matches('/');
+            let host = endpoint
+                .split_once("://")
+                .map_or(endpoint, |(_, host)| host);
+            let path = format!("/{}/{}", bucket, key);
+            (format!("{}{}", endpoint, path), host.to_string(), path)
+        }
+        _ => {
+            let host = format!("{}.s3.{}.amazonaws.com", bucket, region);
+            let path = format!("/{}", key);
+            (format!("https://{}{}", host, path), host, path)
+        }
+    };
+    get(&url, &host, &path, &region)
+}
+
+fn get(url: &str, host: &str, path: &str, region: &str) -> Result<Box<dyn Read>, Error> {
+    let (Ok(access_key), Ok(secret_key)) = (
+        env::var("AWS_ACCESS_KEY_ID"),
+        env::var("AWS_SECRET_ACCESS_KEY"),
+    ) else {
+        return remote::get(url, &[]);
+    };
+    let credentials = Credentials {
+        access_key,
+        secret_key,
+        session_token: env::var("AWS_SESSION_TOKEN").ok(),
+    };
+    let headers = credentials.sign(host, path, region, SystemTime::now());
+    remote::get(url, &headers)
+}
+
+struct Credentials {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+}
+
+impl Credentials {
+    // The headers that authenticate a GET of `path` at the time
+    fn sign(
+        &self,
+        host: &str,
+        path: &str,
+        region: &str,
+        now: SystemTime,
+    ) -> Vec<(&'static str, String)> {
+        let timestamp = timestamp(now);
+        let date = &timestamp[..8];
+
+        let mut headers = vec![
+            ("host", host.to_string()),
+            ("x-amz-content-sha256", UNSIGNED_PAYLOAD.to_string()),
+            ("x-amz-date", timestamp.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let canonical_request = format!(
+            "GET\n{}\n\n{}\n{}\n{}",
+            path, canonical_headers, signed_headers, UNSIGNED_PAYLOAD
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = [date, region, "s3", "aws4_request"].iter().fold(
+            format!("AWS4{}", self.secret_key).into_bytes(),
+            |key, part| hmac(&key, part.as_bytes()),
+        );
+        let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+
+        // ureq sets the host itself
+        headers.remove(0);
+        headers.push((
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key, scope, signed_headers, signature
+            ),
+        ));
+        headers
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+// Keys keep their slashes, everything else but unreserved characters is
+// percent-encoded like S3 expects in the signed path
+fn encode_key(key: &str) -> String {
+    let mut encoded = String::new();
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+    }
+    encoded
+}
+
+// `YYYYMMDDTHHMMSSZ` in UTC
+fn timestamp(time: SystemTime) -> String {
+    let time = UtcTime::new(time);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        time.year, time.month, time.day, time.hour, time.minute, time.second
+    )
+}
@@ -0,0 +1,42 @@
+// The JavaScript API of the built-in operations, for the browser build
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{OperationOutput, OperationRegistry, Pipeline};
+
+#[derive(Serialize)]
+struct OperationInfo<'a> {
+    name: &'a str,
+    aliases: &'a [&'a str],
+    description: &'a str,
+    // Whether the input is text to transform rather than options and file names
+    text: bool,
+}
+
+// `transform("slugify", "Hello World")`, or a pipeline like
+// `transform("lowercase | slugify", ...)`, throwing an `Error` with the
+// message when the operation fails
+#[wasm_bindgen]
+pub fn transform(op: &str, text: &str) -> Result<String, JsError> {
+    let registry = OperationRegistry::builtin();
+    if op.contains('|') {
+        return Ok(op.parse::<Pipeline>()?.execute_with(registry, text)?);
+    }
+    Ok(registry.run(op, text).map(OperationOutput::into_string)?)
+}
+
+// The operations as `{name, aliases, description, text}` objects
+#[wasm_bindgen(js_name = listOperations)]
+pub fn list_operations() -> Result<JsValue, JsError> {
+    let operations = OperationRegistry::builtin().operations();
+    let operations: Vec<OperationInfo> = operations
+        .iter()
+        .map(|operation| OperationInfo {
+            name: operation.name(),
+            aliases: operation.aliases(),
+            description: operation.describe(),
+            text: operation.is_text_operation(),
+        })
+        .collect();
+    Ok(serde_wasm_bindgen::to_value(&operations)?)
+}
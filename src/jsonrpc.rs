@@ -0,0 +1,184 @@
+use std::io::{self, BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use rust_text_transformer::{
+    redacted, set_max_input_bytes, Error, OperationOutput, OperationRegistry,
+};
+
+use crate::cli::{CliOptions, SERVER_MAX_INPUT_BYTES};
+use crate::request::{OperationInfo, TransformRequest};
+use crate::{limited, shutdown};
+
+// Error codes of the JSON-RPC 2.0 specification
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// An operation that failed, `data.kind` tells how
+const OPERATION_FAILED: i64 = -32000;
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    // Notifications have no id and get no response
+    id: Option<Value>,
+}
+
+// `transformBatch` takes the requests as the params or as `{"requests": [...]}`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BatchParams {
+    List(Vec<TransformRequest>),
+    Named { requests: Vec<TransformRequest> },
+}
+
+// Answers one request or batch per line of stdin with one line on stdout,
+// until stdin ends or Ctrl-C
+pub fn run(registry: &OperationRegistry, options: CliOptions) -> io::Result<()> {
+    set_max_input_bytes(Some(
+        options.max_input_bytes.unwrap_or(SERVER_MAX_INPUT_BYTES),
+    ));
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            if let Some(response) = handle_line(registry, options, &line) {
+                writeln!(stdout, "{}", response)?;
+                stdout.flush()?;
+            }
+        }
+        if shutdown::requested() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn handle_line(registry: &OperationRegistry, options: CliOptions, line: &str) -> Option<Value> {
+    let message = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(response(Value::Null, Err(rpc_error(PARSE_ERROR, e)))),
+    };
+
+    match message {
+        Value::Array(batch) if batch.is_empty() => Some(response(
+            Value::Null,
+            Err(rpc_error(INVALID_REQUEST, "Empty batch")),
+        )),
+        Value::Array(batch) => {
+            let responses: Vec<Value> = batch
+                .into_iter()
+                .filter_map(|message| handle(registry, options, message))
+                .collect();
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        message => handle(registry, options, message),
+    }
+}
+
+fn handle(registry: &OperationRegistry, options: CliOptions, message: Value) -> Option<Value> {
+    let request: Request = match serde_json::from_value(message) {
+        Ok(request) => request,
+        Err(e) => return Some(response(Value::Null, Err(rpc_error(INVALID_REQUEST, e)))),
+    };
+    if request.jsonrpc != "2.0" {
+        let error = rpc_error(INVALID_REQUEST, "jsonrpc must be \"2.0\"");
+        return Some(response(request.id.unwrap_or_default(), Err(error)));
+    }
+
+    let result = call(registry, options, &request.method, request.params);
+    Some(response(request.id?, result))
+}
+
+fn call(
+    registry: &OperationRegistry,
+    options: CliOptions,
+    method: &str,
+    params: Value,
+) -> Result<Value, Value> {
+    match method {
+        "transform" => {
+            let request: TransformRequest = parse_params(params)?;
+            transform(registry, options, &request)
+                .map(|output| json!(output))
+                .map_err(|e| operation_error(&e))
+        }
+        "transformBatch" => {
+            let requests = match parse_params(params)? {
+                BatchParams::List(requests) | BatchParams::Named { requests } => requests,
+            };
+            let results = requests
+                .iter()
+                .map(|request| match transform(registry, options, request) {
+                    Ok(output) => json!({ "result": output }),
+                    Err(e) => json!({ "error": operation_error(&e) }),
+                })
+                .collect();
+            Ok(Value::Array(results))
+        }
+        "listOperations" => Ok(json!(OperationInfo::list(registry))),
+        _ => Err(rpc_error(
+            METHOD_NOT_FOUND,
+            format!("Method not found: {}", method),
+        )),
+    }
+}
+
+fn transform(
+    registry: &OperationRegistry,
+    options: CliOptions,
+    request: &TransformRequest,
+) -> Result<OperationOutput, Error> {
+    let command = request.command(registry)?;
+    limited(options.timeout, || registry.execute(&command))
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, Value> {
+    serde_json::from_value(params).map_err(|e| rpc_error(INVALID_PARAMS, e))
+}
+
+fn response(id: Value, result: Result<Value, Value>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(error) => json!({ "jsonrpc": "2.0", "error": error, "id": id }),
+    }
+}
+
+fn rpc_error(code: i64, message: impl ToString) -> Value {
+    json!({ "code": code, "message": message.to_string() })
+}
+
+fn operation_error(e: &Error) -> Value {
+    let mut data = match e {
+        Error::InvalidOperation { suggestions, .. } => {
+            json!({ "kind": "invalid_operation", "suggestions": suggestions })
+        }
+        Error::Io { .. } => json!({ "kind": "io" }),
+        Error::InvalidInput { .. } => json!({ "kind": "invalid_input" }),
+        Error::InvalidValue { value, .. } => {
+            json!({ "kind": "invalid_value", "value": redacted(value) })
+        }
+        Error::Timeout { .. } => json!({ "kind": "timeout" }),
+        Error::TooLarge { limit } => json!({ "kind": "too_large", "limit": limit }),
+        Error::TooManyRequests { .. } => json!({ "kind": "too_many_requests" }),
+        Error::Unauthorized => json!({ "kind": "unauthorized" }),
+        Error::Forbidden { .. } => json!({ "kind": "forbidden" }),
+        Error::Plugin { .. } => json!({ "kind": "plugin" }),
+        #[cfg(feature = "csv")]
+        Error::Csv { line, .. } => json!({ "kind": "csv", "line": line }),
+        #[cfg(any(feature = "csv", feature = "markdown"))]
+        Error::Json(_) => json!({ "kind": "json" }),
+        #[cfg(feature = "parquet")]
+        Error::Parquet(_) => json!({ "kind": "parquet" }),
+    };
+    // The stable code of `Error::code`, unlike `kind` the same across versions
+    data["code"] = json!(e.code());
+    data["class"] = json!(e.class());
+    json!({ "code": OPERATION_FAILED, "message": e.to_string(), "data": data })
+}
@@ -0,0 +1,68 @@
+pub mod args;
+#[cfg(feature = "async")]
+mod async_io;
+mod bytes;
+mod cancel;
+mod config;
+mod confirm;
+#[cfg(feature = "csv")]
+mod csv_ops;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod input;
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "markdown")]
+mod markdown;
+mod memory;
+mod middleware;
+mod modifier;
+mod operation;
+mod output;
+mod pipeline;
+mod plugin;
+mod redaction;
+mod registry;
+#[cfg(feature = "scripting")]
+mod script;
+mod streaming;
+mod text_ops;
+mod time;
+mod tuning;
+#[cfg(feature = "xml")]
+mod xml;
+
+#[cfg(feature = "async")]
+pub use async_io::{process_lines_async, process_operation_async};
+pub use cancel::{check_timeout, max_input_bytes, set_max_input_bytes, with_timeout};
+pub use config::config_dir;
+pub use confirm::{confirmed, set_confirmation, Confirmation};
+pub use error::Error;
+pub use input::{input_policy, set_input_policy, InputPolicy, InputSource};
+pub use memory::{memory_budget, set_memory_budget};
+pub use middleware::{Command, Middleware, Next};
+pub use modifier::{Op, TextModifier, TextModifierBuilder};
+pub use operation::{
+    process_stream, process_stream_chunked, process_stream_with, run_operation, write_operation,
+    Chunking, TextOperation,
+};
+pub use output::OperationOutput;
+pub use pipeline::{Pipeline, Step};
+pub use plugin::{load_plugins, plugin_dir};
+#[cfg(feature = "native-plugins")]
+pub use plugin::{PluginBuffer, PluginDeclaration, PLUGIN_ABI_VERSION, PLUGIN_SYMBOL};
+pub use redaction::{redacted, redaction, set_redaction, shown, Redaction};
+pub use registry::OperationRegistry;
+pub use rust_text_transformer_macros::text_operation;
+pub use time::UtcTime;
+
+// Lets code generated by `text_operation` name this crate from inside it too
+extern crate self as rust_text_transformer;
+
+// Used by code generated by `text_operation`, not part of the API
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::registry::DeclaredOperation;
+    pub use inventory;
+}
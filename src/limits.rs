@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use rust_text_transformer::Error;
+
+// Buckets kept before the full ones, of clients that went quiet, are dropped
+const TRACKED_CLIENTS: usize = 1024;
+
+// What `serve` lets a request through with, checked before the operation runs
+// and shared by all listeners
+pub struct Limits {
+    max_input_bytes: Option<usize>,
+    // Requests per second of one client, who may use a second's worth at once
+    rate: Option<f64>,
+    running: Option<(Arc<Semaphore>, usize)>,
+    // Clients are keyed by IP address, or by uid on the Unix socket
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+// Held while the admitted request runs
+pub struct Admitted {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Limits {
+    pub fn new(
+        max_input_bytes: Option<usize>,
+        rate: Option<usize>,
+        max_concurrent: Option<usize>,
+    ) -> Limits {
+        Limits {
+            max_input_bytes,
+            rate: rate.map(|rate| rate as f64),
+            running: max_concurrent.map(|limit| (Arc::new(Semaphore::new(limit)), limit)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn max_input_bytes(&self) -> Option<usize> {
+        self.max_input_bytes
+    }
+
+    pub fn admit(&self, client: &str, input_bytes: usize) -> Result<Admitted, Error> {
+        if let Some(limit) = self.max_input_bytes.filter(|&limit| input_bytes > limit) {
+            return Err(Error::TooLarge { limit });
+        }
+        if let Some(rate) = self.rate {
+            self.take_token(client, rate)?;
+        }
+        match &self.running {
+            Some((running, limit)) => match Arc::clone(running).try_acquire_owned() {
+                Ok(permit) => Ok(Admitted {
+                    _permit: Some(permit),
+                }),
+                Err(_) => Err(Error::TooManyRequests {
+                    reason: format!("{} requests are running", limit),
+                }),
+            },
+            None => Ok(Admitted { _permit: None }),
+        }
+    }
+
+    fn take_token(&self, client: &str, rate: f64) -> Result<(), Error> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= TRACKED_CLIENTS && !buckets.contains_key(client) {
+            buckets.retain(|_, bucket| bucket.refilled(now, rate) < rate);
+        }
+
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: rate,
+            updated: now,
+        });
+        bucket.tokens = bucket.refilled(now, rate);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            return Err(Error::TooManyRequests {
+                reason: format!("over the limit of {} per second", rate),
+            });
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+impl Bucket {
+    fn refilled(&self, now: Instant, rate: f64) -> f64 {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        (self.tokens + elapsed * rate).min(rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clients_get_a_second_of_requests_at_once() {
+        let limits = Limits::new(None, Some(2), None);
+        assert!(limits.admit("a", 0).is_ok());
+        assert!(limits.admit("a", 0).is_ok());
+        assert!(matches!(
+            limits.admit("a", 0),
+            Err(Error::TooManyRequests { .. })
+        ));
+        // Each client has its own bucket
+        assert!(limits.admit("b", 0).is_ok());
+    }
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let bucket = Bucket {
+            tokens: 0.0,
+            updated: Instant::now(),
+        };
+        let later = bucket.updated + std::time::Duration::from_millis(250);
+        assert_eq!(bucket.refilled(later, 4.0), 1.0);
+        // Never over a second's worth
+        let much_later = bucket.updated + std::time::Duration::from_secs(60);
+        assert_eq!(bucket.refilled(much_later, 4.0), 4.0);
+    }
+
+    #[test]
+    fn running_requests_hold_their_slot() {
+        let limits = Limits::new(None, None, Some(1));
+        let admitted = limits.admit("a", 0).unwrap();
+        assert!(limits.admit("b", 0).is_err());
+        drop(admitted);
+        assert!(limits.admit("b", 0).is_ok());
+    }
+
+    #[test]
+    fn large_inputs_are_refused() {
+        let limits = Limits::new(Some(4), Some(1), None);
+        assert!(matches!(
+            limits.admit("a", 5),
+            Err(Error::TooLarge { limit: 4 })
+        ));
+        // Refused before they use up the rate
+        assert!(limits.admit("a", 4).is_ok());
+    }
+}
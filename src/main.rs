@@ -1,256 +1,782 @@
-use convert_case::{Case, Casing};
-use slug::slugify;
-
+mod audit;
+#[cfg(feature = "server")]
+mod auth;
+mod bench;
+mod cache;
+mod checksum;
+mod cli;
+#[cfg(all(unix, feature = "server"))]
+mod daemon;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod hooks;
+#[cfg(feature = "http")]
+mod http;
+mod i18n;
+#[cfg(feature = "jsonrpc")]
+mod jsonrpc;
+#[cfg(feature = "server")]
+mod limits;
+mod prompt;
+mod repl;
+#[cfg(any(feature = "http", feature = "jsonrpc"))]
+mod request;
+#[cfg(feature = "server")]
+mod serve;
+mod settings;
+mod shutdown;
+mod stats;
+
+use std::collections::BTreeMap;
+use std::env;
 use std::error::Error;
-use std::fmt;
-use std::fs::File;
-use std::io;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::process;
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
+
+use flume::{Receiver, RecvTimeoutError, Sender, TrySendError};
+
+use cache::Cache;
+use cli::{Backpressure, CliOptions};
+use repl::Repl;
+use settings::Settings;
+
+use rust_text_transformer::{
+    args, load_plugins, plugin_dir, process_stream_with, set_max_input_bytes, set_memory_budget,
+    set_redaction, with_timeout, write_operation, Chunking, Command as OperationCommand,
+    Error as OperationError, OperationOutput, OperationRegistry, Pipeline, TextOperation,
+};
+
+// Every operation of the registry, `#[text_operation]` ones included, with the
+// translated description or `describe()`
+fn print_available_operations(registry: &OperationRegistry) {
+    eprintln!("{}", i18n::text("available-operations", &[]));
+    for operation in registry.operations() {
+        eprintln!(
+            "  {:<18} {}",
+            operation.name(),
+            i18n::describe(operation.as_ref())
+        );
+    }
+}
 
-use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
-
-use csv::{ReaderBuilder, StringRecord};
-
-use prettytable::{Cell, Row, Table};
-
-use flume::{Receiver, Sender};
+struct Command {
+    // Position in the input, results are printed in this order
+    sequence: u64,
+    operation: Arc<dyn TextOperation>,
+    input: String,
+}
 
-#[derive(Debug)]
-struct OperationError(String);
+// What a worker produced for one command
+struct Output {
+    sequence: u64,
+    text: Vec<u8>,
+    result: Result<OperationOutput, OperationError>,
+}
 
-impl fmt::Display for OperationError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Operation error: {}", self.0)
+impl Output {
+    fn print(&self, out: &mut impl Write, options: CliOptions) -> io::Result<()> {
+        out.write_all(&self.text)?;
+        out.flush()?;
+        match &self.result {
+            Ok(output) => report(output, options.timing),
+            Err(e) => print_error("error-processing", e, options.porcelain),
+        }
+        Ok(())
     }
 }
 
-impl Error for OperationError {}
+// The message `context` for people, in their language, and with `--porcelain`
+// a line in English with the stable code of the error that scripts can split on tabs
+fn print_error(context: &str, e: &OperationError, porcelain: bool) {
+    hooks::failed(e);
+    if porcelain {
+        eprintln!("error\t{}\t{}\t{}", e.code(), e.class(), e);
+    } else {
+        eprintln!("{}", i18n::text(context, &[("message", &i18n::error(e))]));
+    }
+}
 
-// Struct for CSV handling with Display trait
-struct CsvTable {
-    headers: StringRecord,
-    records: Vec<StringRecord>,
+// Warnings of a finished command, and with `--timing` how long it took
+fn report(output: &OperationOutput, timing: bool) {
+    for warning in &output.warnings {
+        eprintln!("{}", warning);
+    }
+    if timing {
+        eprintln!(
+            "({} took {:.2?}, {} bytes in, {} bytes out)",
+            output.operation, output.duration, output.bytes_in, output.bytes_out
+        );
+    }
 }
 
-impl fmt::Display for CsvTable {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut table = Table::new();
+// Where workers send the results of commands
+#[derive(Clone)]
+enum Sink {
+    // A single worker writes straight to stdout
+    Stdout,
+    // Each result is printed whole as soon as it is done
+    Unordered,
+    // Results go through the output thread, which puts them back in order
+    Ordered(Sender<Output>),
+}
 
-        let mut header_cells = Vec::new();
-        for header in &self.headers {
-            header_cells.push(Cell::new(header).style_spec("bcB"));
+fn input_thread(
+    tx: Sender<Command>,
+    registry: &OperationRegistry,
+    cache: Option<&Cache>,
+    options: CliOptions,
+) -> Result<(), Box<dyn Error>> {
+    let mut repl = Repl::new(registry, options.timeout, cache);
+    prompt::reads_stdin();
+    let mut sequence = 0;
+    let mut line = 0;
+    loop {
+        line += 1;
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 || shutdown::requested() {
+            break;
+        }
+        if prompt::take_answer(&input) {
+            continue;
         }
-        table.add_row(Row::new(header_cells));
 
-        for record in &self.records {
-            let mut row_cells = Vec::new();
-            for field in record {
-                row_cells.push(Cell::new(field));
+        // `:play` runs the commands of a macro as if they were typed
+        let (commands, played) = match repl.command(&input, false) {
+            Some(Ok(commands)) => (commands, true),
+            Some(Err(e)) => {
+                print_error("error", &OperationError::invalid(e), options.porcelain);
+                continue;
+            }
+            None => (vec![input], false),
+        };
+        for input in commands {
+            let result = match played.then(|| repl.command(&input, true)).flatten() {
+                Some(result) => result.map(|_| None),
+                None => Ok(Some(repl.expand(&input))),
+            };
+            let command = match result {
+                Ok(Some(command)) => command,
+                Ok(None) => continue,
+                Err(e) => {
+                    print_error("error", &OperationError::invalid(e), options.porcelain);
+                    continue;
+                }
+            };
+            match submit(&tx, registry, options, line, sequence, &command) {
+                Ok(true) => {
+                    repl.ran(&input);
+                    sequence += 1;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!("{}", i18n::text("error-sending", &[("message", &e)]));
+                    prompt::stdin_closed();
+                    return Ok(());
+                }
             }
-            table.add_row(Row::new(row_cells));
         }
-
-        write!(f, "{}", table)
     }
+    prompt::stdin_closed();
+    Ok(())
 }
 
-#[derive(Debug, EnumIter)]
-enum Operation {
-    CamelCase,
-    Csv,
-    LowerCase,
-    NoSpaces,
-    Slugify,
-    SnakeCase,
-    UpperCase,
-}
+// Sends the command of a line to the workers. False when it was invalid or
+// dropped, an error once the workers are gone
+fn submit(
+    tx: &Sender<Command>,
+    registry: &OperationRegistry,
+    options: CliOptions,
+    line: u64,
+    sequence: u64,
+    input: &str,
+) -> Result<bool, String> {
+    // Split input into operation and data
+    let parts: Vec<&str> = input.split_whitespace().collect();
+    if parts.is_empty() {
+        return Ok(false);
+    }
+    hooks::ran();
 
-impl Operation {
-    fn from_str(s: &str) -> Result<Operation, Box<dyn Error>> {
-        match s.to_lowercase().as_str() {
-            "camelcase" => Ok(Operation::CamelCase),
-            "csv" => Ok(Operation::Csv),
-            "lowercase" => Ok(Operation::LowerCase),
-            "no-spaces" => Ok(Operation::NoSpaces),
-            "slugify" => Ok(Operation::Slugify),
-            "snakecase" => Ok(Operation::SnakeCase),
-            "uppercase" => Ok(Operation::UpperCase),
-            _ => Err(Box::new(OperationError(format!(
-                "Invalid operation: {}",
-                s
-            )))),
+    if parts.len() < 2 {
+        let e = OperationError::invalid(i18n::text("expected-format", &[]));
+        print_error("error", &e, options.porcelain);
+        if !options.porcelain {
+            print_available_operations(registry);
         }
+        return Ok(false);
     }
 
-    fn to_str(&self) -> String {
-        match self {
-            Operation::CamelCase => String::from("camelcase"),
-            Operation::Csv => String::from("csv"),
-            Operation::LowerCase => String::from("lowercase"),
-            Operation::NoSpaces => String::from("no-spaces"),
-            Operation::Slugify => String::from("slugify"),
-            Operation::SnakeCase => String::from("snakecase"),
-            Operation::UpperCase => String::from("uppercase"),
+    match registry.find(parts[0].trim()) {
+        Ok(operation) => {
+            // Consider everything after first space to be input data
+            let input = parts[1..].join(" ");
+            let command = Command {
+                sequence,
+                operation,
+                input,
+            };
+            match options.backpressure {
+                Backpressure::Block => tx.send(command).map_err(|e| e.to_string())?,
+                Backpressure::Drop => match tx.try_send(command) {
+                    Err(TrySendError::Full(_)) => {
+                        eprintln!("{}", i18n::text("dropped-command", &[("line", &line)]));
+                        return Ok(false);
+                    }
+                    sent => sent.map_err(|e| e.to_string())?,
+                },
+            }
+            Ok(true)
+        }
+        Err(e) => {
+            print_error("error", &e, options.porcelain);
+            if !options.porcelain {
+                print_available_operations(registry);
+            }
+            Ok(false)
         }
     }
+}
 
-    fn print_available_operations() {
-        eprintln!("Available operations are:");
-        for operation in Operation::iter() {
-            eprintln!("  {}", operation.to_str());
+// How often idle workers check for a shutdown request
+const SHUTDOWN_POLL: Duration = Duration::from_millis(100);
+
+fn processing_thread(
+    rx: Receiver<Command>,
+    registry: &OperationRegistry,
+    cache: Option<&Cache>,
+    sink: Sink,
+    options: CliOptions,
+) -> Result<(), Box<dyn Error>> {
+    // A single worker keeps stdout locked and writes through one buffer,
+    // flushed after every command
+    let mut stdout = matches!(sink, Sink::Stdout).then(|| BufWriter::new(io::stdout().lock()));
+    loop {
+        let command = match rx.recv_timeout(SHUTDOWN_POLL) {
+            Ok(command) => command,
+            // Commands already queued still run after a shutdown request
+            Err(RecvTimeoutError::Timeout) if shutdown::requested() => break,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        if let Some(stdout) = &mut stdout {
+            writeln!(stdout, "Selected operation: {}", command.operation.name())?;
+            let result = limited(options.timeout, || {
+                execute_audited(registry, cache, &command.operation, &command.input, stdout)
+            });
+            stdout.flush()?;
+            match result {
+                Ok(output) => report(&output, options.timing),
+                Err(e) => print_error("error-processing", &e, options.porcelain),
+            }
+            continue;
+        }
+
+        // Buffered so the output of commands running at the same time doesn't interleave
+        let mut text = Vec::new();
+        writeln!(text, "Selected operation: {}", command.operation.name())?;
+        let result = limited(options.timeout, || {
+            execute_audited(
+                registry,
+                cache,
+                &command.operation,
+                &command.input,
+                &mut text,
+            )
+        });
+        let output = Output {
+            sequence: command.sequence,
+            text,
+            result,
+        };
+
+        match &sink {
+            Sink::Ordered(tx) => tx.send(output)?,
+            _ => output.print(&mut io::stdout().lock(), options)?,
         }
     }
+    Ok(())
 }
 
-fn process_camel_case(input: &str) -> Result<String, Box<dyn Error>> {
-    Ok(input.to_case(Case::Camel))
+// Like `execute_cached`, counted in the usage stats and with a line in the
+// audit log when there is one
+fn execute_audited(
+    registry: &OperationRegistry,
+    cache: Option<&Cache>,
+    operation: &Arc<dyn TextOperation>,
+    input: &str,
+    out: &mut dyn Write,
+) -> Result<OperationOutput, OperationError> {
+    let start = Instant::now();
+    if !audit::enabled() {
+        let result = execute_cached(registry, cache, operation, input, out);
+        stats::record(operation.name(), start.elapsed());
+        return result;
+    }
+    let mut hashing = checksum::Hashing::new(out);
+    let result = execute_cached(registry, cache, operation, input, &mut hashing);
+    audit::record(
+        operation.name(),
+        &checksum::digest(input),
+        &hashing.finish(),
+        start.elapsed(),
+        result.as_ref().map(|_| ()),
+    );
+    stats::record(operation.name(), start.elapsed());
+    result
 }
 
-fn process_csv(file_path: &str) -> Result<String, Box<dyn Error>> {
-    let file = File::open(file_path)
-        .map_err(|e| OperationError(format!("Failed to open file '{}': {}", file_path, e)))?;
-
-    let mut reader = ReaderBuilder::new()
-        .trim(csv::Trim::All)
-        .flexible(true)
-        .from_reader(file);
-
-    let headers = reader.headers()?.clone();
-    if headers.is_empty() {
-        return Err(Box::new(OperationError("CSV has no headers".to_string())));
+// Like `execute`, answering commands run before from the cache. Middleware
+// has to see every command, so nothing is cached when there is any
+fn execute_cached(
+    registry: &OperationRegistry,
+    cache: Option<&Cache>,
+    operation: &Arc<dyn TextOperation>,
+    input: &str,
+    out: &mut dyn Write,
+) -> Result<OperationOutput, OperationError> {
+    let Some(cache) = cache.filter(|_| !registry.has_middleware()) else {
+        return execute(registry, operation, input, out);
+    };
+    let start = Instant::now();
+    if let Some((text, output)) = cache.get(operation.as_ref(), input) {
+        out.write_all(&text)?;
+        return Ok(OperationOutput {
+            duration: start.elapsed(),
+            ..output
+        });
+    }
+    let mut recorder = cache::Recorder::new(out);
+    let output = execute(registry, operation, input, &mut recorder)?;
+    if let Some(text) = recorder.into_copy() {
+        cache.insert(operation.as_ref(), input, text, &output);
     }
+    Ok(output)
+}
 
-    let records: Result<Vec<StringRecord>, _> = reader.records().collect();
-    let records = records?;
-    if records.is_empty() {
-        return Err(Box::new(OperationError("CSV has no data rows".to_string())));
+// Streams the output, unless the registry has middleware that needs it whole
+fn execute(
+    registry: &OperationRegistry,
+    operation: &Arc<dyn TextOperation>,
+    input: &str,
+    out: &mut dyn Write,
+) -> Result<OperationOutput, OperationError> {
+    if !registry.has_middleware() {
+        return registry.scope(|| write_operation(operation.as_ref(), input, out));
     }
 
-    let csv_table = CsvTable { headers, records };
-    Ok(format!("{}", csv_table))
+    let output = registry.execute(&OperationCommand {
+        operation: Arc::clone(operation),
+        input: input.to_string(),
+    })?;
+    writeln!(out, "{}", output.text)?;
+    Ok(output)
 }
 
-fn process_lower_case(input: &str) -> Result<String, Box<dyn Error>> {
-    Ok(input.to_lowercase())
+// `--timeout` stops an operation at the next row or block of input it reads
+fn limited<T>(timeout: Option<Duration>, f: impl FnOnce() -> T) -> T {
+    match timeout {
+        Some(timeout) => with_timeout(timeout, f),
+        None => f(),
+    }
 }
 
-fn process_no_spaces(input: &str) -> Result<String, Box<dyn Error>> {
-    Ok(input.replace(" ", ""))
+// Holds back results that finish early until all earlier ones are printed
+fn output_thread(rx: Receiver<Output>, options: CliOptions) -> io::Result<()> {
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    while let Ok(output) = rx.recv() {
+        pending.insert(output.sequence, output);
+        while let Some(output) = pending.remove(&next) {
+            output.print(&mut stdout, options)?;
+            next += 1;
+        }
+    }
+    Ok(())
 }
 
-fn process_slugify(input: &str) -> Result<String, Box<dyn Error>> {
-    Ok(slugify(input))
-}
+// Runs a single command given on the command line instead of reading from stdin
+fn run_once(args: &[String], registry: &OperationRegistry, options: CliOptions) {
+    // `'lowercase | slugify' Hello World` chains operations
+    hooks::ran();
+    if args[0].contains('|') {
+        if options.verify_sha256.is_some() {
+            let e = OperationError::invalid("--verify-sha256 needs a single operation");
+            print_error("error", &e, options.porcelain);
+            exit_with_hooks(2);
+        }
+        let start = Instant::now();
+        let input = args::join(&args[1..]);
+        let mut steps = Vec::new();
+        let result = args[0].parse::<Pipeline>().and_then(|pipeline| {
+            steps = pipeline.steps().to_vec();
+            limited(options.timeout, || pipeline.execute_with(registry, &input))
+        });
+        let hash = result.as_ref().map_or_else(
+            |_| checksum::digest(""),
+            |output| checksum::digest(format!("{}\n", output)),
+        );
+        audit::record(
+            &args[0],
+            &checksum::digest(&input),
+            &hash,
+            start.elapsed(),
+            result.as_ref().map(|_| ()),
+        );
+        // Counted as the chain of operations, without their options
+        let operations = steps.iter().map(|step| {
+            registry
+                .find(&step.operation)
+                .map(|operation| operation.name().to_string())
+        });
+        if let Ok(operations) = operations.collect::<Result<Vec<_>, _>>() {
+            stats::record(&operations.join(" | "), start.elapsed());
+        }
+        stats::save();
+        match result {
+            Ok(output) => {
+                println!("{}", output);
+                if options.emit_checksum {
+                    print_checksum(&hash, options.porcelain);
+                }
+            }
+            Err(e @ OperationError::InvalidOperation { .. }) => {
+                print_error("error", &e, options.porcelain);
+                if !options.porcelain {
+                    print_available_operations(registry);
+                }
+                exit_with_hooks(2);
+            }
+            Err(e) => {
+                print_error("error-processing", &e, options.porcelain);
+                exit_with_hooks(1);
+            }
+        }
+        return;
+    }
+
+    let operation = match registry.find(&args[0]) {
+        Ok(operation) => operation,
+        Err(e) => {
+            print_error("error", &e, options.porcelain);
+            if !options.porcelain {
+                print_available_operations(registry);
+            }
+            exit_with_hooks(2);
+        }
+    };
+
+    // The input is checked before anything is written
+    if let Some(expected) = &options.verify_sha256 {
+        let verified = checksum::input_file(operation.as_ref(), &args[1..])
+            .and_then(|path| checksum::verify(&path, expected));
+        if let Err(e) = verified {
+            print_error("error", &e, options.porcelain);
+            exit_with_hooks(1);
+        }
+    }
 
-fn process_snake_case(input: &str) -> Result<String, Box<dyn Error>> {
-    Ok(input.to_case(Case::Snake))
+    // Without input text, a text operation transforms stdin line by line
+    let start = Instant::now();
+    let mut input_hash = None;
+    let mut stdout = checksum::Hashing::new(BufWriter::new(io::stdout().lock()));
+    let result = limited(options.timeout, || {
+        registry.scope(|| {
+            if args.len() == 1 && operation.is_text_operation() {
+                let stdin = io::stdin().lock();
+                let mut chunking = Chunking {
+                    bytes: options.chunk_bytes,
+                    follow: options
+                        .follow
+                        .then_some(shutdown::requested as fn() -> bool),
+                    adaptive: options.adaptive,
+                    ..Chunking::default()
+                };
+                if let Some(lines) = options.chunk_lines {
+                    chunking.lines = lines;
+                } else if stdin.is_terminal() && !options.adaptive {
+                    chunking.lines = 1;
+                }
+                // The audit log has the hash of what was read
+                if !audit::enabled() {
+                    return process_stream_with(operation.as_ref(), stdin, &mut stdout, chunking)
+                        .map(|()| None);
+                }
+                let mut stdin = checksum::Hashing::new(stdin);
+                let result =
+                    process_stream_with(operation.as_ref(), &mut stdin, &mut stdout, chunking);
+                input_hash = Some(stdin.finish());
+                result.map(|()| None)
+            } else {
+                // Text is transformed as given, other operations parse quoted arguments
+                let input = if operation.is_text_operation() {
+                    args[1..].join(" ")
+                } else {
+                    args::join(&args[1..])
+                };
+                input_hash = Some(checksum::digest(&input));
+                let output = execute(registry, &operation, &input, &mut stdout)?;
+                stdout.flush()?;
+                Ok(Some(output))
+            }
+        })
+    });
+    // What was written before an error is still shown
+    let _ = stdout.flush();
+    let output_hash = stdout.finish();
+    audit::record(
+        operation.name(),
+        &input_hash.unwrap_or_else(|| checksum::digest("")),
+        &output_hash,
+        start.elapsed(),
+        result.as_ref().map(|_| ()),
+    );
+    stats::record(operation.name(), start.elapsed());
+    stats::save();
+    match result {
+        Ok(Some(output)) => report(&output, options.timing),
+        Ok(None) => {}
+        Err(e) => {
+            print_error("error-processing", &e, options.porcelain);
+            exit_with_hooks(1);
+        }
+    }
+    if options.emit_checksum {
+        print_checksum(&output_hash, options.porcelain);
+    }
 }
 
-fn process_upper_case(input: &str) -> Result<String, Box<dyn Error>> {
-    Ok(input.to_uppercase())
+// Exits after a command given on the command line, running the hook first
+fn exit_with_hooks(code: i32) -> ! {
+    hooks::finished(code);
+    process::exit(code)
 }
 
-fn process_operation(op: Operation, input: &str) -> Result<String, Box<dyn Error>> {
-    match op {
-        Operation::CamelCase => process_camel_case(input),
-        Operation::LowerCase => process_lower_case(input),
-        Operation::NoSpaces => process_no_spaces(input),
-        Operation::Slugify => process_slugify(input),
-        Operation::SnakeCase => process_snake_case(input),
-        Operation::UpperCase => process_upper_case(input),
-        Operation::Csv => process_csv(input),
+// With `--emit-checksum`, the SHA-256 of everything written to stdout, and
+// with `--porcelain` as a `checksum<TAB>sha256<TAB>hex` line
+fn print_checksum(hash: &checksum::Sha256Hash, porcelain: bool) {
+    let hash = checksum::hex(hash);
+    if porcelain {
+        eprintln!("checksum\tsha256\t{}", hash);
+    } else {
+        eprintln!("{}", i18n::text("output-checksum", &[("hash", &hash)]));
     }
 }
 
-#[derive(Debug)]
-struct Command {
-    operation: Operation,
-    input: String,
+// The built-in operations plus those of the plugins in the plugin directory
+fn load_registry() -> OperationRegistry {
+    let mut registry = OperationRegistry::default();
+    if let Some(dir) = plugin_dir() {
+        for e in load_plugins(&mut registry, &dir) {
+            eprintln!(
+                "{}",
+                i18n::text("warning", &[("message", &i18n::error(&e))])
+            );
+        }
+    }
+    registry
 }
 
-fn input_thread(tx: Sender<Command>) -> Result<(), Box<dyn Error>> {
-    loop {
-        let mut input = String::new();
-        if io::stdin().read_line(&mut input)? == 0 {
-            break;
-        }
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut cli_args: Vec<String> = env::args().skip(1).collect();
+    let options = CliOptions::take(&mut cli_args).unwrap_or_else(|e| {
+        eprintln!("{}", i18n::text("error", &[("message", &e)]));
+        process::exit(2);
+    });
+    let settings = Settings::load().unwrap_or_else(|e| {
+        eprintln!("{}", i18n::text("error", &[("message", &e)]));
+        process::exit(2);
+    });
+    set_max_input_bytes(options.max_input_bytes);
+    set_memory_budget(settings.memory_budget());
+    set_redaction(settings.redact_input);
+    stats::set_enabled(settings.usage_stats());
+    prompt::install(options.yes);
+    hooks::install(settings.on_success, settings.on_error);
+    if let Err(e) = shutdown::install() {
+        eprintln!("{}", i18n::text("ctrl-c-unavailable", &[("message", &e)]));
+    }
 
-        // Split input into operation and data
-        let parts: Vec<&str> = input.split_whitespace().collect();
-        if parts.is_empty() {
-            continue;
+    #[cfg(feature = "server")]
+    if cli_args.first().is_some_and(|arg| arg == "serve") {
+        let serve = serve::ServeOptions::parse(&cli_args[1..])
+            .unwrap_or_else(|e| {
+                eprintln!("{}", i18n::text("error", &[("message", &e)]));
+                process::exit(2);
+            })
+            .with_tokens(settings.tokens.clone());
+        prompt::answer_from(prompt::Answer::Never);
+        if let Err(e) = serve::run(serve, audit::wrap(load_registry()), options) {
+            eprintln!("{}", i18n::text("error", &[("message", &e)]));
+            process::exit(1);
         }
-
-        if parts.len() < 2 {
-            eprintln!("Error: Expected format: <operation> <input>");
-            Operation::print_available_operations();
-            continue;
+        return exit_after_shutdown();
+    }
+    #[cfg(all(unix, feature = "server"))]
+    if cli_args.first().is_some_and(|arg| arg == "daemon") {
+        let args = daemon::serve_args(&cli_args[1..]);
+        let serve = serve::ServeOptions::parse(&args)
+            .unwrap_or_else(|e| {
+                eprintln!("{}", i18n::text("error", &[("message", &e)]));
+                process::exit(2);
+            })
+            .with_tokens(settings.tokens.clone());
+        prompt::answer_from(prompt::Answer::Never);
+        if let Err(e) = serve::run(serve, audit::wrap(load_registry()), options) {
+            eprintln!("{}", i18n::text("error", &[("message", &e)]));
+            process::exit(1);
         }
+        return exit_after_shutdown();
+    }
+    #[cfg(all(unix, feature = "server"))]
+    if cli_args.first().is_some_and(|arg| arg == "client") {
+        let client = daemon::ClientOptions::parse(&cli_args[1..]).unwrap_or_else(|e| {
+            eprintln!("{}", i18n::text("error", &[("message", &e)]));
+            process::exit(2);
+        });
+        match daemon::client(client) {
+            Ok(true) => return Ok(()),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                eprintln!("{}", i18n::text("error", &[("message", &e)]));
+                process::exit(1);
+            }
+        }
+    }
 
-        match Operation::from_str(parts[0].trim()) {
-            Ok(operation) => {
-                // Consider everything after first space to be input data
-                let input = parts[1..].join(" ");
-                if let Err(e) = tx.send(Command { operation, input }) {
-                    eprintln!("Error sending command: {}", e);
-                    break;
-                }
+    if cli_args.first().is_some_and(|arg| arg == "bench") {
+        let bench = bench::BenchOptions::parse(&cli_args[1..]).unwrap_or_else(|e| {
+            eprintln!("{}", i18n::text("error", &[("message", &e)]));
+            process::exit(2);
+        });
+        let registry = load_registry();
+        match bench::run(bench, &registry, options) {
+            Ok(()) => return Ok(()),
+            Err(e @ OperationError::InvalidOperation { .. }) => {
+                print_error("error", &e, options.porcelain);
+                process::exit(2);
             }
             Err(e) => {
-                eprintln!("Error: {}", e);
-                Operation::print_available_operations();
+                print_error("error-processing", &e, options.porcelain);
+                process::exit(1);
             }
         }
     }
-    Ok(())
-}
 
-fn processing_thread(rx: Receiver<Command>) -> Result<(), Box<dyn Error>> {
-    while let Ok(command) = rx.recv() {
-        println!("Selected operation: {}", command.operation.to_str());
+    if cli_args.first().is_some_and(|arg| arg == "stats") {
+        if let Err(e) = stats::run(&cli_args[1..], options.porcelain) {
+            eprintln!("{}", i18n::text("error", &[("message", &e)]));
+            process::exit(2);
+        }
+        return Ok(());
+    }
 
-        match process_operation(command.operation, &command.input) {
-            Ok(result) => println!("{}", result),
-            Err(e) => eprintln!("Error processing input: {}", e),
+    #[cfg(feature = "jsonrpc")]
+    if options.jsonrpc {
+        if !cli_args.is_empty() {
+            eprintln!("{}", i18n::text("jsonrpc-arguments", &[]));
+            process::exit(2);
         }
+        prompt::answer_from(prompt::Answer::Never);
+        jsonrpc::run(&audit::wrap(load_registry()), options)?;
+        return exit_after_shutdown();
     }
-    Ok(())
-}
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let (tx, rx) = flume::unbounded();
+    // Kept for the whole run
+    let registry: &'static OperationRegistry = Box::leak(Box::new(load_registry()));
+
+    if !cli_args.is_empty() {
+        run_once(&cli_args, registry, options);
+        hooks::finished(exit_status());
+        return exit_after_shutdown();
+    }
+
+    let cache: Option<&'static Cache> =
+        (!options.no_cache).then(|| &*Box::leak(Box::new(Cache::default())));
+    let (tx, rx) = flume::bounded(options.capacity);
+    // Confirmations are answered on the next line typed
+    if io::stdin().is_terminal() {
+        prompt::answer_from(prompt::Answer::Stdin);
+    }
 
     let input_handle = thread::spawn(move || {
-        if let Err(e) = input_thread(tx) {
+        if let Err(e) = input_thread(tx, registry, cache, options) {
             eprintln!("Input thread error: {}", e);
         }
     });
 
-    let processing_handle = thread::spawn(move || {
-        if let Err(e) = processing_thread(rx) {
-            eprintln!("Processing thread error: {}", e);
+    let (sink, output_handle) = if options.jobs == 1 {
+        (Sink::Stdout, None)
+    } else if options.unordered {
+        (Sink::Unordered, None)
+    } else {
+        let (output_tx, output_rx) = flume::unbounded();
+        let output_handle = thread::spawn(move || {
+            if let Err(e) = output_thread(output_rx, options) {
+                eprintln!("Output thread error: {}", e);
+            }
+        });
+        (Sink::Ordered(output_tx), Some(output_handle))
+    };
+
+    let processing_handles: Vec<_> = (0..options.jobs)
+        .map(|_| {
+            let rx = rx.clone();
+            let sink = sink.clone();
+            thread::spawn(move || {
+                if let Err(e) = processing_thread(rx, registry, cache, sink, options) {
+                    eprintln!("Processing thread error: {}", e);
+                }
+            })
+        })
+        .collect();
+
+    for processing_handle in processing_handles {
+        if let Err(e) = processing_handle.join() {
+            return Err(format!("Processing thread panicked: {:?}", e).into());
         }
-    });
+    }
 
-    if let Err(e) = input_handle.join() {
-        return Err(Box::new(OperationError(format!(
-            "Input thread panicked: {:?}",
-            e
-        ))));
+    // The output thread stops once the last worker drops its sender
+    drop(sink);
+    if let Some(Err(e)) = output_handle.map(thread::JoinHandle::join) {
+        return Err(format!("Output thread panicked: {:?}", e).into());
     }
 
-    if let Err(e) = processing_handle.join() {
-        return Err(Box::new(OperationError(format!(
-            "Processing thread panicked: {:?}",
-            e
-        ))));
+    // After a shutdown request the input thread may still be waiting for a line
+    if !shutdown::requested() {
+        if let Err(e) = input_handle.join() {
+            return Err(format!("Input thread panicked: {:?}", e).into());
+        }
     }
 
+    hooks::finished(exit_status());
+    exit_after_shutdown()
+}
+
+// What `exit_after_shutdown` exits with
+fn exit_status() -> i32 {
+    if shutdown::requested() {
+        shutdown::EXIT_CODE
+    } else {
+        0
+    }
+}
+
+// Flushes what was written and exits with the interrupted code after Ctrl-C or SIGTERM
+fn exit_after_shutdown() -> Result<(), Box<dyn Error>> {
+    io::stdout().flush()?;
+    stats::save();
+    if shutdown::requested() {
+        process::exit(shutdown::EXIT_CODE);
+    }
     Ok(())
 }
 
 // TODO add some unit tests and integration tests
-// TODO divide code into modules
 // TODO update README
-// TODO implement properly traits (FromStr)
\ No newline at end of file
+// TODO implement properly traits (FromStr)
@@ -0,0 +1,287 @@
+use std::fs;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::args::OperationArgs;
+use crate::input::writable;
+use crate::registry::find_nested;
+use crate::{confirmed, output, Confirmation, Error};
+
+use super::{describe, read};
+
+// Prints the frontmatter as a JSON object, or with `--key` the value of one field
+pub fn write_frontmatter(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let key = args.value("key")?;
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(
+            "frontmatter requires a Markdown file, - for stdin or --inline TEXT",
+        ));
+    }
+
+    let text = read(&source, inline)?;
+    let Some(yaml) = find(&text) else {
+        return Err(Error::invalid(format!(
+            "{} has no frontmatter",
+            describe(&source, inline)
+        )));
+    };
+    let fields = parse(&text[yaml])?;
+    match key {
+        None => writeln!(out, "{}", serde_json::to_string_pretty(&fields)?)?,
+        Some(key) => match fields.get(&key) {
+            Some(Value::String(value)) => writeln!(out, "{}", value)?,
+            Some(value) => writeln!(out, "{}", value)?,
+            None => {
+                return Err(Error::invalid(format!(
+                    "The frontmatter has no field {}",
+                    key
+                )))
+            }
+        },
+    }
+    Ok(())
+}
+
+// Sets `--key` to the result of `--op` over the field `--from`, the key itself
+// by default, e.g. `--key slug --from title --op slugify`. The document is
+// printed, or with `--write` every file given is updated in place and the
+// names of the changed ones are printed
+pub fn write_frontmatter_apply(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let key = args.value("key")?;
+    let from = args.value("from")?;
+    let operation = args.value("op")?;
+    let save_files = args.flag("write");
+    let inline = args.flag("inline");
+    let mut sources = args.finish()?;
+
+    let (Some(key), Some(operation)) = (key, operation) else {
+        return Err(Error::invalid(
+            "frontmatter-apply requires --key <field> and --op <operation>",
+        ));
+    };
+    let operation = find_nested(&operation)?;
+    if !operation.is_text_operation() {
+        return Err(Error::invalid(format!(
+            "{} can't be applied to frontmatter fields",
+            operation.name()
+        )));
+    }
+    let from = from.unwrap_or_else(|| key.clone());
+
+    if inline {
+        if save_files {
+            return Err(Error::invalid("--write needs files rather than --inline"));
+        }
+        sources = vec![sources.join(" ")];
+    }
+    match sources.len() {
+        0 => {
+            return Err(Error::invalid(
+                "frontmatter-apply requires Markdown files, - for stdin or --inline TEXT",
+            ))
+        }
+        1 => {}
+        _ if !save_files => {
+            return Err(Error::invalid(
+                "frontmatter-apply prints a single document, add --write to update several files",
+            ))
+        }
+        _ => {}
+    }
+
+    // Files are written once all documents are ready and the overwrite is
+    // confirmed, so a declined or failed run leaves all of them as they were
+    let mut updates = Vec::new();
+    for source in &sources {
+        if save_files && source == "-" {
+            return Err(Error::invalid("--write can't save stdin"));
+        }
+        let target = if save_files {
+            writable(Path::new(source))?
+        } else {
+            PathBuf::from(source)
+        };
+        let text = read(source, inline)?;
+        let name = describe(source, inline);
+
+        // Updating many files skips those the field can't be set in
+        let skip = |reason: String| -> Result<(), Error> {
+            if save_files {
+                output::warn(format!("Skipped {}: {}", name, reason));
+                Ok(())
+            } else {
+                Err(Error::invalid(format!("{}: {}", name, reason)))
+            }
+        };
+        let Some(yaml) = find(&text) else {
+            skip("no frontmatter".to_string())?;
+            continue;
+        };
+        let fields = parse(&text[yaml.clone()])?;
+        let value = match fields.get(&from) {
+            Some(Value::String(value)) => value.clone(),
+            Some(value @ (Value::Number(_) | Value::Bool(_))) => value.to_string(),
+            Some(_) => {
+                skip(format!("the field {} is no text", from))?;
+                continue;
+            }
+            None => {
+                skip(format!("no field {}", from))?;
+                continue;
+            }
+        };
+
+        let result = operation.apply(&value)?;
+        let document = set_field(&text, yaml, &key, &result)?;
+        if !save_files {
+            out.write_all(document.as_bytes())?;
+        } else if document != text {
+            updates.push((PathBuf::from(source), target, document));
+        }
+    }
+
+    if !save_files {
+        return Ok(());
+    }
+    let paths: Vec<PathBuf> = updates.iter().map(|(path, _, _)| path.clone()).collect();
+    if !paths.is_empty()
+        && !confirmed(&Confirmation {
+            operation: "frontmatter-apply",
+            paths: &paths,
+        })
+    {
+        return Err(Error::invalid(
+            "frontmatter-apply wasn't confirmed, no file was updated",
+        ));
+    }
+    for (path, target, document) in &updates {
+        save(target, document)?;
+        writeln!(out, "{}", path.display())?;
+    }
+    output::warn(format!(
+        "{} of {} files updated",
+        updates.len(),
+        sources.len()
+    ));
+    Ok(())
+}
+
+// Where the YAML between a leading `---` line and the next `---` or `...` line is
+fn find(text: &str) -> Option<Range<usize>> {
+    let rest = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let opening = ["---\n", "---\r\n"]
+        .into_iter()
+        .find(|opening| rest.starts_with(opening))?;
+    let start = text.len() - rest.len() + opening.len();
+
+    let mut end = start;
+    for line in text[start..].split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return Some(start..end);
+        }
+        end += line.len();
+    }
+    None
+}
+
+fn parse(yaml: &str) -> Result<Map<String, Value>, Error> {
+    if yaml.trim().is_empty() {
+        return Ok(Map::new());
+    }
+    match serde_yaml_ng::from_str(yaml) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err(Error::invalid("The frontmatter is not a mapping of fields")),
+        Err(e) => Err(Error::invalid(format!(
+            "Invalid YAML in the frontmatter: {}",
+            e
+        ))),
+    }
+}
+
+// The text with the top-level field `key` of the frontmatter replaced, or
+// added after the others. Everything else stays as it was written, comments
+// and formatting included
+fn set_field(text: &str, yaml: Range<usize>, key: &str, value: &str) -> Result<String, Error> {
+    let newline = if text[yaml.clone()].contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let value = serde_yaml_ng::to_string(value)
+        .map_err(|e| Error::invalid(format!("Can't write the field {}: {}", key, e)))?;
+    let value = value.trim_end().replace('\n', newline);
+    let field = format!("{}: {}{}", key, value, newline);
+
+    let mut lines = Vec::new();
+    let mut position = yaml.start;
+    for line in text[yaml.clone()].split_inclusive('\n') {
+        lines.push(position..position + line.len());
+        position += line.len();
+    }
+    let entry = lines.iter().position(|line| {
+        text[line.clone()]
+            .strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    });
+
+    let mut document = String::with_capacity(text.len() + field.len());
+    match entry {
+        Some(index) => {
+            // The value can continue on indented lines and, for lists, lines
+            // starting with `-`
+            let end = lines[index + 1..]
+                .iter()
+                .find(|line| {
+                    let line = &text[(*line).clone()];
+                    !line.trim().is_empty()
+                        && !line.starts_with(char::is_whitespace)
+                        && !line.starts_with('-')
+                })
+                .map_or(yaml.end, |line| line.start);
+            document.push_str(&text[..lines[index].start]);
+            document.push_str(&field);
+            document.push_str(&text[end..]);
+        }
+        None => {
+            document.push_str(&text[..yaml.end]);
+            if !text[..yaml.end].ends_with('\n') {
+                document.push_str(newline);
+            }
+            document.push_str(&field);
+            document.push_str(&text[yaml.end..]);
+        }
+    }
+    Ok(document)
+}
+
+// Writes the document next to the file and moves it over the file, so an
+// interrupted run never leaves a half written file behind
+fn save(path: &Path, document: &str) -> Result<(), Error> {
+    let failed = |e: io::Error| Error::Io {
+        path: None,
+        source: io::Error::new(
+            e.kind(),
+            format!("Failed to write '{}': {}", path.display(), e),
+        ),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::invalid(format!("{} is no file", path.display())))?;
+    let temporary = path.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+
+    let permissions = fs::metadata(path).map_err(failed)?.permissions();
+    fs::write(&temporary, document).map_err(failed)?;
+    fs::set_permissions(&temporary, permissions).map_err(failed)?;
+    fs::rename(&temporary, path).map_err(|e| {
+        let _ = fs::remove_file(&temporary);
+        failed(e)
+    })
+}
@@ -0,0 +1,64 @@
+mod frontmatter;
+mod outline;
+
+use std::io::Read;
+
+use crate::input::open_source;
+use crate::streaming::StreamingOperation;
+use crate::Error;
+
+use frontmatter::{write_frontmatter, write_frontmatter_apply};
+use outline::{write_headings, write_links, write_toc};
+
+pub fn builtins() -> Vec<StreamingOperation> {
+    let operation = StreamingOperation::new;
+
+    vec![
+        operation(
+            "frontmatter",
+            &[],
+            "Prints the YAML frontmatter of a Markdown file as JSON",
+            write_frontmatter,
+        ),
+        operation(
+            "frontmatter-apply",
+            &[],
+            "Runs a text operation over a frontmatter field of Markdown files",
+            write_frontmatter_apply,
+        ),
+        operation(
+            "md-headings",
+            &[],
+            "Lists the headings of a Markdown file with their levels",
+            write_headings,
+        ),
+        operation(
+            "md-links",
+            &[],
+            "Lists the URLs of the links and images in a Markdown file",
+            write_links,
+        ),
+        operation(
+            "md-toc",
+            &[],
+            "Generates a table of contents for a Markdown file",
+            write_toc,
+        ),
+    ]
+}
+
+// The text of a file, `-` for stdin or, with `--inline`, the source itself
+fn read(source: &str, inline: bool) -> Result<String, Error> {
+    let mut text = String::new();
+    open_source(source, inline)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+// How errors name the source
+fn describe(source: &str, inline: bool) -> &str {
+    match source {
+        _ if inline => "The text",
+        "-" => "stdin",
+        source => source,
+    }
+}
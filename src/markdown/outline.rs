@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use slug::slugify;
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::read;
+
+struct Heading {
+    level: usize,
+    text: String,
+}
+
+// Prints every heading with as many `#` as its level, e.g. `## Usage`
+pub fn write_headings(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let args = OperationArgs::parse(input);
+    let markdown = document(args, "md-headings")?;
+
+    for heading in headings(&markdown) {
+        writeln!(out, "{} {}", "#".repeat(heading.level), heading.text)?;
+    }
+    Ok(())
+}
+
+// Prints the destination of every link and image in the order they appear,
+// reference links resolved, once each with `--unique`
+pub fn write_links(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let unique = args.flag("unique");
+    let markdown = document(args, "md-links")?;
+
+    let mut seen = HashSet::new();
+    for event in parser(&markdown) {
+        let url = match event {
+            Event::Start(Tag::Link { dest_url, .. })
+            | Event::Start(Tag::Image { dest_url, .. }) => dest_url,
+            _ => continue,
+        };
+        if url.is_empty() || (unique && !seen.insert(url.to_string())) {
+            continue;
+        }
+        writeln!(out, "{}", url)?;
+    }
+    Ok(())
+}
+
+// A nested list of links to the headings, with the anchors GitHub and most
+// static site generators give them: the slug of the text, `-1`, `-2`, ... for repeats
+pub fn write_toc(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let min_level: usize = args.parsed("min-level")?.unwrap_or(1);
+    let max_level: usize = args.parsed("max-level")?.unwrap_or(6);
+    let markdown = document(args, "md-toc")?;
+    if !(1..=6).contains(&min_level) || !(min_level..=6).contains(&max_level) {
+        return Err(Error::invalid(
+            "--min-level and --max-level need levels from 1 to 6, the minimum first",
+        ));
+    }
+
+    let headings = headings(&markdown);
+    let mut anchors: HashMap<String, usize> = HashMap::new();
+    let mut entries = Vec::new();
+    for heading in &headings {
+        // Anchors count every heading, also the ones left out of the list
+        let slug = slugify(&heading.text);
+        let count = anchors.entry(slug.clone()).or_insert(0);
+        let anchor = match *count {
+            0 => slug,
+            n => format!("{}-{}", slug, n),
+        };
+        *count += 1;
+        if (min_level..=max_level).contains(&heading.level) {
+            entries.push((heading, anchor));
+        }
+    }
+
+    let Some(top) = entries.iter().map(|(heading, _)| heading.level).min() else {
+        return Err(Error::invalid("The document has no headings to list"));
+    };
+    for (heading, anchor) in entries {
+        writeln!(
+            out,
+            "{}- [{}](#{})",
+            "  ".repeat(heading.level - top),
+            heading.text.replace('[', "\\[").replace(']', "\\]"),
+            anchor
+        )?;
+    }
+    Ok(())
+}
+
+// The Markdown of the file named in the input, `-` for stdin, or with `--inline` the text
+fn document(mut args: OperationArgs, operation: &str) -> Result<String, Error> {
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(format!(
+            "{} requires a Markdown file, - for stdin or --inline TEXT",
+            operation
+        )));
+    }
+    read(&source, inline)
+}
+
+// Frontmatter is skipped rather than read as a heading underlined by `---`
+fn parser(markdown: &str) -> Parser<'_> {
+    Parser::new_ext(
+        markdown,
+        Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+            | Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_HEADING_ATTRIBUTES,
+    )
+}
+
+fn headings(markdown: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut current: Option<Heading> = None;
+    for event in parser(markdown) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some(Heading {
+                    level: level_number(level),
+                    text: String::new(),
+                });
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(mut heading) = current.take() {
+                    heading.text = heading.text.trim().to_string();
+                    headings.push(heading);
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = &mut current {
+                    heading.text.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(heading) = &mut current {
+                    heading.text.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+fn level_number(level: HeadingLevel) -> usize {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
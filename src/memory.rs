@@ -0,0 +1,25 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// The most bytes of rows and lines an operation holds before spilling them to
+// temporary files or writing what it has, 0 for no budget
+static MEMORY_BUDGET: AtomicUsize = AtomicUsize::new(0);
+
+// Sets the memory budget for the whole process. The CSV renderer and the line
+// sorting operations keep what they hold under it instead of growing until
+// the process is killed
+pub fn set_memory_budget(bytes: Option<usize>) {
+    MEMORY_BUDGET.store(bytes.unwrap_or(0), Ordering::Relaxed);
+}
+
+pub fn memory_budget() -> Option<usize> {
+    match MEMORY_BUDGET.load(Ordering::Relaxed) {
+        0 => None,
+        bytes => Some(bytes),
+    }
+}
+
+// Whether holding `bytes` goes over the budget
+#[cfg(feature = "csv")]
+pub(crate) fn over_budget(bytes: usize) -> bool {
+    memory_budget().is_some_and(|budget| bytes > budget)
+}
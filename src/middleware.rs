@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::{Error, OperationOutput, TextOperation};
+
+// An operation together with the input it runs on
+#[derive(Clone)]
+pub struct Command {
+    pub operation: Arc<dyn TextOperation>,
+    pub input: String,
+}
+
+// Runs the rest of the chain, ending with the operation itself
+pub type Next<'a> = &'a dyn Fn(&Command) -> Result<OperationOutput, Error>;
+
+// Code wrapped around every operation run through a registry, e.g. logging,
+// timing, input size limits or output redaction. It may change the command
+// before calling `next`, change the output after, or fail without calling it
+pub trait Middleware: Send + Sync {
+    fn handle(&self, command: &Command, next: Next) -> Result<OperationOutput, Error>;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(&Command, Next) -> Result<OperationOutput, Error> + Send + Sync,
+{
+    fn handle(&self, command: &Command, next: Next) -> Result<OperationOutput, Error> {
+        self(command, next)
+    }
+}
@@ -0,0 +1,153 @@
+use convert_case::{Case, Casing};
+use slug::slugify;
+
+use crate::{text_ops, Error, TextOperation};
+
+// The text operations a `TextModifier` can be configured with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    CamelCase,
+    LowerCase,
+    NoSpaces,
+    Slugify,
+    SnakeCase,
+    UpperCase,
+}
+
+impl Op {
+    pub fn name(self) -> &'static str {
+        match self {
+            Op::CamelCase => "camelcase",
+            Op::LowerCase => "lowercase",
+            Op::NoSpaces => "no-spaces",
+            Op::Slugify => "slugify",
+            Op::SnakeCase => "snakecase",
+            Op::UpperCase => "uppercase",
+        }
+    }
+
+    fn supports_separator(self) -> bool {
+        matches!(self, Op::NoSpaces | Op::Slugify | Op::SnakeCase)
+    }
+}
+
+// A text operation with its options set in code rather than in the input string
+pub struct TextModifier {
+    op: Op,
+    separator: Option<char>,
+    // Turkish and Azerbaijani map dotted and dotless i differently
+    turkic: bool,
+}
+
+impl TextModifier {
+    pub fn builder() -> TextModifierBuilder {
+        TextModifierBuilder::default()
+    }
+
+    fn lowercase(&self, input: &str) -> String {
+        if self.turkic {
+            input.replace('I', "ı").replace('İ', "i").to_lowercase()
+        } else {
+            text_ops::lowercase(input)
+        }
+    }
+
+    fn uppercase(&self, input: &str) -> String {
+        if self.turkic {
+            input.replace('i', "İ").to_uppercase()
+        } else {
+            text_ops::uppercase(input)
+        }
+    }
+
+    // Puts the separator in place of the one the operation joins words with
+    fn join(&self, text: &str, default: &str) -> String {
+        match self.separator {
+            Some(separator) => text.replace(default, separator.encode_utf8(&mut [0; 4])),
+            None => text.to_string(),
+        }
+    }
+}
+
+impl TextOperation for TextModifier {
+    fn name(&self) -> &str {
+        self.op.name()
+    }
+
+    fn describe(&self) -> &str {
+        "Configured text operation"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, Error> {
+        Ok(match self.op {
+            Op::CamelCase => input.to_case(Case::Camel),
+            Op::LowerCase => self.lowercase(input),
+            Op::NoSpaces => match self.separator {
+                Some(_) => self.join(input, " "),
+                None => input.replace(' ', ""),
+            },
+            Op::Slugify => self.join(&slugify(input), "-"),
+            Op::SnakeCase => self.join(&input.to_case(Case::Snake), "_"),
+            Op::UpperCase => self.uppercase(input),
+        })
+    }
+
+    fn is_text_operation(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Default)]
+pub struct TextModifierBuilder {
+    op: Option<Op>,
+    separator: Option<char>,
+    locale: Option<String>,
+}
+
+impl TextModifierBuilder {
+    pub fn operation(mut self, op: Op) -> TextModifierBuilder {
+        self.op = Some(op);
+        self
+    }
+
+    // Joins words with this character, for no-spaces, slugify and snakecase
+    pub fn separator(mut self, separator: char) -> TextModifierBuilder {
+        self.separator = Some(separator);
+        self
+    }
+
+    // A BCP 47 language tag like "tr" or "tr-TR", used by lowercase and uppercase
+    pub fn locale(mut self, locale: &str) -> TextModifierBuilder {
+        self.locale = Some(locale.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<TextModifier, Error> {
+        let Some(op) = self.op else {
+            return Err(Error::invalid("TextModifier needs an operation"));
+        };
+        if self.separator.is_some() && !op.supports_separator() {
+            return Err(Error::invalid(format!(
+                "{} does not support a separator",
+                op.name()
+            )));
+        }
+
+        let language = match &self.locale {
+            Some(locale) => {
+                let language = locale.split(['-', '_']).next().unwrap_or("");
+                if language.len() < 2 || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Err(Error::invalid(format!("Invalid locale: {}", locale)));
+                }
+                language.to_lowercase()
+            }
+            None => String::new(),
+        };
+
+        Ok(TextModifier {
+            op,
+            separator: self.separator,
+            turkic: language == "tr" || language == "az",
+        })
+    }
+}
@@ -0,0 +1,279 @@
+use std::borrow::Cow;
+use std::io::{BufRead, BufReader, Write};
+use std::thread;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+// std has no clock in the browser
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use rayon::prelude::*;
+
+use crate::output::{self, OperationOutput};
+use crate::tuning::Tuner;
+use crate::{cancel, input, Error};
+
+// A named operation the REPL and the command line can run, implement it to
+// add operations to an `OperationRegistry`
+pub trait TextOperation: Send + Sync {
+    fn name(&self) -> &str;
+
+    // Other names the operation is found by
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    // One line shown in the list of available operations
+    fn describe(&self) -> &str;
+
+    fn apply(&self, input: &str) -> Result<String, Error>;
+
+    // Like `apply`, but can hand back the input itself when nothing changes,
+    // which saves an allocation per call when processing many values
+    fn apply_cow<'a>(&self, input: &'a str) -> Result<Cow<'a, str>, Error> {
+        self.apply(input).map(Cow::Owned)
+    }
+
+    // Writes the result, operations producing large output override this to stream it
+    fn write(&self, input: &str, out: &mut dyn Write) -> Result<(), Error> {
+        writeln!(out, "{}", self.apply_cow(input)?)?;
+        Ok(())
+    }
+
+    // Operations transforming the input text itself rather than reading files,
+    // only these can be applied to CSV values
+    fn is_text_operation(&self) -> bool {
+        false
+    }
+}
+
+// Runs an operation like the REPL and the command line do, writing its output to `out`
+pub fn write_operation(
+    operation: &dyn TextOperation,
+    input: &str,
+    out: &mut dyn Write,
+) -> Result<OperationOutput, Error> {
+    output::measure(operation, input, out, |out| {
+        write_input(operation, input, out)
+    })
+}
+
+// Like `write_operation`, with the output collected into `OperationOutput::text`
+pub fn run_operation(operation: &dyn TextOperation, input: &str) -> Result<OperationOutput, Error> {
+    let mut text = Vec::new();
+    let mut output = write_operation(operation, input, &mut text)?;
+    let mut text = String::from_utf8(text)
+        .map_err(|e| Error::invalid(format!("Output is not valid UTF-8: {}", e)))?;
+    text.truncate(text.trim_end_matches('\n').len());
+    output.text = text;
+    Ok(output)
+}
+
+fn write_input(
+    operation: &dyn TextOperation,
+    input: &str,
+    out: &mut dyn Write,
+) -> Result<(), Error> {
+    cancel::check_input_size(input.len() as u64)?;
+
+    // `--file PATH` transforms the contents of a file instead of the input text,
+    // `--mmap --file PATH` reads it through a memory map
+    let (mmap, file_input) = match input.strip_prefix("--mmap ") {
+        Some(rest) => (true, rest),
+        None => (false, input),
+    };
+    let source = match file_input.strip_prefix("--file ") {
+        Some(source) if operation.is_text_operation() => source.trim(),
+        _ => return operation.write(input, out),
+    };
+
+    if mmap {
+        match input::map_file(source)? {
+            Some(map) if !input::is_compressed(&map) => {
+                return process_mapped(operation, &map, out)
+            }
+            Some(_) => {
+                let reader = BufReader::new(input::open_mapped(source)?);
+                return process_stream(operation, reader, out);
+            }
+            None => {}
+        }
+    }
+    let reader = BufReader::new(input::open_source(source, false)?);
+    process_stream(operation, reader, out)
+}
+
+// Transforms mapped file contents in place, the lines are never copied
+fn process_mapped(
+    operation: &dyn TextOperation,
+    data: &[u8],
+    out: &mut dyn Write,
+) -> Result<(), Error> {
+    let text = std::str::from_utf8(data).map_err(|e| {
+        Error::invalid(format!(
+            "The file is not valid UTF-8 at byte {}",
+            e.valid_up_to() + 1
+        ))
+    })?;
+    output::count_read(data.len());
+    let lines: Vec<&str> = text.lines().collect();
+    for chunk in lines.chunks(CHUNK_LINES) {
+        cancel::check_timeout()?;
+        write_lines(operation, chunk, out, 0)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+// Lines read before they are transformed in parallel
+const CHUNK_LINES: usize = 16 * 1024;
+
+// How often `Chunking::follow` looks for more input once it has read everything
+const FOLLOW_POLL: Duration = Duration::from_millis(250);
+
+// How `process_stream_with` splits its input. A chunk is transformed and
+// written once it has `lines` lines or, with `bytes`, at least that many bytes,
+// so memory stays bounded however long the input is
+#[derive(Clone, Copy, Debug)]
+pub struct Chunking {
+    pub lines: usize,
+    pub bytes: Option<usize>,
+    // Keeps waiting for more input at its end, like `tail -f`, until this
+    // returns true or the time limit of `with_timeout` has passed
+    pub follow: Option<fn() -> bool>,
+    // Ignores `lines` and `bytes` and sizes chunks, and the number of threads
+    // transforming them, by the throughput measured on the chunks so far
+    pub adaptive: bool,
+}
+
+impl Default for Chunking {
+    fn default() -> Chunking {
+        Chunking {
+            lines: CHUNK_LINES,
+            bytes: None,
+            follow: None,
+            adaptive: false,
+        }
+    }
+}
+
+// Applies a text operation line by line, so inputs of any size are never held in memory
+pub fn process_stream<R: BufRead, W: Write>(
+    operation: &dyn TextOperation,
+    reader: R,
+    writer: W,
+) -> Result<(), Error> {
+    process_stream_chunked(operation, reader, writer, CHUNK_LINES)
+}
+
+// Like `process_stream`, the lines of each chunk are spread over all CPUs and
+// written back in order. Interactive input wants a chunk of one line, so every
+// line is answered as soon as it is entered
+pub fn process_stream_chunked<R: BufRead, W: Write>(
+    operation: &dyn TextOperation,
+    reader: R,
+    writer: W,
+    chunk_lines: usize,
+) -> Result<(), Error> {
+    let chunking = Chunking {
+        lines: chunk_lines,
+        ..Chunking::default()
+    };
+    process_stream_with(operation, reader, writer, chunking)
+}
+
+// Like `process_stream_chunked`, with the chunks split as `chunking` says
+pub fn process_stream_with<R: BufRead, W: Write>(
+    operation: &dyn TextOperation,
+    mut reader: R,
+    mut writer: W,
+    chunking: Chunking,
+) -> Result<(), Error> {
+    let mut tuner = chunking.adaptive.then(Tuner::new);
+    let mut lines: Vec<String> = Vec::new();
+    let mut bytes = 0;
+    let mut line = String::new();
+    loop {
+        let read = reader.read_line(&mut line)?;
+        // While following, a line without its newline is still being written
+        let end = read == 0 || (chunking.follow.is_some() && !line.ends_with('\n'));
+        if !end {
+            bytes += line.len();
+            let text = line.trim_end_matches(['\r', '\n']).len();
+            line.truncate(text);
+            lines.push(std::mem::take(&mut line));
+        }
+
+        let full = match &tuner {
+            Some(tuner) => bytes >= tuner.bytes,
+            None => {
+                lines.len() >= chunking.lines.max(1)
+                    || chunking.bytes.is_some_and(|limit| bytes >= limit)
+            }
+        };
+        if (end || full) && !lines.is_empty() {
+            cancel::check_timeout()?;
+            let start = Instant::now();
+            let workers = tuner.as_ref().map_or(0, |tuner| tuner.workers);
+            write_lines(operation, &lines, &mut writer, workers)?;
+            writer.flush()?;
+            if let Some(tuner) = &mut tuner {
+                tuner.record(bytes, start.elapsed());
+            }
+            lines.clear();
+            bytes = 0;
+        }
+
+        if end {
+            match chunking.follow {
+                Some(stop) if !stop() => {
+                    cancel::check_timeout()?;
+                    thread::sleep(FOLLOW_POLL);
+                }
+                _ => break,
+            }
+        }
+    }
+
+    // The last line of followed input, which never got its newline
+    if !line.is_empty() {
+        let text = line.trim_end_matches(['\r', '\n']).len();
+        write_lines(operation, &[&line[..text]], &mut writer, 0)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+// Transforms a chunk of lines in parallel and writes them in their order,
+// on at most `workers` threads unless it is 0
+fn write_lines<L: AsRef<str> + Sync>(
+    operation: &dyn TextOperation,
+    lines: &[L],
+    writer: &mut dyn Write,
+    workers: usize,
+) -> Result<(), Error> {
+    let results = if lines.len() == 1 {
+        vec![operation.apply_cow(lines[0].as_ref())?]
+    } else if workers > 0 {
+        let parts: Vec<Vec<_>> = lines
+            .par_chunks(lines.len().div_ceil(workers))
+            .map(|part| {
+                part.iter()
+                    .map(|line| operation.apply_cow(line.as_ref()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<_, _>>()?;
+        parts.into_iter().flatten().collect()
+    } else {
+        lines
+            .par_iter()
+            .map(|line| operation.apply_cow(line.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    for result in results {
+        writeln!(writer, "{}", result)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+// std has no clock in the browser
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use serde::{Serialize, Serializer};
+
+use crate::{Error, TextOperation};
+
+// The result of running an operation together with figures about the run
+#[derive(Clone, Debug, Serialize)]
+pub struct OperationOutput {
+    // Empty when `write_operation` wrote the output to a writer instead
+    pub text: String,
+    pub operation: String,
+    #[serde(serialize_with = "seconds")]
+    pub duration: Duration,
+    // The input text plus everything read from files and stdin
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    // Notes for the user that are kept out of the output, e.g. row counts
+    pub warnings: Vec<String>,
+}
+
+impl OperationOutput {
+    pub fn into_string(self) -> String {
+        self.text
+    }
+}
+
+// Figures of the operation running on this thread
+struct Run {
+    bytes_in: u64,
+    warnings: Vec<String>,
+}
+
+thread_local! {
+    static RUN: RefCell<Option<Run>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn count_read(bytes: usize) {
+    RUN.with_borrow_mut(|run| {
+        if let Some(run) = run {
+            run.bytes_in += bytes as u64;
+        }
+    });
+}
+
+// Outside of a measured run the warning goes straight to stderr
+#[cfg_attr(not(any(feature = "csv", feature = "markdown")), allow(dead_code))]
+pub(crate) fn warn(message: String) {
+    RUN.with_borrow_mut(|run| match run {
+        Some(run) => run.warnings.push(message),
+        None => eprintln!("{}", message),
+    });
+}
+
+// Runs `write` against `out`, measuring its time, input and output
+pub(crate) fn measure(
+    operation: &dyn TextOperation,
+    input: &str,
+    out: &mut dyn Write,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), Error>,
+) -> Result<OperationOutput, Error> {
+    let previous = RUN.replace(Some(Run {
+        bytes_in: input.len() as u64,
+        warnings: Vec::new(),
+    }));
+    let start = Instant::now();
+    let mut counter = CountingWriter { out, bytes: 0 };
+    let result = write(&mut counter);
+    let run = RUN.replace(previous);
+    result?;
+
+    let run = run.unwrap_or(Run {
+        bytes_in: 0,
+        warnings: Vec::new(),
+    });
+    Ok(OperationOutput {
+        text: String::new(),
+        operation: operation.name().to_string(),
+        duration: start.elapsed(),
+        bytes_in: run.bytes_in,
+        bytes_out: counter.bytes,
+        warnings: run.warnings,
+    })
+}
+
+struct CountingWriter<'a> {
+    out: &'a mut dyn Write,
+    bytes: u64,
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.out.write(buf)?;
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn seconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
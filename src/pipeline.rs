@@ -0,0 +1,124 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{cancel, registry, Error, OperationRegistry, TextOperation};
+
+// Operations applied one after another, each to the output of the previous one,
+// written as `lowercase | slugify` or stored as a list of steps
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Pipeline {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub operation: String,
+    // Options put in front of the input of the step, e.g. `--format json`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub args: String,
+}
+
+impl Pipeline {
+    pub fn new(steps: Vec<Step>) -> Pipeline {
+        Pipeline { steps }
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    // Runs the steps with the built-in operations, or those of the registry
+    // running the command inside `OperationRegistry::scope`
+    pub fn execute(&self, input: &str) -> Result<String, Error> {
+        registry::with_current(|registry| self.execute_with(registry, input))
+    }
+
+    // Every operation is looked up before the first one runs
+    pub fn execute_with(&self, registry: &OperationRegistry, input: &str) -> Result<String, Error> {
+        let operations = self
+            .steps
+            .iter()
+            .map(|step| registry.find(&step.operation))
+            .collect::<Result<Vec<Arc<dyn TextOperation>>, _>>()?;
+        cancel::check_input_size(input.len() as u64)?;
+
+        registry.scope(|| {
+            let mut text = input.to_string();
+            for (step, operation) in self.steps.iter().zip(operations) {
+                let step_input = if step.args.is_empty() {
+                    text
+                } else {
+                    format!("{} {}", step.args, text)
+                };
+                text = operation.apply(&step_input)?;
+            }
+            Ok(text)
+        })
+    }
+}
+
+impl FromStr for Pipeline {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Pipeline, Error> {
+        let steps = split_steps(s)
+            .iter()
+            .map(|step| {
+                let step = step.trim();
+                let (operation, args) = step.split_once(char::is_whitespace).unwrap_or((step, ""));
+                if operation.is_empty() {
+                    return Err(Error::invalid(format!("Empty step in pipeline: {}", s)));
+                }
+                Ok(Step {
+                    operation: operation.to_string(),
+                    args: args.trim().to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Pipeline { steps })
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, " | ")?;
+            }
+            write!(f, "{}", step.operation)?;
+            if !step.args.is_empty() {
+                write!(f, " {}", step.args)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Splits on `|` outside of single or double quotes
+fn split_steps(s: &str) -> Vec<String> {
+    let mut steps = vec![String::new()];
+    let mut quote: Option<char> = None;
+
+    for c in s.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '|' => {
+                steps.push(String::new());
+                continue;
+            }
+            None => {}
+        }
+        if let Some(step) = steps.last_mut() {
+            step.push(c);
+        }
+    }
+
+    steps
+}
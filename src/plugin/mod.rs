@@ -0,0 +1,54 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{config_dir, Error, OperationRegistry};
+
+#[cfg(feature = "native-plugins")]
+mod native;
+#[cfg(feature = "wasm-plugins")]
+mod wasm;
+
+#[cfg(feature = "native-plugins")]
+pub use native::{PluginBuffer, PluginDeclaration, PLUGIN_ABI_VERSION, PLUGIN_SYMBOL};
+
+// `~/.config/text-modifier/plugins`
+pub fn plugin_dir() -> Option<PathBuf> {
+    Some(config_dir()?.join("plugins"))
+}
+
+// Registers the operation of every plugin in `dir` that this build can load:
+// shared libraries with the `native-plugins` feature, `.wasm` modules with
+// `wasm-plugins`. A missing directory is no error, a plugin that fails to load
+// doesn't stop the others
+#[cfg_attr(
+    not(any(feature = "native-plugins", feature = "wasm-plugins")),
+    allow(unused_variables)
+)]
+pub fn load_plugins(registry: &mut OperationRegistry, dir: &Path) -> Vec<Error> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let loaded: Option<Result<(), String>> =
+                match path.extension().and_then(|extension| extension.to_str()) {
+                    #[cfg(feature = "native-plugins")]
+                    Some(std::env::consts::DLL_EXTENSION) => {
+                        Some(native::load(&path).map(|op| registry.register(op)))
+                    }
+                    #[cfg(feature = "wasm-plugins")]
+                    Some("wasm") => Some(wasm::load(&path).map(|op| registry.register(op))),
+                    _ => None,
+                };
+            let reason = loaded?.err()?;
+            Some(Error::Plugin { path, reason })
+        })
+        .collect()
+}
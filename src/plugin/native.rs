@@ -0,0 +1,133 @@
+use std::ffi::{c_char, CStr};
+use std::path::Path;
+use std::sync::Arc;
+
+use libloading::{Library, Symbol};
+
+use crate::{Error, TextOperation};
+
+// Bumped whenever `PluginDeclaration` changes, plugins built for another
+// version are refused
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+// The symbol every plugin library exports, an
+// `extern "C" fn() -> *const PluginDeclaration`
+pub const PLUGIN_SYMBOL: &[u8] = b"text_modifier_plugin";
+
+// Bytes handed from the plugin to the host, given back to `free` once copied
+#[repr(C)]
+pub struct PluginBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+// What a plugin exports, laid out the same in every language. `name` and
+// `description` are NUL-terminated UTF-8 that lives as long as the library.
+// `apply` may be called from several threads at once, it returns 0 with the
+// result in `output` or anything else with an error message in `output`
+#[repr(C)]
+pub struct PluginDeclaration {
+    pub abi_version: u32,
+    pub name: *const c_char,
+    pub description: *const c_char,
+    pub apply: unsafe extern "C" fn(input: *const u8, len: usize, output: *mut PluginBuffer) -> i32,
+    pub free: unsafe extern "C" fn(buffer: PluginBuffer),
+}
+
+// An operation implemented by a loaded plugin
+struct NativeOperation {
+    name: String,
+    description: String,
+    apply: unsafe extern "C" fn(*const u8, usize, *mut PluginBuffer) -> i32,
+    free: unsafe extern "C" fn(PluginBuffer),
+    // Keeps the code behind `apply` and `free` loaded
+    _library: Arc<Library>,
+}
+
+// SAFETY: plugins promise `apply` and `free` can be called from any thread
+unsafe impl Send for NativeOperation {}
+unsafe impl Sync for NativeOperation {}
+
+impl TextOperation for NativeOperation {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn describe(&self) -> &str {
+        &self.description
+    }
+
+    fn apply(&self, input: &str) -> Result<String, Error> {
+        let mut buffer = PluginBuffer {
+            data: std::ptr::null_mut(),
+            len: 0,
+        };
+        // SAFETY: the input outlives the call and the buffer is only read
+        // after `apply` filled it in, then given back to the plugin
+        let (status, output) = unsafe {
+            let status = (self.apply)(input.as_ptr(), input.len(), &mut buffer);
+            let output = if buffer.data.is_null() {
+                Vec::new()
+            } else {
+                std::slice::from_raw_parts(buffer.data, buffer.len).to_vec()
+            };
+            (self.free)(buffer);
+            (status, output)
+        };
+
+        let output = String::from_utf8_lossy(&output).into_owned();
+        if status == 0 {
+            Ok(output)
+        } else {
+            Err(Error::invalid(format!("{} failed: {}", self.name, output)))
+        }
+    }
+
+    fn is_text_operation(&self) -> bool {
+        true
+    }
+}
+
+pub(super) fn load(path: &Path) -> Result<impl TextOperation, String> {
+    // SAFETY: loading runs the library's initializers, plugins are trusted code
+    // the user put in their plugin directory
+    let library = unsafe { Library::new(path) }.map_err(|e| e.to_string())?;
+
+    // SAFETY: the symbol is declared with this signature by the plugin ABI, and
+    // the declaration it returns lives as long as the library
+    let declaration = unsafe {
+        let entry: Symbol<unsafe extern "C" fn() -> *const PluginDeclaration> =
+            library.get(PLUGIN_SYMBOL).map_err(|e| e.to_string())?;
+        entry().as_ref().ok_or("the plugin declaration is null")?
+    };
+
+    if declaration.abi_version != PLUGIN_ABI_VERSION {
+        return Err(format!(
+            "built for plugin ABI {}, expected {}",
+            declaration.abi_version, PLUGIN_ABI_VERSION
+        ));
+    }
+
+    // SAFETY: the ABI requires NUL-terminated strings that live as long as the library
+    let (name, description) = unsafe {
+        (
+            c_string(declaration.name).ok_or("the plugin has no name")?,
+            c_string(declaration.description).unwrap_or_default(),
+        )
+    };
+
+    Ok(NativeOperation {
+        name: name.to_lowercase(),
+        description,
+        apply: declaration.apply,
+        free: declaration.free,
+        _library: Arc::new(library),
+    })
+}
+
+unsafe fn c_string(pointer: *const c_char) -> Option<String> {
+    if pointer.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(pointer).to_string_lossy().into_owned())
+}
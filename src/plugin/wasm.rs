@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use wasmtime::{
+    Config, Engine, InstancePre, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+use crate::{Error, TextOperation};
+
+// Most memory a module may grow to during one call
+const MEMORY_LIMIT: usize = 256 << 20;
+
+// A WebAssembly plugin. Modules get no imports at all, so they can't reach the
+// filesystem, the network or anything else outside their own memory. Each
+// call runs in a fresh instance, nothing is kept between inputs. A module
+// exports:
+//
+// - `memory`
+// - `alloc(len: i32) -> i32`, returning where the host writes `len` input bytes
+// - `apply(ptr: i32, len: i32) -> i64`, transforming the UTF-8 input there and
+//   returning the output as `ptr << 32 | len`. Failing is done by trapping
+struct WasmOperation {
+    name: String,
+    description: String,
+    instance: InstancePre<StoreLimits>,
+}
+
+impl TextOperation for WasmOperation {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn describe(&self) -> &str {
+        &self.description
+    }
+
+    fn apply(&self, input: &str) -> Result<String, Error> {
+        self.call(input)
+            .map_err(|e| Error::invalid(format!("{} failed: {:#}", self.name, e)))
+    }
+
+    fn is_text_operation(&self) -> bool {
+        true
+    }
+}
+
+impl WasmOperation {
+    fn call(&self, input: &str) -> wasmtime::Result<String> {
+        let limits = StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build();
+        let mut store = Store::new(self.instance.module().engine(), limits);
+        store.limiter(|limits| limits);
+        let instance = self.instance.instantiate(&mut store)?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| wasmtime::Error::msg("the module exports no memory"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let apply = instance.get_typed_func::<(i32, i32), i64>(&mut store, "apply")?;
+
+        let len = i32::try_from(input.len())?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, input.as_bytes())?;
+        let output = apply.call(&mut store, (ptr, len))? as u64;
+
+        let mut bytes = vec![0; output as u32 as usize];
+        memory.read(&store, (output >> 32) as usize, &mut bytes)?;
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+pub(super) fn load(path: &Path) -> Result<impl TextOperation, String> {
+    // Traps are reported as one line, without the plugin's stack
+    let engine =
+        Engine::new(Config::new().wasm_backtrace_max_frames(None)).map_err(|e| e.to_string())?;
+    let module = Module::from_file(&engine, path).map_err(|e| e.to_string())?;
+    if let Some(import) = module.imports().next() {
+        return Err(format!(
+            "the module imports {}::{}, plugins can't import anything",
+            import.module(),
+            import.name()
+        ));
+    }
+    let instance = Linker::new(&engine)
+        .instantiate_pre(&module)
+        .map_err(|e| format!("{:#}", e))?;
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .ok_or("the plugin has no name")?;
+    Ok(WasmOperation {
+        description: format!("WebAssembly plugin {}", path.display()),
+        name,
+        instance,
+    })
+}
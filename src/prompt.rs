@@ -0,0 +1,161 @@
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+use flume::Sender;
+
+use rust_text_transformer::{set_confirmation, Confirmation};
+
+use crate::i18n;
+
+// Paths listed before the rest are only counted
+const PREVIEW: usize = 10;
+
+#[cfg(windows)]
+const TERMINAL: &str = "CONIN$";
+#[cfg(not(windows))]
+const TERMINAL: &str = "/dev/tty";
+
+// Where the answer to a confirmation comes from
+#[derive(Clone, Copy)]
+pub enum Answer {
+    // The terminal, also when stdin is piped
+    Terminal = 0,
+    // The next line of stdin, when commands are typed there
+    Stdin = 1,
+    // Nowhere, the server modes have no one to ask
+    Never = 2,
+}
+
+// `--yes`, confirming everything without asking
+static YES: AtomicBool = AtomicBool::new(false);
+static ANSWER: AtomicU8 = AtomicU8::new(Answer::Terminal as u8);
+
+// The worker waiting for the next line of stdin to answer it
+static PENDING: Mutex<Option<Sender<bool>>> = Mutex::new(None);
+
+// One question at a time when several workers ask
+static ASKING: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    // Whether this is the thread reading the commands of stdin, which has to
+    // read the answer itself instead of waiting for it
+    static READS_STDIN: Cell<bool> = const { Cell::new(false) };
+}
+
+// Asks before operations overwrite files, unless `--yes` was given
+pub fn install(yes: bool) {
+    YES.store(yes, Ordering::Relaxed);
+    set_confirmation(confirm);
+}
+
+pub fn answer_from(answer: Answer) {
+    ANSWER.store(answer as u8, Ordering::Relaxed);
+}
+
+// Marks the calling thread as the one reading stdin
+pub fn reads_stdin() {
+    READS_STDIN.with(|reads| reads.set(true));
+}
+
+// Takes a line read from stdin as the answer when a worker is waiting for one,
+// false when the line is a command
+pub fn take_answer(line: &str) -> bool {
+    let Some(pending) = PENDING.lock().expect("prompt lock poisoned").take() else {
+        return false;
+    };
+    let _ = pending.send(is_yes(line));
+    true
+}
+
+// Declines the question waiting for stdin and the ones after, nothing can
+// answer them anymore
+pub fn stdin_closed() {
+    let mut pending = PENDING.lock().expect("prompt lock poisoned");
+    answer_from(Answer::Never);
+    pending.take();
+}
+
+fn confirm(confirmation: &Confirmation) -> bool {
+    if YES.load(Ordering::Relaxed) {
+        return true;
+    }
+    let _asking = ASKING.lock().expect("prompt lock poisoned");
+    let answer = ANSWER.load(Ordering::Relaxed);
+    if answer == Answer::Never as u8 {
+        eprintln!("{}", i18n::text("confirm-needs-yes", &[]));
+        return false;
+    }
+
+    let mut stderr = io::stderr().lock();
+    let _ = writeln!(
+        stderr,
+        "{}",
+        i18n::text(
+            "confirm-overwrite",
+            &[
+                ("operation", &confirmation.operation),
+                ("count", &confirmation.paths.len()),
+            ]
+        )
+    );
+    for path in confirmation.paths.iter().take(PREVIEW) {
+        let _ = writeln!(stderr, "  {}", path.display());
+    }
+    if confirmation.paths.len() > PREVIEW {
+        let _ = writeln!(
+            stderr,
+            "  {}",
+            i18n::text(
+                "confirm-more",
+                &[("count", &(confirmation.paths.len() - PREVIEW))]
+            )
+        );
+    }
+    let _ = write!(stderr, "{} ", i18n::text("confirm-question", &[]));
+    let _ = stderr.flush();
+    drop(stderr);
+
+    if answer == Answer::Stdin as u8 && READS_STDIN.with(Cell::get) {
+        let mut line = String::new();
+        return matches!(io::stdin().read_line(&mut line), Ok(read) if read > 0) && is_yes(&line);
+    }
+    if answer == Answer::Stdin as u8 {
+        let (tx, rx) = flume::bounded(1);
+        {
+            let mut pending = PENDING.lock().expect("prompt lock poisoned");
+            if ANSWER.load(Ordering::Relaxed) == Answer::Never as u8 {
+                eprintln!();
+                return false;
+            }
+            *pending = Some(tx);
+        }
+        // Dropped when stdin ends without an answer
+        return rx.recv().unwrap_or(false);
+    }
+    let mut line = String::new();
+    let read =
+        File::open(TERMINAL).and_then(|terminal| BufReader::new(terminal).read_line(&mut line));
+    match read {
+        Ok(_) => is_yes(&line),
+        Err(_) => {
+            eprintln!();
+            eprintln!("{}", i18n::text("confirm-needs-yes", &[]));
+            false
+        }
+    }
+}
+
+// `y`, `yes` or the word of the language, or its start
+fn is_yes(line: &str) -> bool {
+    let line = line.trim().to_lowercase();
+    !line.is_empty()
+        && [
+            "yes".to_string(),
+            i18n::text("confirm-yes", &[]).to_lowercase(),
+        ]
+        .iter()
+        .any(|yes| yes.starts_with(&line))
+}
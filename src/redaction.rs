@@ -0,0 +1,58 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+// How input text shows up in error messages, so the tool can run on personal
+// data without it ending up in terminal scrollback or collected logs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Redaction {
+    // The text as it is
+    #[default]
+    Off,
+    // Its length and the start of its SHA-256, which tells equal values apart
+    Hash,
+    // Only its length
+    Elide,
+}
+
+static REDACTION: AtomicU8 = AtomicU8::new(0);
+
+// Sets how input text is shown for the whole process
+pub fn set_redaction(redaction: Redaction) {
+    REDACTION.store(redaction as u8, Ordering::Relaxed);
+}
+
+pub fn redaction() -> Redaction {
+    match REDACTION.load(Ordering::Relaxed) {
+        1 => Redaction::Hash,
+        2 => Redaction::Elide,
+        _ => Redaction::Off,
+    }
+}
+
+// The text, or with redaction `<5 bytes, sha256 2cf24dba5fb0a30e>` or `<5 bytes>`
+pub fn redacted(text: &str) -> Cow<'_, str> {
+    match redaction() {
+        Redaction::Off => Cow::Borrowed(text),
+        Redaction::Hash => {
+            let hash = Sha256::digest(text);
+            let hex: String = hash[..8]
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            Cow::Owned(format!("<{} bytes, sha256 {}>", text.len(), hex))
+        }
+        Redaction::Elide => Cow::Owned(format!("<{} bytes>", text.len())),
+    }
+}
+
+// Like `redacted`, in quotes when the text is shown, for error messages
+pub fn shown(text: &str) -> String {
+    match redaction() {
+        Redaction::Off => format!("'{}'", text),
+        _ => redacted(text).into_owned(),
+    }
+}
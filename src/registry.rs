@@ -0,0 +1,281 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, OnceLock};
+
+#[cfg(feature = "csv")]
+use crate::csv_ops;
+use crate::middleware::{Command, Middleware};
+use crate::{run_operation, text_ops, Error, OperationOutput, TextOperation};
+
+// Operations by name, shared by the REPL, the command line and library users
+pub struct OperationRegistry {
+    operations: Vec<Arc<dyn TextOperation>>,
+    // Wrapped around every `execute`, the first added runs outermost
+    middleware: Vec<Box<dyn Middleware>>,
+}
+
+impl OperationRegistry {
+    // A registry without any operations, see `Default` for the built-in ones
+    pub fn new() -> OperationRegistry {
+        OperationRegistry {
+            operations: Vec::new(),
+            middleware: Vec::new(),
+        }
+    }
+
+    // The built-in operations, used where no registry is passed around
+    pub fn builtin() -> &'static OperationRegistry {
+        static BUILTIN: OnceLock<OperationRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(OperationRegistry::default)
+    }
+
+    // Runs `f` with operations that run others, like `--op` of `csv-apply` or
+    // the steps of a pipeline, finding them in this registry instead of the
+    // built-in one. `execute` does this around every command
+    pub fn scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let operations = OperationRegistry {
+            operations: self.operations.clone(),
+            middleware: Vec::new(),
+        };
+        let previous = CURRENT.replace(Some(Rc::new(operations)));
+        // Put back on panics too, the thread may run other commands after
+        struct Restore(Option<Rc<OperationRegistry>>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT.set(self.0.take());
+            }
+        }
+        let _restore = Restore(previous);
+        f()
+    }
+
+    // An operation registered later wins over an earlier one with the same name
+    pub fn register(&mut self, operation: impl TextOperation + 'static) {
+        self.operations.push(Arc::new(operation));
+    }
+
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> OperationRegistry {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    pub fn has_middleware(&self) -> bool {
+        !self.middleware.is_empty()
+    }
+
+    // Runs a command through the middleware chain
+    pub fn execute(&self, command: &Command) -> Result<OperationOutput, Error> {
+        self.call(0, command)
+    }
+
+    // Like `execute`, looking the operation up by name
+    pub fn run(&self, name: &str, input: &str) -> Result<OperationOutput, Error> {
+        self.execute(&Command {
+            operation: self.find(name)?,
+            input: input.to_string(),
+        })
+    }
+
+    fn call(&self, index: usize, command: &Command) -> Result<OperationOutput, Error> {
+        match self.middleware.get(index) {
+            Some(middleware) => {
+                middleware.handle(command, &|command| self.call(index + 1, command))
+            }
+            None => self.scope(|| run_operation(command.operation.as_ref(), &command.input)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn TextOperation>> {
+        let name = name.to_lowercase();
+        self.operations
+            .iter()
+            .rev()
+            .find(|operation| {
+                operation.name() == name || operation.aliases().iter().any(|alias| *alias == name)
+            })
+            .cloned()
+    }
+
+    // Like `get`, failing with the name that wasn't found and similar ones
+    pub fn find(&self, name: &str) -> Result<Arc<dyn TextOperation>, Error> {
+        self.get(name).ok_or_else(|| Error::InvalidOperation {
+            input: name.to_string(),
+            suggestions: self.suggestions(name),
+        })
+    }
+
+    // Names within a couple of typos of the input, closest first
+    fn suggestions(&self, name: &str) -> Vec<String> {
+        let name = name.to_lowercase();
+        let mut close: Vec<(usize, String)> = self
+            .operations()
+            .iter()
+            .filter_map(|operation| {
+                let distance = std::iter::once(operation.name())
+                    .chain(operation.aliases().iter().copied())
+                    .map(|candidate| edit_distance(&name, candidate))
+                    .min()?;
+                (distance <= MAX_SUGGESTION_DISTANCE)
+                    .then(|| (distance, operation.name().to_string()))
+            })
+            .collect();
+        close.sort();
+        close.into_iter().map(|(_, name)| name).collect()
+    }
+
+    // Operations sorted by name, overridden ones are left out
+    pub fn operations(&self) -> Vec<Arc<dyn TextOperation>> {
+        let mut operations: Vec<Arc<dyn TextOperation>> = Vec::new();
+        for operation in self.operations.iter().rev() {
+            if !operations
+                .iter()
+                .any(|seen| seen.name() == operation.name())
+            {
+                operations.push(Arc::clone(operation));
+            }
+        }
+        operations.sort_by(|a, b| a.name().cmp(b.name()));
+        operations
+    }
+}
+
+thread_local! {
+    // The operations of the registry running the current command
+    static CURRENT: RefCell<Option<Rc<OperationRegistry>>> = const { RefCell::new(None) };
+}
+
+// The registry running the current command, the built-in one outside of `scope`
+pub(crate) fn with_current<T>(f: impl FnOnce(&OperationRegistry) -> T) -> T {
+    // Not borrowed while `f` runs, it may start a scope of its own
+    match CURRENT.with_borrow(Option::clone) {
+        Some(registry) => f(&registry),
+        None => f(OperationRegistry::builtin()),
+    }
+}
+
+// Looks up an operation another one runs
+#[cfg_attr(
+    not(any(feature = "csv", feature = "markdown", feature = "scripting")),
+    allow(dead_code)
+)]
+pub(crate) fn find_nested(name: &str) -> Result<Arc<dyn TextOperation>, Error> {
+    with_current(|registry| registry.find(name))
+}
+
+// An operation declared with `#[text_operation]` anywhere in the program,
+// registered by `Default`
+pub struct DeclaredOperation(pub fn() -> Arc<dyn TextOperation>);
+
+inventory::collect!(DeclaredOperation);
+
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+impl Default for OperationRegistry {
+    fn default() -> OperationRegistry {
+        let mut registry = OperationRegistry::new();
+        for operation in text_ops::builtins() {
+            registry.register(operation);
+        }
+        for operation in text_ops::with_options() {
+            registry.register(operation);
+        }
+        for operation in text_ops::extracting() {
+            registry.register(operation);
+        }
+        #[cfg(feature = "csv")]
+        for operation in csv_ops::builtins() {
+            registry.register(operation);
+        }
+        #[cfg(feature = "markdown")]
+        for operation in crate::markdown::builtins() {
+            registry.register(operation);
+        }
+        #[cfg(feature = "xml")]
+        for operation in crate::xml::builtins() {
+            registry.register(operation);
+        }
+        for operation in crate::bytes::builtins() {
+            registry.register(operation);
+        }
+        #[cfg(feature = "scripting")]
+        registry.register(crate::script::ScriptOperation);
+        for declared in inventory::iter::<DeclaredOperation> {
+            registry.operations.push((declared.0)());
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pipeline, Step};
+
+    struct Shout;
+
+    impl TextOperation for Shout {
+        fn name(&self) -> &str {
+            "shout"
+        }
+
+        fn describe(&self) -> &str {
+            "Uppercases text and adds an exclamation mark"
+        }
+
+        fn apply(&self, input: &str) -> Result<String, Error> {
+            Ok(format!("{}!", input.to_uppercase()))
+        }
+
+        fn is_text_operation(&self) -> bool {
+            true
+        }
+    }
+
+    fn with_shout() -> OperationRegistry {
+        let mut registry = OperationRegistry::default();
+        registry.register(Shout);
+        registry
+    }
+
+    #[test]
+    fn pipelines_run_the_operations_of_their_registry() {
+        let pipeline = Pipeline::new(vec![Step {
+            operation: "shout".to_string(),
+            args: String::new(),
+        }]);
+        assert!(pipeline.execute("hi").is_err());
+        assert_eq!(pipeline.execute_with(&with_shout(), "hi").unwrap(), "HI!");
+        assert_eq!(
+            with_shout().scope(|| pipeline.execute("hi")).unwrap(),
+            "HI!"
+        );
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn nested_operations_are_found_in_the_running_registry() {
+        let input = "--column name --op shout --inline 'name\nada'";
+        let output = with_shout().run("csv-apply", input).unwrap();
+        assert_eq!(output.text.trim_end(), "name\nADA!");
+        // Outside of it only the built-in operations are known
+        assert!(OperationRegistry::default()
+            .run("csv-apply", input)
+            .is_err());
+        assert!(find_nested("shout").is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rust_text_transformer::{config_dir, confirmed, with_timeout, Confirmation, OperationRegistry};
+
+use crate::cache::Cache;
+use crate::i18n;
+
+// The `:` commands of the REPL, which stdin lines can use between
+// `<operation> <input>` commands
+pub struct Repl<'a> {
+    registry: &'a OperationRegistry,
+    // `--timeout`, for the commands of `:set`
+    timeout: Option<Duration>,
+    // The macro being recorded, with the commands run so far
+    recording: Option<(String, Vec<String>)>,
+    // Results of `:set`, which `$name` in later commands is replaced with
+    variables: HashMap<String, String>,
+    // What `:cache` reports on, none with `--no-cache`
+    cache: Option<&'a Cache>,
+}
+
+impl<'a> Repl<'a> {
+    pub fn new(
+        registry: &'a OperationRegistry,
+        timeout: Option<Duration>,
+        cache: Option<&'a Cache>,
+    ) -> Repl<'a> {
+        Repl {
+            registry,
+            timeout,
+            recording: None,
+            variables: HashMap::new(),
+            cache,
+        }
+    }
+
+    // `None` for a line that is no REPL command, otherwise the commands it
+    // runs or why it failed. Macros being `played` can only set variables
+    pub fn command(&mut self, line: &str, played: bool) -> Option<Result<Vec<String>, String>> {
+        let line = line.trim();
+        let (command, argument) = match line.strip_prefix(':')?.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (&line[1..], ""),
+        };
+        Some(match command {
+            "set" => self.set(argument).map(|()| {
+                self.ran(line);
+                Vec::new()
+            }),
+            "record" | "stop" | "play" if played => Err(i18n::text(
+                "repl-in-macro",
+                &[("command", &format!(":{}", command))],
+            )),
+            "record" => self.record(argument).map(|()| Vec::new()),
+            "stop" => self.stop().map(|()| Vec::new()),
+            "play" => play(argument),
+            "cache" => self.cache(argument).map(|()| Vec::new()),
+            _ => Err(i18n::text(
+                "repl-unknown-command",
+                &[("command", &format!(":{}", command))],
+            )),
+        })
+    }
+
+    // The line with `$name` and `${name}` replaced by the values of the
+    // variables, `$$` stands for `$` itself. Names that were never set stay as
+    // they are, piped text can have `$PATH` or `${x}` of its own
+    pub fn expand(&self, line: &str) -> String {
+        let mut expanded = String::with_capacity(line.len());
+        let mut rest = line;
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                expanded.push('$');
+                rest = after;
+                continue;
+            }
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.split_once('}') {
+                    Some((name, after)) => (name, after),
+                    None => ("", rest),
+                },
+                None => {
+                    let end = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            // A `$` that starts no variable stays as it is, like in `$5`
+            match self.variables.get(name) {
+                Some(value) => expanded.push_str(value),
+                None => {
+                    expanded.push('$');
+                    continue;
+                }
+            }
+            rest = after;
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    // `name = <operation> <input>` stores the result of the operation
+    fn set(&mut self, argument: &str) -> Result<(), String> {
+        let (name, command) = argument
+            .split_once('=')
+            .map(|(name, command)| (name.trim(), command))
+            .filter(|(name, _)| {
+                name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                    && name.chars().all(is_name_char)
+            })
+            .ok_or_else(|| i18n::text("repl-set-usage", &[]))?;
+        let command = self.expand(command);
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        if parts.len() < 2 {
+            return Err(i18n::text("repl-set-usage", &[]));
+        }
+
+        let run = || self.registry.run(parts[0], &parts[1..].join(" "));
+        let output = match self.timeout {
+            Some(timeout) => with_timeout(timeout, run),
+            None => run(),
+        }
+        .map_err(|e| i18n::error(&e))?;
+        eprintln!("${} = {}", name, output.text);
+        self.variables.insert(name.to_string(), output.text);
+        Ok(())
+    }
+
+    // Adds a command that was run to the macro being recorded
+    pub fn ran(&mut self, command: &str) {
+        if let Some((_, commands)) = &mut self.recording {
+            commands.push(command.trim().to_string());
+        }
+    }
+
+    // `stats` prints how well the cache works, `clear` empties it
+    fn cache(&self, argument: &str) -> Result<(), String> {
+        let cache = self
+            .cache
+            .ok_or_else(|| i18n::text("repl-cache-off", &[]))?;
+        match argument {
+            "stats" => {
+                let stats = cache.stats();
+                eprintln!(
+                    "{}",
+                    i18n::text(
+                        "repl-cache-stats",
+                        &[
+                            ("entries", &stats.entries),
+                            ("bytes", &stats.bytes),
+                            ("hits", &stats.hits),
+                            ("misses", &stats.misses),
+                        ]
+                    )
+                );
+            }
+            "clear" => {
+                cache.clear();
+                eprintln!("{}", i18n::text("repl-cache-cleared", &[]));
+            }
+            _ => return Err(i18n::text("repl-cache-usage", &[])),
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, name: &str) -> Result<(), String> {
+        if let Some((recording, _)) = &self.recording {
+            return Err(i18n::text("repl-already-recording", &[("name", recording)]));
+        }
+        macro_path(name)?;
+        eprintln!("{}", i18n::text("repl-recording", &[("name", &name)]));
+        self.recording = Some((name.to_string(), Vec::new()));
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        let (name, commands) = self
+            .recording
+            .take()
+            .ok_or_else(|| i18n::text("repl-not-recording", &[]))?;
+        let path = macro_path(&name)?;
+        if path.exists()
+            && !confirmed(&Confirmation {
+                operation: ":stop",
+                paths: std::slice::from_ref(&path),
+            })
+        {
+            return Err(i18n::text("repl-not-saved", &[("name", &name)]));
+        }
+        let saved = match path.parent() {
+            Some(dir) => fs::create_dir_all(dir),
+            None => Ok(()),
+        }
+        .and_then(|()| {
+            let mut text = commands.join("\n");
+            text.push('\n');
+            fs::write(&path, text)
+        });
+        if let Err(e) = saved {
+            return Err(i18n::text(
+                "repl-save-failed",
+                &[("name", &name), ("reason", &e)],
+            ));
+        }
+        eprintln!(
+            "{}",
+            i18n::text(
+                "repl-recorded",
+                &[("name", &name), ("count", &commands.len())]
+            )
+        );
+        Ok(())
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// The commands of a recorded macro, run like lines of stdin
+fn play(name: &str) -> Result<Vec<String>, String> {
+    let path = macro_path(name)?;
+    match fs::read_to_string(&path) {
+        Ok(text) => Ok(text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            Err(i18n::text("repl-unknown-macro", &[("name", &name)]))
+        }
+        Err(e) => Err(i18n::text(
+            "read-failed",
+            &[("path", &path.display()), ("reason", &e)],
+        )),
+    }
+}
+
+// `~/.config/text-modifier/macros/<name>`, one command per line
+fn macro_path(name: &str) -> Result<PathBuf, String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(i18n::text("repl-invalid-name", &[("name", &name)]));
+    }
+    let dir = config_dir().ok_or_else(|| i18n::text("repl-no-config-dir", &[]))?;
+    Ok(dir.join("macros").join(name))
+}
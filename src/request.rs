@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use rust_text_transformer::{args, Command, Error, OperationRegistry};
+
+// A command given as JSON by the HTTP API and JSON-RPC, e.g.
+// `{"operation": "csv", "input": "data.csv", "options": {"head": 5}}`
+#[derive(Deserialize)]
+pub struct TransformRequest {
+    pub operation: String,
+    #[serde(default)]
+    pub input: String,
+    #[serde(default)]
+    pub options: Map<String, Value>,
+}
+
+impl TransformRequest {
+    // `options` become `--name value` arguments in front of the input
+    pub fn command(&self, registry: &OperationRegistry) -> Result<Command, Error> {
+        let operation = registry.find(&self.operation)?;
+        let input = format!("{} {}", options(&self.options)?, self.input);
+        Ok(Command {
+            operation,
+            input: input.trim_start().to_string(),
+        })
+    }
+}
+
+// `true` is a flag, `false` and `null` leave the option out and an array
+// repeats it for each value
+pub fn options(options: &Map<String, Value>) -> Result<String, Error> {
+    let mut tokens = Vec::new();
+    for (name, value) in options {
+        let values = match value {
+            Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            match value {
+                Value::Bool(true) => tokens.push(format!("--{}", name)),
+                Value::Bool(false) | Value::Null => {}
+                Value::String(value) => tokens.extend([format!("--{}", name), value.clone()]),
+                Value::Number(value) => tokens.extend([format!("--{}", name), value.to_string()]),
+                _ => {
+                    return Err(Error::invalid(format!(
+                        "Invalid value for option {}, expected a string, number or boolean",
+                        name
+                    )))
+                }
+            }
+        }
+    }
+    Ok(args::join(&tokens))
+}
+
+#[derive(Serialize)]
+pub struct OperationInfo {
+    name: String,
+    aliases: Vec<String>,
+    description: String,
+    // Whether the input is text to transform rather than options and file names
+    text: bool,
+}
+
+impl OperationInfo {
+    pub fn list(registry: &OperationRegistry) -> Vec<OperationInfo> {
+        registry
+            .operations()
+            .iter()
+            .map(|operation| OperationInfo {
+                name: operation.name().to_string(),
+                aliases: operation
+                    .aliases()
+                    .iter()
+                    .map(|alias| alias.to_string())
+                    .collect(),
+                description: operation.describe().to_string(),
+                text: operation.is_text_operation(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+
+use crate::args::OperationArgs;
+use crate::input::{open_source, readable};
+use crate::registry::find_nested;
+use crate::{check_timeout, run_operation, Error, OperationRegistry, TextOperation};
+
+// Runs a rhai script over the input, e.g. `script fix.rhai some text` or
+// `script --eval 'uppercase(input) + "!"' some text`. The text is the `input`
+// variable and the value of the last statement is the output
+pub struct ScriptOperation;
+
+impl TextOperation for ScriptOperation {
+    fn name(&self) -> &str {
+        "script"
+    }
+
+    fn describe(&self) -> &str {
+        "Transforms text with a rhai script"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, Error> {
+        let mut args = OperationArgs::parse(input);
+        let eval = args.value("eval")?;
+        let file = args.value("file")?;
+        let mut rest = args.finish()?;
+
+        let script = match eval {
+            Some(script) => script,
+            None if !rest.is_empty() => {
+                let path = rest.remove(0);
+                fs::read_to_string(readable(Path::new(&path))?).map_err(|e| Error::io(path, e))?
+            }
+            None => {
+                return Err(Error::invalid(
+                    "script requires a script file or --eval <expression>",
+                ))
+            }
+        };
+        let text = match file {
+            Some(path) => {
+                let mut text = String::new();
+                open_source(&path, false)?.read_to_string(&mut text)?;
+                text
+            }
+            None => rest.join(" "),
+        };
+
+        let mut scope = Scope::new();
+        scope.push("input", text);
+        let result = engine().eval_with_scope::<Dynamic>(&mut scope, &script);
+        // A script stopped by the time limit fails with the timeout itself
+        check_timeout()?;
+        result
+            .map(|value| value.to_string())
+            .map_err(|e| Error::invalid(format!("Script error: {}", e)))
+    }
+}
+
+// Every built-in text operation is a function named like the operation with
+// `_` for `-`, e.g. `no_spaces(text)`. `run(name, text)` runs any operation
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.on_progress(|_| check_timeout().err().map(|e| e.to_string().into()));
+
+    for operation in OperationRegistry::builtin().operations() {
+        if !operation.is_text_operation() {
+            continue;
+        }
+        let name = operation.name().replace('-', "_");
+        engine.register_fn(
+            name,
+            move |text: &str| -> Result<String, Box<EvalAltResult>> {
+                operation.apply(text).map_err(|e| e.to_string().into())
+            },
+        );
+    }
+    engine.register_fn(
+        "run",
+        |name: &str, text: &str| -> Result<String, Box<EvalAltResult>> {
+            find_nested(name)
+                .and_then(|operation| run_operation(operation.as_ref(), text))
+                .map(|output| output.into_string())
+                .map_err(|e| e.to_string().into())
+        },
+    );
+    engine
+}
@@ -0,0 +1,615 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time;
+
+use rust_text_transformer::{
+    process_operation_async, set_input_policy, set_max_input_bytes, with_timeout, Command, Error,
+    InputPolicy, Next, OperationOutput, OperationRegistry,
+};
+
+use crate::auth::Tokens;
+use crate::cli::{bytes, positive, CliOptions, SERVER_MAX_INPUT_BYTES};
+use crate::i18n;
+use crate::limits::Limits;
+use crate::{shutdown, SHUTDOWN_POLL};
+
+// Arguments of `serve`, e.g. `serve --tcp 0.0.0.0:7878 --max-connections 16`
+pub struct ServeOptions {
+    tcp: Option<String>,
+    #[cfg(unix)]
+    socket: Option<PathBuf>,
+    #[cfg(feature = "http")]
+    http: Option<String>,
+    #[cfg(feature = "grpc")]
+    grpc: Option<String>,
+    // Connections over the limit are refused with an error line
+    max_connections: usize,
+    // Requests over these limits are refused before they run
+    max_input_bytes: Option<usize>,
+    rate_limit: Option<usize>,
+    max_concurrent: Option<usize>,
+    // Network clients have to give one of its tokens, the Unix socket is
+    // guarded by its file permissions
+    token_file: Option<PathBuf>,
+    // `tokens` of the config file
+    tokens: Vec<String>,
+    // The directory commands may read and write files in, without it they
+    // only take inline input
+    root: Option<PathBuf>,
+}
+
+impl ServeOptions {
+    pub fn parse(args: &[String]) -> Result<ServeOptions, String> {
+        let mut tcp = None;
+        #[cfg(unix)]
+        let mut socket = None;
+        #[cfg(feature = "http")]
+        let mut http = None;
+        #[cfg(feature = "grpc")]
+        let mut grpc = None;
+        let mut max_connections = 64;
+        let mut max_input_bytes = None;
+        let mut rate_limit = None;
+        let mut max_concurrent = None;
+        let mut token_file = None;
+        let mut root = None;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let mut value = || match &inline {
+                Some(value) => Ok(value.clone()),
+                None => args
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| i18n::text("option-requires-value", &[("option", &name)])),
+            };
+
+            match name {
+                "--tcp" => tcp = Some(value()?),
+                #[cfg(unix)]
+                "--socket" => socket = Some(PathBuf::from(value()?)),
+                #[cfg(feature = "http")]
+                "--http" => http = Some(all_interfaces(value()?)),
+                #[cfg(feature = "grpc")]
+                "--grpc" => grpc = Some(all_interfaces(value()?)),
+                "--max-connections" => max_connections = positive(name, &value()?)?,
+                "--max-input-bytes" => max_input_bytes = Some(bytes(name, &value()?)?),
+                "--rate-limit" => rate_limit = Some(positive(name, &value()?)?),
+                "--max-concurrent" => max_concurrent = Some(positive(name, &value()?)?),
+                "--token-file" => token_file = Some(PathBuf::from(value()?)),
+                "--root" => root = Some(PathBuf::from(value()?)),
+                _ => return Err(i18n::text("unknown-option", &[("option", &name)])),
+            }
+        }
+
+        let options = ServeOptions {
+            tcp,
+            #[cfg(unix)]
+            socket,
+            #[cfg(feature = "http")]
+            http,
+            #[cfg(feature = "grpc")]
+            grpc,
+            max_connections,
+            max_input_bytes,
+            rate_limit,
+            max_concurrent,
+            token_file,
+            tokens: Vec::new(),
+            root,
+        };
+        if options.listeners() == 0 {
+            return Err(
+                "serve requires at least one of --tcp, --socket, --http or --grpc".to_string(),
+            );
+        }
+        Ok(options)
+    }
+
+    // Also accepts the tokens of the config file
+    pub fn with_tokens(self, tokens: Vec<String>) -> ServeOptions {
+        ServeOptions { tokens, ..self }
+    }
+
+    fn listeners(&self) -> usize {
+        #[cfg(unix)]
+        let socket = usize::from(self.socket.is_some());
+        #[cfg(not(unix))]
+        let socket = 0;
+        #[cfg(feature = "http")]
+        let http = usize::from(self.http.is_some());
+        #[cfg(not(feature = "http"))]
+        let http = 0;
+        #[cfg(feature = "grpc")]
+        let grpc = usize::from(self.grpc.is_some());
+        #[cfg(not(feature = "grpc"))]
+        let grpc = 0;
+        usize::from(self.tcp.is_some()) + socket + http + grpc
+    }
+}
+
+// Accepts connections speaking the stdin protocol, `<operation> <input>` per
+// line, until Ctrl-C or SIGTERM. Each connection runs up to `--jobs` commands
+// at once with results written back in order. With both `--tcp` and `--socket`
+// the connection limit is shared
+pub fn run(
+    serve: ServeOptions,
+    registry: OperationRegistry,
+    options: CliOptions,
+) -> io::Result<()> {
+    let registry = match options.timeout {
+        Some(timeout) => registry.with_middleware(move |command: &Command, next: Next| {
+            with_timeout(timeout, || next(command))
+        }),
+        None => registry,
+    };
+    // Files the operations read are held to the limit of the requests
+    let max_input_bytes = serve
+        .max_input_bytes
+        .or(options.max_input_bytes)
+        .unwrap_or(SERVER_MAX_INPUT_BYTES);
+    set_max_input_bytes(Some(max_input_bytes));
+    // Commands come from clients, which mustn't reach the files, stdin or
+    // network of the server beyond `--root`
+    set_input_policy(match &serve.root {
+        Some(root) => {
+            InputPolicy::Root(root.canonicalize().map_err(|e| {
+                io::Error::new(e.kind(), format!("--root {}: {}", root.display(), e))
+            })?)
+        }
+        None => InputPolicy::InlineOnly,
+    });
+    let server = Server {
+        registry: Arc::new(registry),
+        slots: Arc::new(Semaphore::new(serve.max_connections)),
+        max_connections: serve.max_connections,
+        jobs: options.jobs,
+        limits: Arc::new(Limits::new(
+            Some(max_input_bytes),
+            serve.rate_limit,
+            serve.max_concurrent,
+        )),
+        tokens: Tokens::load(&serve.tokens, serve.token_file.as_deref())?.map(Arc::new),
+    };
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let mut listeners = Vec::new();
+        if let Some(address) = &serve.tcp {
+            let listener = TcpListener::bind(address)
+                .await
+                .map_err(|e| listen_error(address, e))?;
+            eprintln!("Listening on {}", listener.local_addr()?);
+            listeners.push(Listener::Tcp(listener));
+        }
+        #[cfg(unix)]
+        if let Some(path) = &serve.socket {
+            let listener = bind_socket(path).map_err(|e| listen_error(&path.display(), e))?;
+            eprintln!("Listening on {}", path.display());
+            listeners.push(Listener::Unix(listener, path.clone()));
+        }
+
+        #[cfg(feature = "http")]
+        let http = match &serve.http {
+            Some(address) => {
+                let listener = TcpListener::bind(address)
+                    .await
+                    .map_err(|e| listen_error(address, e))?;
+                eprintln!("Serving HTTP on {}", listener.local_addr()?);
+                let registry = Arc::clone(&server.registry);
+                let limits = Arc::clone(&server.limits);
+                let tokens = server.tokens.clone();
+                Some(tokio::spawn(crate::http::serve(
+                    listener, registry, limits, tokens,
+                )))
+            }
+            None => None,
+        };
+        #[cfg(feature = "grpc")]
+        let grpc = match &serve.grpc {
+            Some(address) => {
+                let listener = TcpListener::bind(address)
+                    .await
+                    .map_err(|e| listen_error(address, e))?;
+                eprintln!("Serving gRPC on {}", listener.local_addr()?);
+                let registry = Arc::clone(&server.registry);
+                let limits = Arc::clone(&server.limits);
+                let tokens = server.tokens.clone();
+                Some(tokio::spawn(crate::grpc::serve(
+                    listener,
+                    registry,
+                    server.jobs,
+                    limits,
+                    tokens,
+                )))
+            }
+            None => None,
+        };
+
+        let accepting = listeners.into_iter().map(|listener| {
+            let server = server.clone();
+            tokio::spawn(async move { server.accept(listener).await })
+        });
+        for accepting in accepting.collect::<Vec<_>>() {
+            accepting.await?;
+        }
+        #[cfg(feature = "http")]
+        if let Some(http) = http {
+            http.await??;
+        }
+        #[cfg(feature = "grpc")]
+        if let Some(grpc) = grpc {
+            grpc.await??;
+        }
+        Ok(())
+    })
+}
+
+// A bare port listens on all interfaces
+#[cfg(any(feature = "http", feature = "grpc"))]
+fn all_interfaces(address: String) -> String {
+    match address.parse::<u16>() {
+        Ok(port) => format!("0.0.0.0:{}", port),
+        Err(_) => address,
+    }
+}
+
+fn listen_error(address: &dyn std::fmt::Display, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("Failed to listen on {}: {}", address, e))
+}
+
+#[derive(Clone)]
+struct Server {
+    registry: Arc<OperationRegistry>,
+    slots: Arc<Semaphore>,
+    max_connections: usize,
+    jobs: usize,
+    limits: Arc<Limits>,
+    tokens: Option<Arc<Tokens>>,
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    // Removed again when the server stops
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+type Reader = Box<dyn AsyncRead + Unpin + Send>;
+type Writer = Box<dyn AsyncWrite + Unpin + Send>;
+type Pending = JoinHandle<Result<OperationOutput, Error>>;
+
+// How long a connection may take to send its `AUTH` line, and how long it may be
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+const AUTH_LINE: usize = 4096;
+
+// An accepted connection
+struct Accepted {
+    reader: Reader,
+    writer: Writer,
+    // A description of the peer for the log
+    peer: String,
+    // Who the rate limit is counted for
+    client: String,
+    // Whether the connection starts with an `AUTH <token>` line when the
+    // server has tokens
+    authenticate: bool,
+}
+
+impl Listener {
+    async fn accept(&self) -> io::Result<Accepted> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                let (reader, writer) = stream.into_split();
+                Ok(Accepted {
+                    reader: Box::new(reader),
+                    writer: Box::new(writer),
+                    peer: peer.to_string(),
+                    client: peer.ip().to_string(),
+                    authenticate: true,
+                })
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, path) => {
+                let (stream, _) = listener.accept().await?;
+                let cred = stream.peer_cred();
+                let peer = match &cred {
+                    Ok(cred) => match cred.pid() {
+                        Some(pid) => format!(
+                            "{} (pid {}, uid {}, gid {})",
+                            path.display(),
+                            pid,
+                            cred.uid(),
+                            cred.gid()
+                        ),
+                        None => format!(
+                            "{} (uid {}, gid {})",
+                            path.display(),
+                            cred.uid(),
+                            cred.gid()
+                        ),
+                    },
+                    Err(_) => path.display().to_string(),
+                };
+                let client = match &cred {
+                    Ok(cred) => format!("uid {}", cred.uid()),
+                    Err(_) => path.display().to_string(),
+                };
+                let (reader, writer) = stream.into_split();
+                Ok(Accepted {
+                    reader: Box::new(reader),
+                    writer: Box::new(writer),
+                    peer,
+                    client,
+                    authenticate: false,
+                })
+            }
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl Server {
+    async fn accept(&self, listener: Listener) {
+        while !shutdown::requested() {
+            let mut accepted = match time::timeout(SHUTDOWN_POLL, listener.accept()).await {
+                Err(_) => continue,
+                Ok(Ok(accepted)) => accepted,
+                // E.g. running out of file descriptors, which passes once connections close
+                Ok(Err(e)) => {
+                    eprintln!("Error accepting a connection: {}", e);
+                    continue;
+                }
+            };
+
+            let Ok(slot) = Arc::clone(&self.slots).try_acquire_owned() else {
+                eprintln!(
+                    "Refused connection from {}, {} connections are open",
+                    accepted.peer, self.max_connections
+                );
+                let refused = Error::TooManyRequests {
+                    reason: format!("{} connections are open", self.max_connections),
+                };
+                tokio::spawn(async move {
+                    let _ = accepted
+                        .writer
+                        .write_all(error_line(&refused).as_bytes())
+                        .await;
+                });
+                continue;
+            };
+
+            let server = self.clone();
+            tokio::spawn(async move {
+                server.connection(accepted).await;
+                drop(slot);
+            });
+        }
+    }
+
+    async fn connection(&self, accepted: Accepted) {
+        let Accepted {
+            reader,
+            mut writer,
+            peer,
+            client,
+            authenticate,
+        } = accepted;
+        eprintln!("Connection from {}", peer);
+        let start = Instant::now();
+        let mut reader = BufReader::new(reader);
+        if let Some(tokens) = self.tokens.as_ref().filter(|_| authenticate) {
+            if !self.authenticate(&mut reader, tokens).await {
+                eprintln!("Refused connection from {}, no valid token", peer);
+                let _ = writer
+                    .write_all(error_line(&Error::Unauthorized).as_bytes())
+                    .await;
+                return;
+            }
+        }
+        match self.lines(reader, writer, &client).await {
+            Ok(()) => eprintln!(
+                "Connection from {} closed after {:.2?}",
+                peer,
+                start.elapsed()
+            ),
+            Err(e) => eprintln!(
+                "Connection from {} failed after {:.2?}: {}",
+                peer,
+                start.elapsed(),
+                e
+            ),
+        }
+    }
+
+    // Reads the `AUTH <token>` line a connection has to start with
+    async fn authenticate(&self, reader: &mut BufReader<Reader>, tokens: &Tokens) -> bool {
+        let mut line = Vec::new();
+        let read = time::timeout(AUTH_TIMEOUT, read_line(reader, Some(AUTH_LINE), &mut line));
+        match read.await {
+            Ok(Ok(Some(true))) => String::from_utf8_lossy(&line)
+                .strip_prefix("AUTH ")
+                .is_some_and(|token| tokens.accepts(token.trim())),
+            _ => false,
+        }
+    }
+
+    // Like `process_lines_async`, with every line checked against the limits
+    async fn lines(
+        &self,
+        mut reader: BufReader<Reader>,
+        mut writer: Writer,
+        client: &str,
+    ) -> Result<(), Error> {
+        let (tx, mut rx) = mpsc::channel::<Result<Pending, Error>>(self.jobs.max(1));
+
+        let read = async move {
+            let max_input_bytes = self.limits.max_input_bytes();
+            let mut line = Vec::new();
+            while let Some(within) = read_line(&mut reader, max_input_bytes, &mut line).await? {
+                let pending = match max_input_bytes {
+                    Some(limit) if !within => Err(Error::TooLarge { limit }),
+                    _ => match self.start(&String::from_utf8_lossy(&line), client) {
+                        Some(pending) => pending,
+                        None => continue,
+                    },
+                };
+                // The writer stopped, e.g. because the peer went away
+                if tx.send(pending).await.is_err() {
+                    break;
+                }
+            }
+            Ok::<(), Error>(())
+        };
+
+        let write = async {
+            while let Some(pending) = rx.recv().await {
+                let result = match pending {
+                    Ok(handle) => handle
+                        .await
+                        .unwrap_or_else(|e| Err(Error::invalid(e.to_string()))),
+                    Err(e) => Err(e),
+                };
+                let text = match result {
+                    Ok(output) => format!(
+                        "Selected operation: {}\n{}\n",
+                        output.operation, output.text
+                    ),
+                    Err(e) => error_line(&e),
+                };
+                writer.write_all(text.as_bytes()).await?;
+                writer.flush().await?;
+            }
+            Ok::<(), Error>(())
+        };
+
+        let (read, write) = tokio::join!(read, write);
+        read.and(write)
+    }
+
+    // Starts the command of a line, `None` for a blank one
+    fn start(&self, line: &str, client: &str) -> Option<Result<Pending, Error>> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (name, input) = match parts.as_slice() {
+            [] => return None,
+            [_] => return Some(Err(Error::invalid("Expected format: <operation> <input>"))),
+            [name, input @ ..] => (name, input.join(" ")),
+        };
+        let started = self.limits.admit(client, line.len()).and_then(|admitted| {
+            let command = Command {
+                operation: self.registry.find(name)?,
+                input,
+            };
+            let registry = Arc::clone(&self.registry);
+            Ok(tokio::spawn(async move {
+                let output = process_operation_async(registry, command).await;
+                drop(admitted);
+                output
+            }))
+        });
+        Some(started)
+    }
+}
+
+// `Error<TAB>code<TAB>class<TAB>message`, on one line like `--porcelain` errors
+fn error_line(e: &Error) -> String {
+    format!(
+        "Error\t{}\t{}\t{}\n",
+        e.code(),
+        e.class(),
+        e.to_string().replace(['\r', '\n'], " ")
+    )
+}
+
+// Reads the next line into `line` without its line break. `false` when it was
+// over `limit` bytes and has been skipped, `None` at the end of the input
+async fn read_line(
+    reader: &mut (impl AsyncBufRead + Unpin),
+    limit: Option<usize>,
+    line: &mut Vec<u8>,
+) -> io::Result<Option<bool>> {
+    line.clear();
+    let limit = limit.map_or(u64::MAX, |limit| limit as u64 + 1);
+    if (&mut *reader).take(limit).read_until(b'\n', line).await? == 0 {
+        return Ok(None);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        return Ok(Some(true));
+    }
+    // The last line, without a line break
+    if (line.len() as u64) < limit {
+        return Ok(Some(true));
+    }
+
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            break;
+        }
+        match available.iter().position(|&byte| byte == b'\n') {
+            Some(end) => {
+                reader.consume(end + 1);
+                break;
+            }
+            None => {
+                let skipped = available.len();
+                reader.consume(skipped);
+            }
+        }
+    }
+    Ok(Some(false))
+}
+
+// A socket file left behind by a server that didn't stop cleanly is replaced,
+// one that still accepts connections is not
+#[cfg(unix)]
+fn bind_socket(path: &std::path::Path) -> io::Result<UnixListener> {
+    match UnixListener::bind(path) {
+        Err(e)
+            if e.kind() == io::ErrorKind::AddrInUse
+                && is_socket(path)
+                && std::os::unix::net::UnixStream::connect(path).is_err() =>
+        {
+            std::fs::remove_file(path)?;
+            UnixListener::bind(path)
+        }
+        bound => bound,
+    }
+}
+
+#[cfg(unix)]
+fn is_socket(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket())
+}
@@ -0,0 +1,68 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use rust_text_transformer::{config_dir, Redaction};
+
+use crate::i18n;
+
+// `~/.config/text-modifier/config.toml`, settings that hold for every run
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    // The memory budget of the CSV renderer and the line sorting operations
+    pub max_memory_mb: Option<usize>,
+    // `hash` or `elide` to keep input text out of error messages
+    pub redact_input: Redaction,
+    // `false` stops counting the operations run in `stats.json`
+    usage_stats: Option<bool>,
+    // Shell commands run when a command line or the commands of stdin are
+    // done, `on_error` when any of them failed
+    pub on_success: Option<String>,
+    pub on_error: Option<String>,
+    // Bearer tokens `serve` accepts, next to those of `--token-file`
+    pub tokens: Vec<String>,
+}
+
+impl Settings {
+    // The settings of the config file, the defaults when there is none
+    pub fn load() -> Result<Settings, String> {
+        let Some(path) = path() else {
+            return Ok(Settings::default());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Settings::default()),
+            Err(e) => return Err(invalid(&path, &e.to_string())),
+        };
+        let settings: Settings = toml::from_str(&text).map_err(|e| invalid(&path, e.message()))?;
+        if settings.max_memory_mb == Some(0) {
+            return Err(invalid(&path, "max_memory_mb has to be at least 1"));
+        }
+        if settings.tokens.iter().any(|token| token.trim().is_empty()) {
+            return Err(invalid(&path, "tokens can't be empty"));
+        }
+        Ok(settings)
+    }
+
+    pub fn usage_stats(&self) -> bool {
+        self.usage_stats.unwrap_or(true)
+    }
+
+    pub fn memory_budget(&self) -> Option<usize> {
+        self.max_memory_mb.map(|mb| mb.saturating_mul(1 << 20))
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+fn invalid(path: &Path, message: &str) -> String {
+    i18n::text(
+        "config-invalid",
+        &[("path", &path.display()), ("message", &message)],
+    )
+}
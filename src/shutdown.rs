@@ -0,0 +1,22 @@
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Exit code of a process stopped by Ctrl-C or SIGTERM
+pub const EXIT_CODE: i32 = 130;
+
+static SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+// The first Ctrl-C or SIGTERM asks for a graceful shutdown, the second aborts
+pub fn install() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if SIGNALS.fetch_add(1, Ordering::SeqCst) == 0 {
+            eprintln!("{}", crate::i18n::text("stopping", &[]));
+        } else {
+            process::exit(EXIT_CODE);
+        }
+    })
+}
+
+pub fn requested() -> bool {
+    SIGNALS.load(Ordering::SeqCst) > 0
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use prettytable::format::consts;
+use prettytable::{Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+
+use rust_text_transformer::config_dir;
+
+use crate::i18n;
+
+// Whether commands are counted, `usage_stats = false` in the config file turns
+// it off
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+// The commands of this run not saved yet
+static PENDING: Mutex<BTreeMap<String, Usage>> = Mutex::new(BTreeMap::new());
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+struct Usage {
+    runs: u64,
+    // Time spent in the operation over all runs
+    seconds: f64,
+}
+
+// `~/.config/text-modifier/stats.json`, the usage of every operation so far
+#[derive(Default, Serialize, Deserialize)]
+struct StatsFile {
+    operations: BTreeMap<String, Usage>,
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+// Counts a processed command, failed ones too since they took the time as well
+pub fn record(operation: &str, duration: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut pending = PENDING.lock().expect("stats lock poisoned");
+    let usage = pending.entry(operation.to_string()).or_default();
+    usage.runs += 1;
+    usage.seconds += duration.as_secs_f64();
+}
+
+// Adds the commands counted so far to the stats file. Runs saving at the same
+// time can lose each other's counts, which is fine for finding the operations
+// worth optimizing
+pub fn save() {
+    let pending = std::mem::take(&mut *PENDING.lock().expect("stats lock poisoned"));
+    if pending.is_empty() {
+        return;
+    }
+    let Some(path) = path() else {
+        return;
+    };
+    let saved = load(&path).and_then(|mut stats| {
+        for (operation, usage) in pending {
+            let total = stats.operations.entry(operation).or_default();
+            total.runs += usage.runs;
+            total.seconds += usage.seconds;
+        }
+        let dir = path
+            .parent()
+            .expect("the stats file is in the config directory");
+        fs::create_dir_all(dir)?;
+        // Written next to it first, a run stopped halfway leaves the old file
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        file.write_all(&serde_json::to_vec(&stats).map_err(io::Error::other)?)?;
+        file.persist(&path).map_err(|e| e.error)?;
+        Ok(())
+    });
+    if let Err(e) = saved {
+        eprintln!(
+            "{}",
+            i18n::text(
+                "stats-save-failed",
+                &[("path", &path.display()), ("reason", &e)]
+            )
+        );
+    }
+}
+
+// `stats` prints the operations run most often with the time they took,
+// `stats --reset` starts counting again
+pub fn run(args: &[String], porcelain: bool) -> Result<(), String> {
+    let path = path().ok_or_else(|| i18n::text("stats-no-config-dir", &[]))?;
+    match args {
+        [] => {}
+        [reset] if reset == "--reset" => {
+            return match fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(i18n::text(
+                    "stats-reset-failed",
+                    &[("path", &path.display()), ("reason", &e)],
+                )),
+                _ => Ok(()),
+            };
+        }
+        _ => return Err(i18n::text("stats-usage", &[])),
+    }
+    let stats = load(&path).map_err(|e| {
+        i18n::text(
+            "stats-read-failed",
+            &[("path", &path.display()), ("reason", &e)],
+        )
+    })?;
+    let mut operations: Vec<(String, Usage)> = stats.operations.into_iter().collect();
+    operations.sort_by(|(a, a_usage), (b, b_usage)| {
+        b_usage
+            .runs
+            .cmp(&a_usage.runs)
+            .then(b_usage.seconds.total_cmp(&a_usage.seconds))
+            .then_with(|| a.cmp(b))
+    });
+
+    if porcelain {
+        for (operation, usage) in &operations {
+            println!("stats\t{}\t{}\t{}", operation, usage.runs, usage.seconds);
+        }
+        return Ok(());
+    }
+    if operations.is_empty() {
+        eprintln!("{}", i18n::text("stats-empty", &[]));
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_format(*consts::FORMAT_NO_LINESEP_WITH_TITLE);
+    table.set_titles(Row::new(
+        ["stats-operation", "stats-runs", "stats-total", "stats-mean"]
+            .iter()
+            .map(|title| Cell::new(&i18n::text(title, &[])))
+            .collect(),
+    ));
+    for (operation, usage) in &operations {
+        let total = Duration::from_secs_f64(usage.seconds.max(0.0));
+        let mean = Duration::from_secs_f64(usage.seconds.max(0.0) / usage.runs.max(1) as f64);
+        table.add_row(Row::new(vec![
+            Cell::new(operation),
+            Cell::new(&usage.runs.to_string()).style_spec("r"),
+            Cell::new(&format!("{:.2?}", total)).style_spec("r"),
+            Cell::new(&format!("{:.2?}", mean)).style_spec("r"),
+        ]));
+    }
+    table
+        .print(&mut io::stdout().lock())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn path() -> Option<PathBuf> {
+    Some(config_dir()?.join("stats.json"))
+}
+
+// The saved stats, none before the first save
+fn load(path: &Path) -> io::Result<StatsFile> {
+    match fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(io::Error::other),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(StatsFile::default()),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::Write;
+
+use crate::{Error, TextOperation};
+
+// An operation writing its result incrementally instead of returning it
+pub type WriteFn = fn(&str, &mut dyn Write) -> Result<(), Error>;
+
+// An operation whose `write_*` function streams its result, with options and
+// file names in its input like the CSV operations
+pub struct StreamingOperation {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    description: &'static str,
+    write: WriteFn,
+}
+
+impl StreamingOperation {
+    pub fn new(
+        name: &'static str,
+        aliases: &'static [&'static str],
+        description: &'static str,
+        write: WriteFn,
+    ) -> StreamingOperation {
+        StreamingOperation {
+            name,
+            aliases,
+            description,
+            write,
+        }
+    }
+}
+
+impl TextOperation for StreamingOperation {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn aliases(&self) -> &[&str] {
+        self.aliases
+    }
+
+    fn describe(&self) -> &str {
+        self.description
+    }
+
+    fn apply(&self, input: &str) -> Result<String, Error> {
+        collect_output(self.write, input)
+    }
+
+    fn write(&self, input: &str, out: &mut dyn Write) -> Result<(), Error> {
+        (self.write)(input, out)
+    }
+}
+
+// Runs a streaming operation into memory for callers that want the whole result
+fn collect_output(write: WriteFn, input: &str) -> Result<String, Error> {
+    let mut output = Vec::new();
+    write(input, &mut output)?;
+
+    let mut output = String::from_utf8(output)
+        .map_err(|e| Error::invalid(format!("Output is not valid UTF-8: {}", e)))?;
+    // Callers add their own line break after the result
+    output.truncate(output.trim_end_matches('\n').len());
+    Ok(output)
+}
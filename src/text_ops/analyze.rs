@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::args::OperationArgs;
+use crate::input::open_source;
+use crate::streaming::StreamingOperation;
+use crate::Error;
+
+// Frequent short words of languages written in Latin script, enough to tell
+// them apart in a few sentences
+#[rustfmt::skip]
+const COMMON_WORDS: &[(&str, &[&str])] = &[
+    ("English", &["the", "and", "of", "to", "is", "in", "that", "it", "was", "for", "with", "you"]),
+    ("German", &["der", "die", "und", "das", "ist", "nicht", "ein", "zu", "den", "mit", "ich", "sie"]),
+    ("French", &["le", "la", "les", "et", "est", "des", "une", "pas", "que", "dans", "pour", "je"]),
+    ("Spanish", &["el", "la", "los", "las", "y", "es", "que", "del", "una", "por", "con", "para"]),
+    ("Italian", &["il", "di", "che", "e", "la", "per", "non", "una", "sono", "del", "gli", "con"]),
+    ("Portuguese", &["o", "os", "de", "que", "e", "do", "da", "não", "uma", "para", "com", "em"]),
+    ("Dutch", &["de", "het", "een", "en", "van", "is", "dat", "niet", "ik", "op", "zijn", "met"]),
+    ("Czech", &["a", "je", "se", "na", "v", "to", "že", "s", "z", "ve", "jsem", "jako"]),
+    ("Polish", &["i", "w", "nie", "się", "na", "jest", "że", "z", "do", "to", "jak", "co"]),
+];
+
+// Fewer common words than this and the language is left unknown
+const MIN_COMMON_WORDS: usize = 3;
+
+pub fn operation() -> StreamingOperation {
+    StreamingOperation::new(
+        "analyze",
+        &[],
+        "Reports character classes, scripts and a rough guess of the language",
+        write_analyze,
+    )
+}
+
+#[derive(Default)]
+struct Counts {
+    characters: usize,
+    uppercase: usize,
+    lowercase: usize,
+    // Letters without case, like those of CJK scripts
+    uncased: usize,
+    digits: usize,
+    punctuation: usize,
+    whitespace: usize,
+    scripts: HashMap<&'static str, usize>,
+    common_words: HashMap<&'static str, usize>,
+    words: usize,
+    // Letters of Ukrainian missing from Russian
+    ukrainian: bool,
+}
+
+// The share of uppercase and lowercase letters, digits, punctuation,
+// whitespace and other characters like emoji among all characters, the scripts of the letters, and a guess of
+// the language from the main script, or for Latin text from its most common
+// words. Meant to triage data, the guess is no language identification
+fn write_analyze(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(
+            "analyze requires a file, - for stdin or --inline TEXT",
+        ));
+    }
+
+    let mut counts = Counts::default();
+    let mut reader = BufReader::new(open_source(&source, inline)?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        counts.line(&line);
+        line.clear();
+    }
+
+    let share = |count: usize| percent(count, counts.characters);
+    writeln!(out, "characters: {}", counts.characters)?;
+    writeln!(out, "uppercase: {}", share(counts.uppercase))?;
+    writeln!(out, "lowercase: {}", share(counts.lowercase))?;
+    if counts.uncased > 0 {
+        writeln!(out, "uncased letters: {}", share(counts.uncased))?;
+    }
+    writeln!(out, "digits: {}", share(counts.digits))?;
+    writeln!(out, "punctuation: {}", share(counts.punctuation))?;
+    writeln!(out, "whitespace: {}", share(counts.whitespace))?;
+    let classified = counts.uppercase
+        + counts.lowercase
+        + counts.uncased
+        + counts.digits
+        + counts.punctuation
+        + counts.whitespace;
+    if classified < counts.characters {
+        writeln!(out, "other: {}", share(counts.characters - classified))?;
+    }
+
+    let letters = counts.letters();
+    let mut scripts: Vec<(&str, usize)> = counts.scripts.iter().map(|(s, n)| (*s, *n)).collect();
+    scripts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let scripts: Vec<String> = scripts
+        .iter()
+        .map(|(script, count)| format!("{} {}", script, percent(*count, letters)))
+        .collect();
+    if !scripts.is_empty() {
+        writeln!(out, "scripts: {}", scripts.join(", "))?;
+    }
+    writeln!(out, "language: {}", counts.language())?;
+    Ok(())
+}
+
+impl Counts {
+    fn letters(&self) -> usize {
+        self.scripts.values().sum()
+    }
+
+    fn line(&mut self, line: &str) {
+        for c in line.chars() {
+            self.characters += 1;
+            if c.is_uppercase() {
+                self.uppercase += 1;
+            } else if c.is_lowercase() {
+                self.lowercase += 1;
+            } else if c.is_alphabetic() {
+                self.uncased += 1;
+            } else if c.is_numeric() {
+                self.digits += 1;
+            } else if c.is_whitespace() {
+                self.whitespace += 1;
+            } else if is_punctuation(c) {
+                self.punctuation += 1;
+            }
+            if c.is_alphabetic() {
+                *self.scripts.entry(script(c)).or_default() += 1;
+            }
+            self.ukrainian |= matches!(c, 'і' | 'ї' | 'є' | 'ґ' | 'І' | 'Ї' | 'Є' | 'Ґ');
+        }
+
+        for word in line.unicode_words() {
+            self.words += 1;
+            let word = word.to_lowercase();
+            for (language, words) in COMMON_WORDS {
+                if words.contains(&word.as_str()) {
+                    *self.common_words.entry(language).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    fn language(&self) -> String {
+        let main = self
+            .scripts
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map(|(script, _)| *script);
+        let language = match main {
+            None => return "unknown, no letters".to_string(),
+            Some("Latin") => return self.latin_language(),
+            Some("Cyrillic") if self.ukrainian => "Ukrainian",
+            Some("Cyrillic") => "Russian or another Cyrillic language",
+            Some("Greek") => "Greek",
+            Some("Arabic") => "Arabic or another language in Arabic script",
+            Some("Hebrew") => "Hebrew",
+            Some("Devanagari") => "Hindi or another language in Devanagari",
+            Some("Thai") => "Thai",
+            Some("Hangul") => "Korean",
+            Some("Kana") => "Japanese",
+            // Japanese mixes Han with kana, enough of them says it's Japanese
+            Some("Han")
+                if self
+                    .scripts
+                    .get("Kana")
+                    .is_some_and(|&kana| kana * 10 >= self.letters()) =>
+            {
+                "Japanese"
+            }
+            Some("Han") => "Chinese",
+            Some(_) => "unknown",
+        };
+        language.to_string()
+    }
+
+    // The language with the most common words among the words of the text
+    fn latin_language(&self) -> String {
+        let best = self
+            .common_words
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)));
+        match best {
+            Some((language, &count)) if count >= MIN_COMMON_WORDS => format!(
+                "{} ({} of {} words are common {} words)",
+                language, count, self.words, language
+            ),
+            _ => "unknown, too few common words".to_string(),
+        }
+    }
+}
+
+fn percent(count: usize, total: usize) -> String {
+    if total == 0 {
+        return "0.0%".to_string();
+    }
+    format!("{:.1}%", count as f64 * 100.0 / total as f64)
+}
+
+// ASCII punctuation and symbols plus the General Punctuation block, enough to
+// tell prose from symbols of other kinds
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c, '\u{a1}'..='\u{bf}' | '\u{2010}'..='\u{205e}' | '\u{3000}'..='\u{303f}' | '\u{ff01}'..='\u{ff0f}')
+}
+
+// The script of a letter by its Unicode block, the common ones only
+fn script(c: char) -> &'static str {
+    match c {
+        'A'..='Z' | 'a'..='z' | '\u{c0}'..='\u{24f}' | '\u{1e00}'..='\u{1eff}' => "Latin",
+        '\u{370}'..='\u{3ff}' | '\u{1f00}'..='\u{1fff}' => "Greek",
+        '\u{400}'..='\u{52f}' => "Cyrillic",
+        '\u{590}'..='\u{5ff}' => "Hebrew",
+        '\u{600}'..='\u{6ff}' | '\u{750}'..='\u{77f}' => "Arabic",
+        '\u{900}'..='\u{97f}' => "Devanagari",
+        '\u{e00}'..='\u{e7f}' => "Thai",
+        '\u{3040}'..='\u{30ff}' | '\u{31f0}'..='\u{31ff}' => "Kana",
+        '\u{1100}'..='\u{11ff}' | '\u{ac00}'..='\u{d7af}' => "Hangul",
+        '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{f900}'..='\u{faff}' => "Han",
+        _ => "Other",
+    }
+}
@@ -0,0 +1,264 @@
+use crate::args::OperationArgs;
+use crate::{shown, Error};
+
+use super::options::{OptionTransform, Transform};
+
+// The length of the IBANs of each country in the IBAN registry
+#[rustfmt::skip]
+const IBAN_LENGTHS: &[(&str, usize)] = &[
+    ("AD", 24), ("AE", 23), ("AL", 28), ("AT", 20), ("AZ", 28), ("BA", 20), ("BE", 16),
+    ("BG", 22), ("BH", 22), ("BI", 27), ("BR", 29), ("BY", 28), ("CH", 21), ("CR", 22),
+    ("CY", 28), ("CZ", 24), ("DE", 22), ("DJ", 27), ("DK", 18), ("DO", 28), ("EE", 20),
+    ("EG", 29), ("ES", 24), ("FI", 18), ("FK", 18), ("FO", 18), ("FR", 27), ("GB", 22),
+    ("GE", 22), ("GI", 23), ("GL", 18), ("GR", 27), ("GT", 28), ("HN", 28), ("HR", 21),
+    ("HU", 28), ("IE", 22), ("IL", 23), ("IQ", 23), ("IS", 26), ("IT", 27), ("JO", 30),
+    ("KW", 30), ("KZ", 20), ("LB", 28), ("LC", 32), ("LI", 21), ("LT", 20), ("LU", 20),
+    ("LV", 21), ("LY", 25), ("MC", 27), ("MD", 24), ("ME", 22), ("MK", 19), ("MN", 20),
+    ("MR", 27), ("MT", 31), ("MU", 30), ("NI", 28), ("NL", 18), ("NO", 15), ("OM", 23),
+    ("PK", 24), ("PL", 28), ("PS", 29), ("PT", 25), ("QA", 29), ("RO", 24), ("RS", 22),
+    ("RU", 33), ("SA", 24), ("SC", 31), ("SD", 18), ("SE", 24), ("SI", 19), ("SK", 24),
+    ("SM", 27), ("SO", 23), ("ST", 25), ("SV", 28), ("TL", 23), ("TN", 24), ("TR", 26),
+    ("UA", 29), ("VA", 22), ("VG", 24), ("XK", 20), ("YE", 30),
+];
+
+pub fn luhn_check() -> OptionTransform {
+    OptionTransform::new(
+        "luhn-check",
+        &["luhn"],
+        "Validates card and other numbers with the Luhn checksum",
+        &[("digits", false)],
+        |args| {
+            let digits_only = args.flag("digits");
+            Ok(Box::new(move |input| {
+                let number = input.trim();
+                let digits = luhn_digits(number)?;
+                if digits_only {
+                    Ok(digits.iter().map(|digit| digit.to_string()).collect())
+                } else {
+                    Ok(number.to_string())
+                }
+            }))
+        },
+    )
+}
+
+pub fn iban_format() -> OptionTransform {
+    OptionTransform::new(
+        "iban-format",
+        &["iban"],
+        "Validates an IBAN and groups it by four characters",
+        &[("compact", false)],
+        configure_iban,
+    )
+}
+
+// The digits of a number passing the Luhn check, spaces and dashes between
+// groups allowed. Errors say why it doesn't, with the check digit it would need
+fn luhn_digits(number: &str) -> Result<Vec<u32>, Error> {
+    let mut digits = Vec::with_capacity(number.len());
+    for c in number.chars() {
+        match c.to_digit(10) {
+            Some(digit) => digits.push(digit),
+            None if c == ' ' || c == '-' => {}
+            None => {
+                return Err(Error::invalid_value(
+                    number,
+                    format!("{} is no digit", shown(&c.to_string())),
+                ))
+            }
+        }
+    }
+    let Some((&last, payload)) = digits
+        .split_last()
+        .filter(|(_, payload)| !payload.is_empty())
+    else {
+        return Err(Error::invalid_value(number, "needs at least two digits"));
+    };
+
+    let expected = check_digit(payload);
+    if last != expected {
+        return Err(Error::invalid_value(
+            number,
+            format!(
+                "fails the Luhn check, the last digit would have to be {}",
+                expected
+            ),
+        ));
+    }
+    Ok(digits)
+}
+
+// The digit that makes the payload pass the Luhn check when appended
+fn check_digit(payload: &[u32]) -> u32 {
+    let sum: u32 = payload
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match (i % 2, digit * 2) {
+            (1, _) => digit,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+// Whether the digits of a number pass the Luhn check, skipping anything else
+pub(crate) fn luhn(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    match digits.split_last() {
+        Some((&last, payload)) if !payload.is_empty() => check_digit(payload) == last,
+        _ => false,
+    }
+}
+
+// `CZ65 0800 0000 1920 0014 5399`, the print format of IBANs, or with
+// `--compact` the electronic one without spaces
+fn configure_iban(args: &mut OperationArgs) -> Result<Transform, Error> {
+    let compact = args.flag("compact");
+    Ok(Box::new(move |input| {
+        let iban = validate_iban(input.trim())?;
+        if compact {
+            return Ok(iban);
+        }
+        let groups: Vec<&str> = iban
+            .as_bytes()
+            .chunks(4)
+            .map(|group| std::str::from_utf8(group).expect("IBANs are ASCII"))
+            .collect();
+        Ok(groups.join(" "))
+    }))
+}
+
+// The IBAN without spaces and in uppercase, when its country, length and
+// ISO 7064 check digits are right
+fn validate_iban(input: &str) -> Result<String, Error> {
+    let iban: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_ascii_uppercase();
+    let iban = iban.strip_prefix("IBAN").unwrap_or(&iban).to_string();
+    if let Some(c) = iban.chars().find(|c| !c.is_ascii_alphanumeric()) {
+        return Err(Error::invalid_value(
+            input,
+            format!("{} is no letter or digit", shown(&c.to_string())),
+        ));
+    }
+    if iban.len() < 4 {
+        return Err(Error::invalid_value(input, "is too short for an IBAN"));
+    }
+
+    let country = &iban[..2];
+    let Some(&(_, length)) = IBAN_LENGTHS.iter().find(|(code, _)| *code == country) else {
+        return Err(Error::invalid_value(
+            input,
+            format!("{} is no country with IBANs", country),
+        ));
+    };
+    if iban.len() != length {
+        return Err(Error::invalid_value(
+            input,
+            format!(
+                "IBANs of {} have {} characters, this one has {}",
+                country,
+                length,
+                iban.len()
+            ),
+        ));
+    }
+    if !iban[2..4].bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::invalid_value(
+            input,
+            "the third and fourth characters are the check digits",
+        ));
+    }
+
+    // The country and check digits go to the end, letters become 10 to 35,
+    // and the number has to leave 1 when divided by 97
+    let remainder = iban[4..]
+        .chars()
+        .chain(iban[..4].chars())
+        .fold(0u32, |remainder, c| {
+            let value = c.to_digit(36).expect("checked to be alphanumeric");
+            let shift = if value > 9 { 100 } else { 10 };
+            (remainder * shift + value) % 97
+        });
+    if remainder != 1 {
+        return Err(Error::invalid_value(input, "the check digits don't match"));
+    }
+    Ok(iban)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luhn_accepts_valid_numbers_with_separators() {
+        for number in [
+            "4111 1111 1111 1111",
+            "5500-0000-0000-0004",
+            "79927398713",
+            "00",
+        ] {
+            assert!(luhn_digits(number).is_ok(), "{}", number);
+            assert!(luhn(number), "{}", number);
+        }
+        assert_eq!(luhn_digits("0 0").unwrap(), vec![0, 0]);
+    }
+
+    #[test]
+    fn luhn_errors_name_the_check_digit() {
+        let e = luhn_digits("79927398710").unwrap_err();
+        assert!(
+            e.to_string().ends_with("the last digit would have to be 3"),
+            "{}",
+            e
+        );
+        assert!(!luhn("79927398710"));
+        assert!(luhn_digits("4111x1111").is_err());
+        assert!(luhn_digits("7").is_err());
+        assert!(!luhn("7"));
+    }
+
+    #[test]
+    fn ibans_are_normalized_when_valid() {
+        assert_eq!(
+            validate_iban("cz65 0800 0000 1920 0014 5399").unwrap(),
+            "CZ6508000000192000145399"
+        );
+        assert_eq!(
+            validate_iban("IBAN DE89-3704-0044-0532-0130-00").unwrap(),
+            "DE89370400440532013000"
+        );
+        assert_eq!(
+            validate_iban("GB82WEST12345698765432").unwrap(),
+            "GB82WEST12345698765432"
+        );
+    }
+
+    #[test]
+    fn invalid_ibans_say_why() {
+        for (iban, reason) in [
+            (
+                "CZ65 0800 0000 1920 0014 5398",
+                "the check digits don't match",
+            ),
+            (
+                "CZ65 0800 0000 1920 0014 539",
+                "IBANs of CZ have 24 characters, this one has 23",
+            ),
+            ("QQ65 0800", "QQ is no country with IBANs"),
+            ("CZ", "is too short for an IBAN"),
+            ("CZ6_0800", "'_' is no letter or digit"),
+            (
+                "CZAB 0800 0000 1920 0014 5399",
+                "the third and fourth characters are the check digits",
+            ),
+        ] {
+            let e = validate_iban(iban).unwrap_err();
+            assert!(e.to_string().ends_with(reason), "{}: {}", iban, e);
+        }
+    }
+}
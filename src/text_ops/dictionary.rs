@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::args::OperationArgs;
+use crate::input::open_source;
+use crate::streaming::StreamingOperation;
+use crate::Error;
+
+pub fn operation() -> StreamingOperation {
+    StreamingOperation::new(
+        "filter-dictionary",
+        &[],
+        "Reports or removes the words of a text missing from a word list",
+        write_filter,
+    )
+}
+
+// `filter-dictionary WORDLIST FILE` prints the words of the file, `-` for
+// stdin or `--inline TEXT`, that are not in the word list, each once in the
+// order they appear. `--positions` prints every one as `line:column: word`
+// instead and `--remove` prints the text without them. Words compare ignoring
+// case unless `--case-sensitive` is given, and `--split-identifiers` checks
+// and reports the parts of `snake_case` and `camelCase` names on their own
+fn write_filter(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let positions = args.flag("positions");
+    let remove = args.flag("remove");
+    let case_sensitive = args.flag("case-sensitive");
+    let split = args.flag("split-identifiers");
+    let inline = args.flag("inline");
+    let mut positional = args.finish()?;
+    if positional.len() < 2 {
+        return Err(Error::invalid(
+            "filter-dictionary requires a word list and a file, - for stdin or --inline TEXT",
+        ));
+    }
+    if positions && remove {
+        return Err(Error::invalid("--positions and --remove can't be combined"));
+    }
+    let source = positional.split_off(1).join(" ");
+    let fold = |word: &str| {
+        if case_sensitive {
+            word.to_string()
+        } else {
+            word.to_lowercase()
+        }
+    };
+    let dictionary = read_words(&positional[0], fold)?;
+    // The words to report in a word of the text, with their offsets in it
+    let unknown = |word: &str| -> Vec<(usize, String)> {
+        let parts = if split {
+            identifier_parts(word)
+        } else {
+            vec![(0, word)]
+        };
+        parts
+            .into_iter()
+            .filter(|(_, part)| !dictionary.contains(&fold(part)))
+            .map(|(offset, part)| (offset, part.to_string()))
+            .collect()
+    };
+
+    let mut reader = BufReader::new(open_source(&source, inline)?);
+    let mut line = String::new();
+    let mut number = 0;
+    let mut reported = HashSet::new();
+    while reader.read_line(&mut line)? > 0 {
+        number += 1;
+        let mut kept = String::with_capacity(line.len());
+        for (index, piece) in line.split_word_bound_indices() {
+            let missing = if is_word(piece) {
+                unknown(piece)
+            } else {
+                Vec::new()
+            };
+            if missing.is_empty() {
+                kept.push_str(piece);
+            }
+            for (offset, word) in missing {
+                if positions {
+                    let column = line[..index + offset].chars().count() + 1;
+                    writeln!(out, "{}:{}: {}", number, column, word)?;
+                } else if !remove && reported.insert(fold(&word)) {
+                    writeln!(out, "{}", word)?;
+                }
+            }
+        }
+        if remove {
+            out.write_all(kept.as_bytes())?;
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+// One word per line, blank lines and `#` comments skipped
+fn read_words(source: &str, fold: impl Fn(&str) -> String) -> Result<HashSet<String>, Error> {
+    let mut words = HashSet::new();
+    for line in BufReader::new(open_source(source, false)?).lines() {
+        let line = line?;
+        let word = line.trim();
+        if !word.is_empty() && !word.starts_with('#') {
+            words.insert(fold(word));
+        }
+    }
+    Ok(words)
+}
+
+// Numbers are no words to check
+fn is_word(piece: &str) -> bool {
+    piece.chars().any(char::is_alphabetic)
+}
+
+// `parseHTTPResponse_v2` becomes `parse`, `HTTP` and `Response` at their
+// offsets: parts end at `_`, `-` and digits, before an uppercase letter
+// following a lowercase one, and before the last capital of a run starting a
+// new word. Single letters like the `v` are left out
+fn identifier_parts(word: &str) -> Vec<(usize, &str)> {
+    let mut parts = Vec::new();
+    let mut start = None;
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    for (i, &(index, c)) in chars.iter().enumerate() {
+        if !c.is_alphabetic() {
+            if let Some(start) = start.take() {
+                parts.push((start, &word[start..index]));
+            }
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|i| chars[i].1);
+        let next_is_lower = chars
+            .get(i + 1)
+            .is_some_and(|(_, next)| next.is_lowercase());
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase() || (previous.is_uppercase() && next_is_lower)
+            });
+        match start {
+            Some(part) if boundary => {
+                parts.push((part, &word[part..index]));
+                start = Some(index);
+            }
+            Some(_) => {}
+            None => start = Some(index),
+        }
+    }
+    if let Some(start) = start {
+        parts.push((start, &word[start..]));
+    }
+    parts.retain(|(_, part)| part.chars().nth(1).is_some());
+    parts
+}
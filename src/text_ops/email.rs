@@ -0,0 +1,100 @@
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::options::{OptionTransform, Transform};
+
+// The domains of Gmail, where dots in the name make no difference
+const GMAIL_DOMAINS: &[&str] = &["gmail.com", "googlemail.com"];
+
+pub fn operation() -> OptionTransform {
+    OptionTransform::new(
+        "email-normalize",
+        &[],
+        "Validates an email address and normalizes it for comparing",
+        &[
+            ("strip-tags", false),
+            ("gmail-dots", false),
+            ("lowercase", false),
+        ],
+        configure,
+    )
+}
+
+// Trims the address and lowercases its domain. `--strip-tags` drops a `+tag`
+// from the name, `--gmail-dots` the dots of Gmail names and `--lowercase` also
+// lowercases the name, which few servers tell apart
+fn configure(args: &mut OperationArgs) -> Result<Transform, Error> {
+    let strip_tags = args.flag("strip-tags");
+    let gmail_dots = args.flag("gmail-dots");
+    let lowercase = args.flag("lowercase");
+
+    Ok(Box::new(move |input| {
+        let address = input.trim();
+        let (name, domain) = validate(address)?;
+        let domain = domain.to_lowercase();
+
+        let mut name = name.to_string();
+        if strip_tags {
+            if let Some((untagged, _)) = name.split_once('+') {
+                name = untagged.to_string();
+            }
+        }
+        if gmail_dots && GMAIL_DOMAINS.contains(&domain.as_str()) {
+            name.retain(|c| c != '.');
+        }
+        if lowercase {
+            name = name.to_lowercase();
+        }
+        if name.is_empty() {
+            return Err(Error::invalid_value(
+                address,
+                "has no name left before the @",
+            ));
+        }
+        Ok(format!("{}@{}", name, domain))
+    }))
+}
+
+// The name and domain of an address with the basic syntax right: one `@`,
+// a name without spaces or dots at its ends, and a domain of at least two
+// labels of letters, digits and dashes
+fn validate(address: &str) -> Result<(&str, &str), Error> {
+    let invalid = |reason: &str| Err(Error::invalid_value(address, reason));
+    let Some((name, domain)) = address.rsplit_once('@') else {
+        return invalid("has no @");
+    };
+
+    if name.is_empty() {
+        return invalid("has no name before the @");
+    }
+    if name.len() > 64 {
+        return invalid("has a name longer than 64 characters");
+    }
+    if name.contains(|c: char| c.is_whitespace() || c.is_control() || "@,;:<>()[]\\\"".contains(c))
+    {
+        return invalid("has a character in the name that needs quoting");
+    }
+    if name.starts_with('.') || name.ends_with('.') || name.contains("..") {
+        return invalid("has a dot at the start or end of the name or two in a row");
+    }
+
+    if domain.len() > 253 {
+        return invalid("has a domain longer than 253 characters");
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 {
+        return invalid("has a domain without a dot");
+    }
+    for label in &labels {
+        if label.is_empty() || label.len() > 63 {
+            return invalid("has an empty or too long part in the domain");
+        }
+        if !label.chars().all(|c| c.is_alphanumeric() || c == '-') {
+            return invalid("has a character in the domain other than letters, digits and -");
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return invalid("has a part of the domain starting or ending with -");
+        }
+    }
+    Ok((name, domain))
+}
@@ -0,0 +1,205 @@
+// Backslash escapes as Rust and C string literals write them: `\n`, `\r`, `\t`,
+// `\0`, `\\` and `\"`, and `\u{..}` for other control characters. Everything
+// else, like non-ASCII letters, stays as it is
+pub(super) fn escape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + input.len() / 8);
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            // `\0` before an octal digit would read back as one octal escape
+            '\0' if chars.peek().is_some_and(|next| ('0'..='7').contains(next)) => {
+                output.push_str("\\x00")
+            }
+            '\0' => output.push_str("\\0"),
+            '\\' => output.push_str("\\\\"),
+            '"' => output.push_str("\\\""),
+            c if needs_escape(c) => output.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+pub(super) fn is_escaped(input: &str) -> bool {
+    !input
+        .chars()
+        .any(|c| matches!(c, '\\' | '"') || needs_escape(c))
+}
+
+// Control characters and the invisible ones that change how text around them
+// is shown, like the bidirectional overrides
+fn needs_escape(c: char) -> bool {
+    c.is_control()
+        || matches!(c, '\u{200b}'..='\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}' | '\u{feff}')
+}
+
+// The escapes of Rust, C, Java and JSON: the single characters of `escape`
+// plus `\'`, `\a`, `\b`, `\f`, `\v` and `\e`, `\xHH`, octal `\NNN`, `\u{..}`,
+// `\uXXXX` with surrogate pairs and `\UXXXXXXXX`. A backslash before a line
+// break joins the lines. Unknown or invalid escapes stay as they are written
+pub(super) fn unescape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('\\') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match unescape_one(&rest[1..]) {
+            Some((c, length)) => {
+                output.extend(c);
+                rest = &rest[1 + length..];
+            }
+            None => {
+                // A backslash before a character that is no escape, or at the end
+                let next = rest[1..].chars().next().map_or(0, char::len_utf8);
+                output.push_str(&rest[..1 + next]);
+                rest = &rest[1 + next..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+// The character of the escape at the start of the text after a backslash, and
+// how much of the text it takes. None for the line breaks it removes
+fn unescape_one(text: &str) -> Option<(Option<char>, usize)> {
+    let simple = match text.chars().next()? {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        '\\' => '\\',
+        '"' => '"',
+        '\'' => '\'',
+        'a' => '\u{7}',
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        'v' => '\u{b}',
+        'e' => '\u{1b}',
+        '\n' => return Some((None, 1)),
+        '\r' if text[1..].starts_with('\n') => return Some((None, 2)),
+        '0'..='7' => {
+            let digits = text
+                .bytes()
+                .take(3)
+                .take_while(|b| (b'0'..=b'7').contains(b))
+                .count();
+            let c = u32::from_str_radix(&text[..digits], 8)
+                .ok()
+                .and_then(char::from_u32)?;
+            return Some((Some(c), digits));
+        }
+        'x' => return hex(&text[1..], 2).map(|(c, length)| (Some(c), 1 + length)),
+        'U' => return hex(&text[1..], 8).map(|(c, length)| (Some(c), 1 + length)),
+        'u' if text[1..].starts_with('{') => {
+            let end = text.find('}')?;
+            let digits = text[2..end].replace('_', "");
+            if digits.is_empty() || digits.len() > 6 {
+                return None;
+            }
+            let c = u32::from_str_radix(&digits, 16)
+                .ok()
+                .and_then(char::from_u32)?;
+            return Some((Some(c), end + 1));
+        }
+        'u' => return utf16(&text[1..]).map(|(c, length)| (Some(c), 1 + length)),
+        _ => return None,
+    };
+    Some((Some(simple), 1))
+}
+
+// Exactly `digits` hex digits naming a character
+fn hex(text: &str, digits: usize) -> Option<(char, usize)> {
+    let code = code(text, digits)?;
+    char::from_u32(code).map(|c| (c, digits))
+}
+
+fn code(text: &str, digits: usize) -> Option<u32> {
+    let hex = text.get(..digits)?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+// `\uXXXX`, where characters outside the Basic Multilingual Plane take a high
+// and a low surrogate, as `\ud83d\ude00` for 😀
+fn utf16(text: &str) -> Option<(char, usize)> {
+    let high = code(text, 4)?;
+    if !(0xd800..0xdc00).contains(&high) {
+        return char::from_u32(high).map(|c| (c, 4));
+    }
+    let low = text[4..].strip_prefix("\\u").and_then(|low| code(low, 4))?;
+    if !(0xdc00..0xe000).contains(&low) {
+        return None;
+    }
+    char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)).map(|c| (c, 10))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_text_reads_back_as_it_was() {
+        for text in [
+            "plain",
+            "line\nbreak\r\n\ttab",
+            "quote \" and \\ backslash",
+            "nul\0 and nul\u{0}7 before a digit",
+            "\u{0}\u{0}12",
+            "bell\u{7} escape\u{1b} delete\u{7f}",
+            "bidi \u{202e}override\u{202c} zero\u{200b}width \u{feff}",
+            "non-ASCII stays: žluťoučký 😀",
+        ] {
+            let escaped = escape(text);
+            assert!(!escaped.chars().any(needs_escape), "{:?}", escaped);
+            assert_eq!(unescape(&escaped), text);
+        }
+    }
+
+    #[test]
+    fn text_is_escaped_when_escape_changes_nothing() {
+        assert!(is_escaped("plain text, žluťoučký"));
+        assert!(!is_escaped("tab\t"));
+        assert!(!is_escaped("\\"));
+        assert!(!is_escaped("\u{202e}"));
+    }
+
+    #[test]
+    fn nul_before_an_octal_digit_is_written_in_hex() {
+        assert_eq!(escape("\u{0}"), "\\0");
+        assert_eq!(escape("\u{0}8"), "\\08");
+        assert_eq!(escape("\u{0}1"), "\\x001");
+    }
+
+    #[test]
+    fn escapes_of_other_languages() {
+        assert_eq!(
+            unescape("\\x41\\101\\u00e9\\u{1F600}\\U0001F600"),
+            "AAé😀😀"
+        );
+        assert_eq!(unescape("\\ud83d\\ude00"), "😀");
+        assert_eq!(
+            unescape("\\a\\b\\f\\v\\e\\'"),
+            "\u{7}\u{8}\u{c}\u{b}\u{1b}'"
+        );
+        assert_eq!(unescape("joined \\\nlines"), "joined lines");
+    }
+
+    #[test]
+    fn unknown_and_invalid_escapes_stay() {
+        for text in [
+            "\\q",
+            "\\xZZ",
+            "\\u{}",
+            "\\u{110000}",
+            "\\ud83d alone",
+            "end\\",
+        ] {
+            assert_eq!(unescape(text), text);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::args::OperationArgs;
+use crate::input::open_source;
+use crate::streaming::StreamingOperation;
+use crate::Error;
+
+use super::redact::email_pattern;
+
+pub fn builtins() -> Vec<StreamingOperation> {
+    let operation = StreamingOperation::new;
+
+    vec![
+        operation(
+            "extract-emails",
+            &[],
+            "Prints the email addresses found in text, one per line",
+            write_emails,
+        ),
+        operation(
+            "extract-urls",
+            &[],
+            "Prints the URLs found in text, one per line",
+            write_urls,
+        ),
+    ]
+}
+
+// Addresses compare case-insensitively for `--unique`, the first spelling is printed
+fn write_emails(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let clean = |email: &str| Some(email.to_string());
+    write_matches(input, out, "extract-emails", email_pattern(), clean, true)
+}
+
+// `http`, `https` and `ftp` URLs and ones starting with `www.`, without the
+// punctuation of the sentence around them
+fn write_urls(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    let pattern = URL.get_or_init(|| {
+        Regex::new(r#"(?i)\b(?:(?:https?|ftp)://|www\.)[^\s<>"'`]+"#)
+            .expect("invalid built-in pattern")
+    });
+    write_matches(input, out, "extract-urls", pattern, trim_url, false)
+}
+
+// Scans the file named in the input, `-` for stdin or with `--inline` the text
+// itself, line by line and prints every match, only the first of equal ones
+// with `--unique`, ignoring case with `fold_case`
+fn write_matches(
+    input: &str,
+    out: &mut dyn Write,
+    operation: &str,
+    pattern: &Regex,
+    clean: fn(&str) -> Option<String>,
+    fold_case: bool,
+) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let unique = args.flag("unique");
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(format!(
+            "{} requires a file, - for stdin or --inline TEXT",
+            operation
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    let mut reader = BufReader::new(open_source(&source, inline)?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        for found in pattern.find_iter(&line) {
+            let Some(found) = clean(found.as_str()) else {
+                continue;
+            };
+            let key = if fold_case {
+                found.to_lowercase()
+            } else {
+                found.clone()
+            };
+            if unique && !seen.insert(key) {
+                continue;
+            }
+            writeln!(out, "{}", found)?;
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+// Drops what ends the sentence rather than the URL, like `.` or `,`, and a
+// `)` closing a parenthesis opened before the URL
+fn trim_url(url: &str) -> Option<String> {
+    let mut url = url.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+    while let Some(inner) = url.strip_suffix(')') {
+        if url.matches('(').count() >= url.matches(')').count() {
+            break;
+        }
+        url = inner.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+    }
+    let host = url.split_once("://").map_or(url, |(_, rest)| rest);
+    (!host.is_empty() && host != "www.").then(|| url.to_string())
+}
@@ -0,0 +1,117 @@
+use std::io::{BufRead, BufReader, Write};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::args::OperationArgs;
+use crate::input::open_source;
+use crate::streaming::StreamingOperation;
+use crate::Error;
+
+pub fn operation() -> StreamingOperation {
+    StreamingOperation::new(
+        "lint-text",
+        &["lint"],
+        "Flags doubled words, trailing whitespace, mixed indentation and long lines",
+        write_lint,
+    )
+}
+
+// Prints a `line:column: problem` line for each finding, prefixed with the
+// file name like compilers do so editors can jump to it, and fails when any
+// were found. `--max-length N` sets the longest line allowed, 100 by default
+fn write_lint(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let max_length: usize = args.parsed("max-length")?.unwrap_or(100);
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(
+            "lint-text requires a file, - for stdin or --inline TEXT",
+        ));
+    }
+    if max_length == 0 {
+        return Err(Error::invalid("--max-length must be at least 1"));
+    }
+    let prefix = if inline || source == "-" {
+        String::new()
+    } else {
+        format!("{}:", source)
+    };
+
+    let mut reader = BufReader::new(open_source(&source, inline)?);
+    let mut bytes = Vec::new();
+    let mut number = 0;
+    let mut problems = 0;
+    // The last word of the lines before, doubled words can span a line break
+    let mut previous: Option<String> = None;
+    while reader.read_until(b'\n', &mut bytes)? > 0 {
+        number += 1;
+        let line = String::from_utf8_lossy(&bytes);
+        let line = line.trim_end_matches(['\n', '\r']);
+        for (column, problem) in lint_line(line, max_length, &mut previous) {
+            writeln!(out, "{}{}:{}: {}", prefix, number, column, problem)?;
+            problems += 1;
+        }
+        bytes.clear();
+    }
+
+    if problems > 0 {
+        return Err(Error::invalid(format!(
+            "Found {} problems in {} lines",
+            problems, number
+        )));
+    }
+    Ok(())
+}
+
+// The problems of a line with their columns, counted in characters from 1
+fn lint_line(line: &str, max_length: usize, previous: &mut Option<String>) -> Vec<(usize, String)> {
+    let mut problems = Vec::new();
+    let column = |index: usize| line[..index].chars().count() + 1;
+
+    let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+    if let Some(tab) = line[..indent].find(" \t") {
+        problems.push((
+            column(tab + 1),
+            "tab after spaces in the indentation".to_string(),
+        ));
+    }
+
+    // Words separated by nothing but whitespace, ignoring case
+    let mut gap_is_space = true;
+    for (index, piece) in line.split_word_bound_indices() {
+        if !piece.chars().any(char::is_alphanumeric) {
+            gap_is_space &= piece.chars().all(char::is_whitespace);
+            continue;
+        }
+        let word = piece.to_lowercase();
+        if gap_is_space
+            && previous.as_deref() == Some(word.as_str())
+            && piece.chars().any(char::is_alphabetic)
+        {
+            problems.push((column(index), format!("doubled word '{}'", piece)));
+        }
+        *previous = Some(word);
+        gap_is_space = true;
+    }
+    if !gap_is_space {
+        *previous = None;
+    }
+
+    let content = line.trim_end();
+    if content.len() < line.len() {
+        problems.push((column(content.len()), "trailing whitespace".to_string()));
+    }
+
+    let length = line.chars().count();
+    if length > max_length {
+        problems.push((
+            max_length + 1,
+            format!(
+                "line is {} characters long, more than {}",
+                length, max_length
+            ),
+        ));
+    }
+    problems
+}
@@ -0,0 +1,345 @@
+mod analyze;
+mod checksum;
+mod dictionary;
+mod email;
+mod escape;
+mod extract;
+mod lint;
+mod options;
+mod palindrome;
+#[cfg(feature = "phone")]
+mod phone;
+mod redact;
+mod sort;
+
+use std::borrow::Cow;
+
+use convert_case::{Case, Casing};
+use memchr::{memchr, memchr_iter};
+use slug::slugify;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::streaming::StreamingOperation;
+use crate::{Error, TextOperation};
+
+pub use options::OptionTransform;
+
+// An operation rewriting its input text as a whole
+pub struct TextTransform {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    description: &'static str,
+    transform: fn(&str) -> String,
+    // True when the transform would return the input as it is
+    unchanged: fn(&str) -> bool,
+}
+
+impl TextOperation for TextTransform {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn aliases(&self) -> &[&str] {
+        self.aliases
+    }
+
+    fn describe(&self) -> &str {
+        self.description
+    }
+
+    fn apply(&self, input: &str) -> Result<String, Error> {
+        Ok((self.transform)(input))
+    }
+
+    fn apply_cow<'a>(&self, input: &'a str) -> Result<Cow<'a, str>, Error> {
+        if (self.unchanged)(input) {
+            Ok(Cow::Borrowed(input))
+        } else {
+            Ok(Cow::Owned((self.transform)(input)))
+        }
+    }
+
+    fn is_text_operation(&self) -> bool {
+        true
+    }
+}
+
+// The operations scanning text for what they print
+pub fn extracting() -> Vec<StreamingOperation> {
+    let mut operations = extract::builtins();
+    operations.push(analyze::operation());
+    operations.push(dictionary::operation());
+    operations.push(lint::operation());
+    operations.push(sort::operation());
+    operations
+}
+
+// The text operations taking options in front of the text
+pub fn with_options() -> Vec<OptionTransform> {
+    vec![
+        checksum::iban_format(),
+        checksum::luhn_check(),
+        email::operation(),
+        #[cfg(feature = "phone")]
+        phone::operation(),
+        redact::operation(),
+    ]
+}
+
+pub fn builtins() -> Vec<TextTransform> {
+    vec![
+        TextTransform {
+            name: "anagram-key",
+            aliases: &[],
+            description: "Sorts the letters of each word, giving anagrams equal keys",
+            transform: palindrome::anagram_key,
+            unchanged: |_| false,
+        },
+        TextTransform {
+            name: "camelcase",
+            aliases: &["camel"],
+            description: "Converts text to camelCase format",
+            transform: |input| input.to_case(Case::Camel),
+            unchanged: |_| false,
+        },
+        TextTransform {
+            name: "escape",
+            aliases: &[],
+            description:
+                "Writes line breaks, tabs, quotes and control characters as backslash escapes",
+            transform: escape::escape,
+            unchanged: escape::is_escaped,
+        },
+        TextTransform {
+            name: "is-palindrome",
+            aliases: &["palindrome"],
+            description: "Tells whether text reads the same backwards, ignoring case, accents and punctuation",
+            transform: palindrome::is_palindrome,
+            unchanged: |_| false,
+        },
+        TextTransform {
+            name: "lowercase",
+            aliases: &["lower"],
+            description: "Converts text to lowercase",
+            transform: lowercase,
+            unchanged: is_lowercase,
+        },
+        TextTransform {
+            name: "no-spaces",
+            aliases: &[],
+            description: "Removes all spaces from text",
+            transform: remove_spaces,
+            unchanged: |input| memchr(b' ', input.as_bytes()).is_none(),
+        },
+        TextTransform {
+            name: "sentences",
+            aliases: &[],
+            description: "Splits text into one sentence per line",
+            transform: sentences,
+            unchanged: |_| false,
+        },
+        TextTransform {
+            name: "slugify",
+            aliases: &["slug"],
+            description: "Creates URL-friendly slugs",
+            transform: |input| slugify(input),
+            unchanged: is_slug,
+        },
+        TextTransform {
+            name: "snakecase",
+            aliases: &["snake"],
+            description: "Converts text to snake_case format",
+            transform: |input| input.to_case(Case::Snake),
+            unchanged: |_| false,
+        },
+        TextTransform {
+            name: "unescape",
+            aliases: &[],
+            description: "Turns backslash escapes like \\n and \\u{1F600} into the characters",
+            transform: escape::unescape,
+            unchanged: |input| memchr(b'\\', input.as_bytes()).is_none(),
+        },
+        TextTransform {
+            name: "uppercase",
+            aliases: &["upper"],
+            description: "Converts text to UPPERCASE",
+            transform: uppercase,
+            unchanged: is_uppercase,
+        },
+        TextTransform {
+            name: "words",
+            aliases: &[],
+            description: "Splits text into one word per line",
+            transform: |input| input.unicode_words().collect::<Vec<_>>().join("\n"),
+            unchanged: |_| false,
+        },
+        TextTransform {
+            name: "xml-escape",
+            aliases: &[],
+            description: "Escapes the characters XML gives a meaning",
+            transform: xml_escape,
+            unchanged: |input| !input.contains(['&', '<', '>', '"', '\'']),
+        },
+        TextTransform {
+            name: "xml-unescape",
+            aliases: &[],
+            description: "Replaces XML entities and character references",
+            transform: xml_unescape,
+            unchanged: |input| memchr(b'&', input.as_bytes()).is_none(),
+        },
+    ]
+}
+
+// Pure ASCII input, found with a fast scan, skips the Unicode case tables
+pub(crate) fn lowercase(input: &str) -> String {
+    if input.is_ascii() {
+        input.to_ascii_lowercase()
+    } else {
+        input.to_lowercase()
+    }
+}
+
+pub(crate) fn uppercase(input: &str) -> String {
+    if input.is_ascii() {
+        input.to_ascii_uppercase()
+    } else {
+        input.to_uppercase()
+    }
+}
+
+fn is_lowercase(input: &str) -> bool {
+    if input.is_ascii() {
+        !input.bytes().any(|b| b.is_ascii_uppercase())
+    } else {
+        input.chars().all(|c| c.to_lowercase().eq([c]))
+    }
+}
+
+fn is_uppercase(input: &str) -> bool {
+    if input.is_ascii() {
+        !input.bytes().any(|b| b.is_ascii_lowercase())
+    } else {
+        input.chars().all(|c| c.to_uppercase().eq([c]))
+    }
+}
+
+// Copies the runs between spaces, which memchr finds many bytes at a time
+fn remove_spaces(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut start = 0;
+    for space in memchr_iter(b' ', input.as_bytes()) {
+        output.push_str(&input[start..space]);
+        start = space + 1;
+    }
+    output.push_str(&input[start..]);
+    output
+}
+
+// Non-empty runs of lowercase letters and digits joined by single dashes
+fn is_slug(input: &str) -> bool {
+    input.split('-').all(|part| {
+        !part.is_empty()
+            && part
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+    })
+}
+
+// Abbreviations whose period ends no sentence even before a capital letter.
+// `etc.` is left out, it ends sentences as often as not
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "e.g", "i.e", "cf", "inc", "ltd",
+    "co", "corp", "no", "fig", "approx", "dept", "z.b", "bzw", "usw", "nr", "tj", "např", "atd",
+];
+
+// The sentences of Unicode text segmentation, without breaks after
+// abbreviations and initials like `J. R. R. Tolkien`
+fn sentences(input: &str) -> String {
+    let mut sentences: Vec<String> = Vec::new();
+    let mut pending = String::new();
+    for sentence in input.unicode_sentences() {
+        pending.push_str(sentence);
+        let trimmed = pending.trim_end();
+        let last = trimmed
+            .rsplit(char::is_whitespace)
+            .next()
+            .unwrap_or("")
+            .trim_start_matches(|c: char| !c.is_alphanumeric());
+        let continues = last.strip_suffix('.').is_some_and(|word| {
+            let mut chars = word.chars();
+            let initial = chars.next().is_some_and(char::is_uppercase) && chars.next().is_none();
+            initial || ABBREVIATIONS.contains(&lowercase(word).as_str())
+        });
+        if !continues {
+            sentences.push(normalize_whitespace(&pending));
+            pending.clear();
+        }
+    }
+    if !pending.trim().is_empty() {
+        sentences.push(normalize_whitespace(&pending));
+    }
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences.join("\n")
+}
+
+// Line breaks inside a sentence become spaces, so it stays on its line
+fn normalize_whitespace(sentence: &str) -> String {
+    sentence.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// All five predefined entities, so the text is safe in content as well as in
+// attributes quoted either way. Unlike HTML escaping `'` becomes `&apos;`
+fn xml_escape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + input.len() / 8);
+    for c in input.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&apos;"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+// The predefined entities and `&#NN;` / `&#xHH;` references. Others, like the
+// named entities of HTML, are no XML and stay as they are
+fn xml_unescape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let replaced = rest[1..].find(';').and_then(|end| {
+            let character = match &rest[1..end + 1] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                reference => reference
+                    .strip_prefix("#x")
+                    .or_else(|| reference.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| reference.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            character.map(|character| (character, end + 2))
+        });
+        match replaced {
+            Some((character, length)) => {
+                output.push(character);
+                rest = &rest[length..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
@@ -0,0 +1,171 @@
+use std::io::{BufReader, Write};
+
+use crate::args::{self, OperationArgs};
+use crate::input::open_source;
+use crate::{process_stream, Error, TextOperation};
+
+// The configured transform of an `OptionTransform`, applied to the text or
+// each line of `--file`
+pub type Transform = Box<dyn Fn(&str) -> Result<String, Error> + Send + Sync>;
+
+// A text operation taking options in front of the text, e.g.
+// `redact --mask Call me at 555-123-4567`. Only its own options are taken off
+// the front, the text after them stays as it was written. `--file PATH`
+// transforms a file line by line instead, `-` for stdin
+pub struct OptionTransform {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    description: &'static str,
+    // The names of the options and whether they take a value
+    options: &'static [(&'static str, bool)],
+    configure: fn(&mut OperationArgs) -> Result<Transform, Error>,
+}
+
+impl OptionTransform {
+    pub fn new(
+        name: &'static str,
+        aliases: &'static [&'static str],
+        description: &'static str,
+        options: &'static [(&'static str, bool)],
+        configure: fn(&mut OperationArgs) -> Result<Transform, Error>,
+    ) -> OptionTransform {
+        OptionTransform {
+            name,
+            aliases,
+            description,
+            options,
+            configure,
+        }
+    }
+
+    // The transform configured by the leading options, the file they name and the text
+    fn parse<'a>(&self, input: &'a str) -> Result<(Transform, Option<String>, &'a str), Error> {
+        let (options, text) = self.split(input);
+        let mut args = OperationArgs::parse(&args::join(&options));
+        let file = args.value("file")?;
+        let transform = (self.configure)(&mut args)?;
+        args.finish()?;
+        if file.is_some() && !text.is_empty() {
+            return Err(Error::invalid(format!(
+                "{} takes --file or text, not both",
+                self.name
+            )));
+        }
+        Ok((transform, file, text))
+    }
+
+    // The known options at the start of the input and the text after them. A
+    // bare `--` ends the options early, for text starting with one
+    fn split<'a>(&self, input: &'a str) -> (Vec<String>, &'a str) {
+        let mut options = Vec::new();
+        let mut rest = input.trim_start();
+        loop {
+            let (token, after) = next_token(rest);
+            if token == "--" {
+                return (options, after.trim_start());
+            }
+            let Some(option) = token.strip_prefix("--") else {
+                break;
+            };
+            let (name, inline_value) = match option.split_once('=') {
+                Some((name, _)) => (name, true),
+                None => (option, false),
+            };
+            let takes_value = match name {
+                "file" => true,
+                name => match self.options.iter().find(|(option, _)| *option == name) {
+                    Some(&(_, takes_value)) => takes_value,
+                    None => break,
+                },
+            };
+            options.push(token);
+            rest = after.trim_start();
+            if takes_value && !inline_value {
+                let (value, after) = next_token(rest);
+                options.push(value);
+                rest = after.trim_start();
+            }
+        }
+        (options, rest)
+    }
+}
+
+impl TextOperation for OptionTransform {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn aliases(&self) -> &[&str] {
+        self.aliases
+    }
+
+    fn describe(&self) -> &str {
+        self.description
+    }
+
+    fn apply(&self, input: &str) -> Result<String, Error> {
+        let (transform, file, text) = self.parse(input)?;
+        if file.is_none() {
+            return transform(text);
+        }
+
+        let mut output = Vec::new();
+        self.write(input, &mut output)?;
+        let mut output = String::from_utf8(output)
+            .map_err(|e| Error::invalid(format!("Output is not valid UTF-8: {}", e)))?;
+        output.truncate(output.trim_end_matches('\n').len());
+        Ok(output)
+    }
+
+    fn write(&self, input: &str, out: &mut dyn Write) -> Result<(), Error> {
+        let (transform, file, text) = self.parse(input)?;
+        match file {
+            Some(source) => {
+                let reader = BufReader::new(open_source(&source, false)?);
+                process_stream(&Configured(transform), reader, out)
+            }
+            None => {
+                writeln!(out, "{}", transform(text)?)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn is_text_operation(&self) -> bool {
+        true
+    }
+}
+
+// A transform run over the lines of a file like any other text operation
+struct Configured(Transform);
+
+impl TextOperation for Configured {
+    fn name(&self) -> &str {
+        "configured"
+    }
+
+    fn describe(&self) -> &str {
+        ""
+    }
+
+    fn apply(&self, input: &str) -> Result<String, Error> {
+        (self.0)(input)
+    }
+}
+
+// The first token of the input, with its quotes removed like `OperationArgs`
+// does, and the input after it
+fn next_token(input: &str) -> (String, &str) {
+    let mut token = String::new();
+    let mut quote = None;
+    for (i, c) in input.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => token.push(c),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c.is_whitespace() => return (token, &input[i..]),
+            None => token.push(c),
+        }
+    }
+    (token, "")
+}
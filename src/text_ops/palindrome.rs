@@ -0,0 +1,36 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+// `true` when the letters and digits of the text read the same backwards,
+// ignoring case, accents, spaces and punctuation, as in
+// `Was it a car or a cat I saw?`
+pub(super) fn is_palindrome(input: &str) -> String {
+    let letters = folded(input);
+    let palindrome = !letters.is_empty() && letters.iter().eq(letters.iter().rev());
+    palindrome.to_string()
+}
+
+// Each word with its letters sorted, so `Listen` and `silent` both become
+// `eilnst` and anagrams end up with equal keys
+pub(super) fn anagram_key(input: &str) -> String {
+    input
+        .unicode_words()
+        .map(|word| {
+            let mut letters = folded(word);
+            letters.sort_unstable();
+            letters.into_iter().collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The lowercase letters and digits of the text, accents taken off by
+// decomposing them and dropping the combining marks
+fn folded(text: &str) -> Vec<char> {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
@@ -0,0 +1,70 @@
+use phonenumber::country::Id;
+use phonenumber::Mode;
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::options::{OptionTransform, Transform};
+
+pub fn operation() -> OptionTransform {
+    OptionTransform::new(
+        "phone",
+        &[],
+        "Parses a phone number and formats it as E.164 or another format",
+        &[("region", true), ("format", true)],
+        configure,
+    )
+}
+
+// `--region CZ` is the country of numbers written without `+` and a country
+// code, `--format e164|international|national|rfc3966` how they are printed.
+// A value that can't be read as a number and a number that can't exist in its
+// country fail with different reasons
+fn configure(args: &mut OperationArgs) -> Result<Transform, Error> {
+    let region = match args.value("region")? {
+        Some(region) => Some(region.to_ascii_uppercase().parse::<Id>().map_err(|_| {
+            Error::invalid(format!(
+                "Unknown --region '{}', expected a country code like CZ or US",
+                region
+            ))
+        })?),
+        None => None,
+    };
+    let mode = match args.value("format")?.as_deref() {
+        None | Some("e164") => Mode::E164,
+        Some("international") => Mode::International,
+        Some("national") => Mode::National,
+        Some("rfc3966") => Mode::Rfc3966,
+        Some(other) => {
+            return Err(Error::invalid(format!(
+                "Unknown --format '{}', expected e164, international, national or rfc3966",
+                other
+            )))
+        }
+    };
+
+    Ok(Box::new(move |input| {
+        let value = input.trim();
+        let number = phonenumber::parse(region, value).map_err(|e| {
+            match (region, e) {
+                // Without a region only numbers with `+` and a country code can be read
+                (None, phonenumber::ParseError::InvalidCountryCode) => Error::invalid_value(
+                    value,
+                    "has no country code, start it with + or give --region",
+                ),
+                (_, e) => Error::invalid_value(value, format!("is no phone number, {}", e)),
+            }
+        })?;
+        if !number.is_valid() {
+            let country = match number.country().id() {
+                Some(id) => format!("in {}", id.as_ref()),
+                None => format!("with the country code +{}", number.code().value()),
+            };
+            return Err(Error::invalid_value(
+                value,
+                format!("can be read but is no valid number {}", country),
+            ));
+        }
+        Ok(number.format().mode(mode).to_string())
+    }))
+}
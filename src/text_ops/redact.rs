@@ -0,0 +1,149 @@
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use crate::args::OperationArgs;
+use crate::Error;
+
+use super::checksum::luhn;
+use super::options::{OptionTransform, Transform};
+
+const PLACEHOLDER: &str = "[REDACTED]";
+
+// What `redact` finds without `--only`
+const KINDS: &[&str] = &["email", "phone", "card"];
+
+pub fn operation() -> OptionTransform {
+    OptionTransform::new(
+        "redact",
+        &[],
+        "Masks emails, phone numbers and card numbers",
+        &[
+            ("placeholder", true),
+            ("mask", false),
+            ("only", true),
+            ("pattern", true),
+        ],
+        configure,
+    )
+}
+
+// `--placeholder TEXT` replaces matches instead of `[REDACTED]`, `--mask`
+// replaces each of their letters and digits with `X` and keeps the rest, so
+// `555-123-4567` becomes `XXX-XXX-XXXX`. `--only email,phone,card` picks what
+// is found and `--pattern REGEX`, given any number of times, adds more
+fn configure(args: &mut OperationArgs) -> Result<Transform, Error> {
+    let placeholder = args.value("placeholder")?;
+    let mask = args.flag("mask");
+    if mask && placeholder.is_some() {
+        return Err(Error::invalid("--mask and --placeholder can't be combined"));
+    }
+    let kinds = match args.value("only")? {
+        Some(only) => {
+            let kinds: Vec<String> = only
+                .split(',')
+                .map(|kind| kind.trim().to_string())
+                .collect();
+            if let Some(unknown) = kinds.iter().find(|kind| !KINDS.contains(&kind.as_str())) {
+                return Err(Error::invalid(format!(
+                    "Unknown kind for --only: {}, expected {}",
+                    unknown,
+                    KINDS.join(", ")
+                )));
+            }
+            kinds
+        }
+        None => KINDS.iter().map(|kind| kind.to_string()).collect(),
+    };
+
+    // Custom patterns go first, they are the more specific ones
+    let mut patterns = Vec::new();
+    while let Some(pattern) = args.value("pattern")? {
+        let pattern = Regex::new(&pattern)
+            .map_err(|e| Error::invalid(format!("Invalid --pattern '{}': {}", pattern, e)))?;
+        patterns.push((pattern, anything as Check));
+    }
+    // Cards before phones, which would otherwise take parts of card numbers
+    for kind in ["email", "card", "phone"] {
+        if kinds.iter().any(|wanted| wanted == kind) {
+            let (pattern, check) = builtin(kind);
+            patterns.push((pattern.clone(), check));
+        }
+    }
+
+    let placeholder = placeholder.unwrap_or_else(|| PLACEHOLDER.to_string());
+    Ok(Box::new(move |text: &str| {
+        let mut text = text.to_string();
+        for (pattern, check) in &patterns {
+            let replaced = pattern.replace_all(&text, |captures: &Captures| {
+                let found = &captures[0];
+                if !check(found) {
+                    found.to_string()
+                } else if mask {
+                    masked(found)
+                } else {
+                    placeholder.clone()
+                }
+            });
+            text = replaced.into_owned();
+        }
+        Ok(text)
+    }))
+}
+
+type Check = fn(&str) -> bool;
+
+fn anything(_: &str) -> bool {
+    true
+}
+
+// The pattern of a kind and the check a match has to pass, both compiled once
+fn builtin(kind: &str) -> (&'static Regex, Check) {
+    static CARD: OnceLock<Regex> = OnceLock::new();
+    static PHONE: OnceLock<Regex> = OnceLock::new();
+    let compile = |pattern: &str| Regex::new(pattern).expect("invalid built-in pattern");
+    match kind {
+        "email" => (email_pattern(), anything),
+        // 13 to 19 digits, in groups separated by spaces or dashes
+        "card" => (
+            CARD.get_or_init(|| compile(r"\b\d(?:[ -]?\d){12,18}\b")),
+            |found| luhn(found),
+        ),
+        // An optional country code and area code in parentheses, then groups
+        // of digits, never part of a longer number
+        _ => (
+            PHONE.get_or_init(|| {
+                compile(
+                    r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{1,4}\)[ .-]?|\b)\d{2,4}(?:[ .-]?\d{2,4}){1,4}\b",
+                )
+            }),
+            is_phone,
+        ),
+    }
+}
+
+// Addresses like `jane.doe+tag@mail.example.com`, shared with `extract-emails`
+pub(crate) fn email_pattern() -> &'static Regex {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    EMAIL.get_or_init(|| {
+        Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}")
+            .expect("invalid built-in pattern")
+    })
+}
+
+// Between 7 and 15 digits, the most E.164 allows, and no ISO date or time
+fn is_phone(found: &str) -> bool {
+    static DATE: OnceLock<Regex> = OnceLock::new();
+    let date = DATE.get_or_init(|| {
+        Regex::new(r"^\d{4}-\d{2}-\d{2}|^\d{2}:\d{2}").expect("invalid built-in pattern")
+    });
+    let digits = found.chars().filter(char::is_ascii_digit).count();
+    (7..=15).contains(&digits) && !date.is_match(found)
+}
+
+fn masked(found: &str) -> String {
+    found
+        .chars()
+        .map(|c| if c.is_alphanumeric() { 'X' } else { c })
+        .collect()
+}
@@ -0,0 +1,351 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, Write};
+
+use rayon::slice::ParallelSliceMut;
+
+use crate::args::OperationArgs;
+use crate::input::open_source;
+use crate::streaming::StreamingOperation;
+use crate::{cancel, memory, Error};
+
+// Lines sorted in memory before they go to a temporary file as a sorted run,
+// when no memory budget is set
+const RUN_BYTES: usize = 256 << 20;
+
+// The most runs merged at once, more are merged in several passes to stay
+// under the limit of open files
+const MERGE_WIDTH: usize = 64;
+
+pub fn operation() -> StreamingOperation {
+    StreamingOperation::new(
+        "sort-lines",
+        &["sort"],
+        "Sorts the lines of a file, through temporary files when it is too large for memory",
+        write_sorted,
+    )
+}
+
+#[derive(Clone, Copy)]
+struct Order {
+    numeric: bool,
+    ignore_case: bool,
+    reverse: bool,
+}
+
+impl Order {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        let ordering = if self.numeric {
+            number(a)
+                .partial_cmp(&number(b))
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.cmp(b))
+        } else if self.ignore_case {
+            a.to_lowercase()
+                .cmp(&b.to_lowercase())
+                .then_with(|| a.cmp(b))
+        } else {
+            a.cmp(b)
+        };
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    // Whether `--unique` keeps only one of the lines
+    fn same(&self, a: &str, b: &str) -> bool {
+        if self.numeric {
+            number(a) == number(b) && number(a).is_some()
+        } else if self.ignore_case {
+            a.to_lowercase() == b.to_lowercase()
+        } else {
+            a == b
+        }
+    }
+}
+
+// The number a line starts with, lines without one sort before all numbers
+fn number(line: &str) -> Option<f64> {
+    let line = line.trim_start();
+    let end = line
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map_or(line.len(), |(i, _)| i);
+    line[..end].parse().ok().filter(|n: &f64| !n.is_nan())
+}
+
+// Sorts the file named in the input, `-` for stdin or with `--inline` the text
+// itself. `--numeric` compares the numbers lines start with, `--ignore-case`
+// ignores case, `--reverse` sorts the other way and `--unique` prints the first
+// of equal lines only. Input over the memory budget, 256M without one, is
+// sorted in runs written to temporary files and merged
+fn write_sorted(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let order = Order {
+        numeric: args.flag("numeric"),
+        ignore_case: args.flag("ignore-case"),
+        reverse: args.flag("reverse"),
+    };
+    let unique = args.flag("unique");
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(
+            "sort-lines requires a file, - for stdin or --inline TEXT",
+        ));
+    }
+    let run_bytes = memory::memory_budget().unwrap_or(RUN_BYTES);
+    let reader = BufReader::new(open_source(&source, inline)?);
+    sort_lines(reader, out, order, unique, run_bytes)
+}
+
+// Sorts the lines of the reader into `out`, in runs of about `run_bytes`
+fn sort_lines(
+    mut reader: impl BufRead,
+    out: &mut dyn Write,
+    order: Order,
+    unique: bool,
+    run_bytes: usize,
+) -> Result<(), Error> {
+    let mut lines = Vec::new();
+    let mut held = 0;
+    let mut runs = Vec::new();
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        let text = line.strip_suffix('\n').unwrap_or(&line);
+        let text = text.strip_suffix('\r').unwrap_or(text).to_string();
+        held += text.len() + size_of::<String>();
+        lines.push(text);
+        line.clear();
+        if held > run_bytes {
+            runs.push(write_run(&mut lines, order, unique)?);
+            held = 0;
+        }
+    }
+
+    let mut sink = Sink::new(out, order, unique);
+    if runs.is_empty() {
+        sort(&mut lines, order);
+        for line in lines {
+            sink.push(line)?;
+        }
+        return Ok(());
+    }
+    if !lines.is_empty() {
+        runs.push(write_run(&mut lines, order, unique)?);
+    }
+    while runs.len() > MERGE_WIDTH {
+        let rest = runs.split_off(MERGE_WIDTH);
+        let mut merged = BufWriter::new(tempfile::tempfile()?);
+        merge(
+            std::mem::take(&mut runs),
+            &mut Sink::new(&mut merged, order, unique),
+        )?;
+        runs = rest;
+        runs.push(rewound(merged)?);
+    }
+    merge(runs, &mut sink)
+}
+
+fn sort(lines: &mut [String], order: Order) {
+    lines.par_sort_by(|a, b| order.compare(a, b));
+}
+
+// Sorts the held lines into a temporary file, which is returned ready to be
+// read from the start
+fn write_run(lines: &mut Vec<String>, order: Order, unique: bool) -> Result<File, Error> {
+    cancel::check_timeout()?;
+    sort(lines, order);
+    let mut run = BufWriter::new(tempfile::tempfile()?);
+    let mut sink = Sink::new(&mut run, order, unique);
+    for line in lines.drain(..) {
+        sink.push(line)?;
+    }
+    rewound(run)
+}
+
+fn rewound(writer: BufWriter<File>) -> Result<File, Error> {
+    let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+    file.rewind()?;
+    Ok(file)
+}
+
+// Writes sorted lines, dropping the ones equal to the line before with `--unique`
+struct Sink<'a> {
+    out: &'a mut dyn Write,
+    order: Order,
+    unique: bool,
+    last: Option<String>,
+}
+
+impl<'a> Sink<'a> {
+    fn new(out: &'a mut dyn Write, order: Order, unique: bool) -> Sink<'a> {
+        Sink {
+            out,
+            order,
+            unique,
+            last: None,
+        }
+    }
+
+    fn push(&mut self, line: String) -> Result<(), Error> {
+        if self.unique {
+            if self
+                .last
+                .as_ref()
+                .is_some_and(|last| self.order.same(last, &line))
+            {
+                return Ok(());
+            }
+            writeln!(self.out, "{}", line)?;
+            self.last = Some(line);
+        } else {
+            writeln!(self.out, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+// The next line of a run in the merge, the heap gives the smallest one first
+struct Head {
+    line: String,
+    run: usize,
+    order: Order,
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Head) -> Ordering {
+        // Equal lines come from the earlier run first
+        self.order
+            .compare(&other.line, &self.line)
+            .then_with(|| other.run.cmp(&self.run))
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Head) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Head) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head {}
+
+// Merges sorted runs into one sorted sequence of lines
+fn merge(runs: Vec<File>, sink: &mut Sink) -> Result<(), Error> {
+    let mut readers: Vec<BufReader<File>> = runs.into_iter().map(BufReader::new).collect();
+    let mut heap = BinaryHeap::with_capacity(readers.len());
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(line) = next_line(reader)? {
+            heap.push(Head {
+                line,
+                run,
+                order: sink.order,
+            });
+        }
+    }
+    while let Some(mut head) = heap.pop() {
+        match next_line(&mut readers[head.run])? {
+            Some(next) => {
+                let line = std::mem::replace(&mut head.line, next);
+                heap.push(head);
+                sink.push(line)?;
+            }
+            None => sink.push(head.line)?,
+        }
+    }
+    Ok(())
+}
+
+fn next_line(reader: &mut BufReader<File>) -> Result<Option<String>, Error> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    line.pop();
+    Ok(Some(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDER: Order = Order {
+        numeric: false,
+        ignore_case: false,
+        reverse: false,
+    };
+
+    fn sorted(text: &str, order: Order, unique: bool, run_bytes: usize) -> String {
+        let mut out = Vec::new();
+        sort_lines(text.as_bytes(), &mut out, order, unique, run_bytes).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    // Lines in a scrambled order, with some of them twice
+    fn scrambled(count: usize) -> String {
+        (0..count)
+            .map(|i| format!("line {}\n", (i * 37) % (count / 2 + 1)))
+            .collect()
+    }
+
+    #[test]
+    fn runs_merge_into_the_order_of_an_in_memory_sort() {
+        let text = scrambled(500);
+        let expected = sorted(&text, ORDER, false, usize::MAX);
+        // A few runs, and one run per line, which takes several merge passes
+        for run_bytes in [2000, 1] {
+            assert_eq!(sorted(&text, ORDER, false, run_bytes), expected);
+        }
+        let unique = sorted(&text, ORDER, true, usize::MAX);
+        assert!(unique.lines().count() < expected.lines().count());
+        assert_eq!(sorted(&text, ORDER, true, 1), unique);
+    }
+
+    #[test]
+    fn reverse_ignore_case_and_numeric_orders_survive_merging() {
+        let text = "b\nA\n10 x\n9 y\na\n-1\nB\n";
+        for order in [
+            Order {
+                reverse: true,
+                ..ORDER
+            },
+            Order {
+                ignore_case: true,
+                ..ORDER
+            },
+            Order {
+                numeric: true,
+                ..ORDER
+            },
+        ] {
+            assert_eq!(
+                sorted(text, order, false, 1),
+                sorted(text, order, false, usize::MAX)
+            );
+        }
+        let numeric = Order {
+            numeric: true,
+            ..ORDER
+        };
+        assert_eq!(sorted("10\n9\n-1\n", numeric, false, 1), "-1\n9\n10\n");
+        let ignore_case = Order {
+            ignore_case: true,
+            ..ORDER
+        };
+        assert_eq!(sorted("b\nA\na\n", ignore_case, true, 1), "A\nb\n");
+    }
+
+    #[test]
+    fn line_endings_are_dropped() {
+        assert_eq!(sorted("b\r\na\r\nc", ORDER, false, 1), "a\nb\nc\n");
+    }
+}
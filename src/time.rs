@@ -0,0 +1,72 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A point in time as UTC calendar date and time of day, for timestamps without
+// a date library. Times before 1970 count as 1970-01-01
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UtcTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millisecond: u32,
+}
+
+impl UtcTime {
+    pub fn new(time: SystemTime) -> UtcTime {
+        let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = since.as_secs();
+        let (days, seconds) = (seconds / 86400, seconds % 86400);
+        // Civil date from days since the epoch, Howard Hinnant's algorithm
+        let z = days as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        UtcTime {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month: month as u32,
+            day: day as u32,
+            hour: (seconds / 3600) as u32,
+            minute: (seconds % 3600 / 60) as u32,
+            second: (seconds % 60) as u32,
+            millisecond: since.subsec_millis(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(seconds: u64, millis: u64) -> UtcTime {
+        UtcTime::new(UNIX_EPOCH + Duration::from_secs(seconds) + Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn dates_of_known_days() {
+        let parts = |t: UtcTime| (t.year, t.month, t.day, t.hour, t.minute, t.second);
+        assert_eq!(parts(at(0, 0)), (1970, 1, 1, 0, 0, 0));
+        // Leap days, also of the century year divisible by 400
+        assert_eq!(parts(at(951_782_400, 0)), (2000, 2, 29, 0, 0, 0));
+        assert_eq!(parts(at(1_709_210_096, 0)), (2024, 2, 29, 12, 34, 56));
+        assert_eq!(parts(at(1_798_761_599, 0)), (2026, 12, 31, 23, 59, 59));
+        assert_eq!(at(1_769_862_896, 789).millisecond, 789);
+    }
+
+    #[test]
+    fn times_before_the_epoch_are_the_epoch() {
+        assert_eq!(UtcTime::new(UNIX_EPOCH - Duration::from_secs(1)), at(0, 0));
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+// Where `Chunking::adaptive` starts and the range it stays in. Chunks are
+// measured in bytes, so neither many tiny lines nor a few huge ones make them
+// too small to spread over the workers or too large to hold
+const START_BYTES: usize = 1 << 20;
+const MIN_BYTES: usize = 16 << 10;
+const MAX_BYTES: usize = 64 << 20;
+
+// Throughput has to drop by more than this share before a change counts as
+// worse, chunk timings are noisy
+const TOLERANCE: f64 = 0.05;
+
+// Chunk size and parallelism of adaptive chunking. After every chunk one of
+// the two changes, and a change that made the throughput worse is followed by
+// one in the other direction, so both settle where the input runs fastest
+pub(crate) struct Tuner {
+    pub bytes: usize,
+    pub workers: usize,
+    max_workers: usize,
+    // Bytes per second of the chunk before
+    last: Option<f64>,
+    // Whether the size changes next rather than the workers
+    size_turn: bool,
+    grow_bytes: bool,
+    grow_workers: bool,
+}
+
+impl Tuner {
+    pub fn new() -> Tuner {
+        let max_workers = rayon::current_num_threads().max(1);
+        Tuner {
+            bytes: START_BYTES,
+            workers: max_workers,
+            max_workers,
+            last: None,
+            size_turn: true,
+            grow_bytes: true,
+            grow_workers: false,
+        }
+    }
+
+    // Takes the figures of a finished chunk and sets up the next one
+    pub fn record(&mut self, bytes: usize, elapsed: Duration) {
+        let throughput = bytes as f64 / elapsed.as_secs_f64().max(1e-9);
+        if self
+            .last
+            .is_some_and(|last| throughput < last * (1.0 - TOLERANCE))
+        {
+            // The last change made it worse, the same setting goes back
+            self.size_turn = !self.size_turn;
+            if self.size_turn {
+                self.grow_bytes = !self.grow_bytes;
+            } else {
+                self.grow_workers = !self.grow_workers;
+            }
+        }
+        self.last = Some(throughput);
+
+        if self.size_turn {
+            self.bytes = if self.grow_bytes {
+                (self.bytes * 2).min(MAX_BYTES)
+            } else {
+                (self.bytes / 2).max(MIN_BYTES)
+            };
+        } else {
+            self.workers = if self.grow_workers {
+                (self.workers + 1).min(self.max_workers)
+            } else {
+                self.workers.saturating_sub(1).max(1)
+            };
+        }
+        self.size_turn = !self.size_turn;
+    }
+}
@@ -0,0 +1,89 @@
+use std::io::{Read, Write};
+
+use quick_xml::events::Event;
+use quick_xml::{Reader, Writer};
+
+use crate::args::OperationArgs;
+use crate::input::open_source;
+use crate::streaming::StreamingOperation;
+use crate::Error;
+
+pub fn builtins() -> Vec<StreamingOperation> {
+    vec![StreamingOperation::new(
+        "xml-pretty",
+        &[],
+        "Reformats XML with consistent indentation",
+        write_xml_pretty,
+    )]
+}
+
+// Reindents the elements of the document by `--indent N` spaces, 2 by default,
+// or with `--tabs`. Whitespace around text is dropped, so content mixing text
+// and elements can change spacing
+pub fn write_xml_pretty(input: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut args = OperationArgs::parse(input);
+    let indent: Option<usize> = args.parsed("indent")?;
+    let tabs = args.flag("tabs");
+    let inline = args.flag("inline");
+    let source = args.finish()?.join(" ");
+    if source.is_empty() {
+        return Err(Error::invalid(
+            "xml-pretty requires an XML file, - for stdin or --inline TEXT",
+        ));
+    }
+    let (character, size) = match (tabs, indent) {
+        (true, Some(_)) => return Err(Error::invalid("--tabs and --indent can't be combined")),
+        (true, None) => (b'\t', 1),
+        (false, indent) => (b' ', indent.unwrap_or(2)),
+    };
+
+    let mut text = String::new();
+    open_source(&source, inline)?.read_to_string(&mut text)?;
+
+    let mut reader = Reader::from_str(&text);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(Vec::with_capacity(text.len()), character, size);
+    let mut depth = 0usize;
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            Error::invalid(format!(
+                "Invalid XML at {}: {}",
+                position(&text, reader.error_position()),
+                e
+            ))
+        })?;
+        match &event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            Event::Eof => break,
+            _ => {}
+        }
+        writer
+            .write_event(event)
+            .map_err(|e| Error::invalid(format!("Can't write the XML: {}", e)))?;
+    }
+    if depth > 0 {
+        return Err(Error::invalid(format!(
+            "Invalid XML, {} elements are not closed",
+            depth
+        )));
+    }
+
+    out.write_all(&writer.into_inner())?;
+    writeln!(out)?;
+    Ok(())
+}
+
+// `line L, column C` of a byte offset, which is what editors jump to
+fn position(text: &str, offset: u64) -> String {
+    let offset = (offset as usize).min(text.len());
+    let before = &text.as_bytes()[..offset];
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    let column = offset
+        - before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1)
+        + 1;
+    format!("line {}, column {}", line, column)
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const COMMANDS: &str = "lowercase HELLO\nuppercase abc\nslugify Hello World\nreverse nope\n";
+
+// Runs the commands on stdin with its own config directory, so nothing of the
+// user's is read or written
+fn run(config: &Path, args: &[&str]) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust-text-transformer"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", config)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(COMMANDS.as_bytes())
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn every_worker_writes_to_the_audit_log() {
+    for jobs in ["1", "4"] {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("audit.jsonl");
+        run(
+            dir.path(),
+            &["--jobs", jobs, "--audit-log", log.to_str().unwrap()],
+        );
+        let text = std::fs::read_to_string(&log).unwrap();
+        // The unknown operation is no processed command
+        assert_eq!(text.lines().count(), 3, "--jobs {}: {}", jobs, text);
+        for operation in ["lowercase", "uppercase", "slugify"] {
+            assert!(
+                text.contains(&format!("\"operation\":\"{}\"", operation)),
+                "--jobs {}: {}",
+                jobs,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn every_worker_counts_in_the_usage_stats() {
+    for jobs in ["1", "4"] {
+        let dir = tempfile::tempdir().unwrap();
+        run(dir.path(), &["--jobs", jobs]);
+        let stats = std::fs::read_to_string(dir.path().join("text-modifier/stats.json")).unwrap();
+        for operation in ["lowercase", "uppercase", "slugify"] {
+            assert!(
+                stats.contains(&format!("\"{}\":{{\"runs\":1,", operation)),
+                "--jobs {}: {}",
+                jobs,
+                stats
+            );
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+
+use rust_text_transformer::{text_operation, Error, OperationRegistry, TextOperation};
+
+#[text_operation(aliases("rev"), description = "Reverses the text")]
+fn reverse_text(input: &str) -> Result<String, Error> {
+    Ok(input.chars().rev().collect())
+}
+
+#[text_operation(
+    name = "count-args",
+    text = false,
+    description = "Counts the arguments"
+)]
+fn count(input: &str) -> Result<String, Error> {
+    Ok(input.split_whitespace().count().to_string())
+}
+
+#[derive(Debug)]
+struct Empty;
+
+impl fmt::Display for Empty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "nothing to check")
+    }
+}
+
+impl From<Empty> for Error {
+    fn from(e: Empty) -> Error {
+        Error::invalid(e.to_string())
+    }
+}
+
+#[text_operation(description = "Fails on empty input")]
+fn non_empty(input: &str) -> Result<String, Empty> {
+    if input.is_empty() {
+        return Err(Empty);
+    }
+    Ok(input.to_string())
+}
+
+#[test]
+fn declared_operations_are_in_the_default_registry() {
+    let registry = OperationRegistry::default();
+    let operation = registry.find("reverse-text").unwrap();
+    assert_eq!(operation.aliases(), ["rev"]);
+    assert!(operation.is_text_operation());
+    assert_eq!(registry.run("rev", "abc").unwrap().into_string(), "cba");
+
+    let operation = registry.find("count-args").unwrap();
+    assert!(!operation.is_text_operation());
+    assert_eq!(operation.apply("a b c").unwrap(), "3");
+}
+
+#[test]
+fn descriptions_are_listed_with_the_operations() {
+    // The listing of the command line and the REPL shows `describe()`
+    let operations = OperationRegistry::default().operations();
+    let reverse = operations
+        .iter()
+        .find(|operation| operation.name() == "reverse-text")
+        .unwrap();
+    assert_eq!(reverse.describe(), "Reverses the text");
+}
+
+#[test]
+fn errors_convert_into_error() {
+    let operation = NonEmptyOperation;
+    assert_eq!(operation.name(), "non-empty");
+    assert!(matches!(
+        operation.apply(""),
+        Err(Error::InvalidInput { reason }) if reason == "nothing to check"
+    ));
+}